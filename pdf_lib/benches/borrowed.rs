@@ -0,0 +1,76 @@
+#![feature(test)]
+extern crate test;
+
+use pdf_lib::{
+    PdfName, PdfNameRef, PdfStream, PdfStreamRef, PdfString, PdfStringRef, parse, parse_ref,
+};
+use test::{Bencher, black_box};
+
+const STREAM_LEN: usize = 1 << 20;
+
+fn names() -> Vec<u8> {
+    (0..10_000)
+        .flat_map(|i| format!("/SomeRatherLongName{i} ").into_bytes())
+        .collect()
+}
+
+fn strings() -> Vec<u8> {
+    (0..10_000)
+        .flat_map(|i| format!("(A literal string without escapes {i}) ").into_bytes())
+        .collect()
+}
+
+fn stream() -> Vec<u8> {
+    let mut bytes = format!("1 0 obj <</Length {STREAM_LEN}>> stream\n").into_bytes();
+    bytes.extend((0..STREAM_LEN).map(|i| b"0123456789abcdef"[i % 16]));
+    bytes.extend_from_slice(b"\nendstream\nendobj");
+    bytes
+}
+
+/// Parses `input` as a sequence of space separated values of type `T`.
+macro_rules! parse_all {
+    ($parse:ident, $T:ty, $input:expr) => {{
+        let mut bytes: &[u8] = $input;
+        while !bytes.is_empty() {
+            let (value, rest) = $parse::<$T>(bytes).unwrap();
+            black_box(value);
+            bytes = &rest[1..];
+        }
+    }};
+}
+
+#[bench]
+fn names_owned(b: &mut Bencher) {
+    let input = names();
+    b.iter(|| parse_all!(parse, PdfName, &input));
+}
+
+#[bench]
+fn names_borrowed(b: &mut Bencher) {
+    let input = names();
+    b.iter(|| parse_all!(parse_ref, PdfNameRef, &input));
+}
+
+#[bench]
+fn strings_owned(b: &mut Bencher) {
+    let input = strings();
+    b.iter(|| parse_all!(parse, PdfString, &input));
+}
+
+#[bench]
+fn strings_borrowed(b: &mut Bencher) {
+    let input = strings();
+    b.iter(|| parse_all!(parse_ref, PdfStringRef, &input));
+}
+
+#[bench]
+fn stream_owned(b: &mut Bencher) {
+    let input = stream();
+    b.iter(|| black_box(parse::<PdfStream>(&input).unwrap()));
+}
+
+#[bench]
+fn stream_borrowed(b: &mut Bencher) {
+    let input = stream();
+    b.iter(|| black_box(parse_ref::<PdfStreamRef>(&input).unwrap()));
+}
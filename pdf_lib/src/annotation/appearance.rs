@@ -0,0 +1,475 @@
+use crate::{
+    Annotation, AnnotationType, BorderLine, BorderStyle, Color, IndirectData, PdfArray, PdfDict,
+    PdfFile, PdfName, PdfNumeric, PdfObject, PdfRect, PdfStream, Quad, content::ContentBuilder,
+    font::Base14Font, pdf_error::PdfResult,
+};
+
+/// Space between the border and the text of free text annotations.
+pub(crate) const TEXT_PADDING: f64 = 2.0;
+/// Line height of generated text, in font sizes.
+pub(crate) const LINE_HEIGHT: f64 = 1.2;
+/// Height of the glyphs above the baseline, in font sizes, to place the first line of text.
+pub(crate) const ASCENT: f64 = 0.8;
+
+/// Font, size and colour of the `/DA` default appearance string of free text annotations and
+/// form fields (12.7.4.3).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DefaultAppearance {
+    /// Name of the font in the `/Font` resources.
+    pub font: Vec<u8>,
+    /// Font size, 0 for text sized to fit.
+    pub size: f64,
+    pub color: Color,
+}
+
+impl Default for DefaultAppearance {
+    fn default() -> Self {
+        Self {
+            font: b"Helv".to_vec(),
+            size: 0.0,
+            color: Color::Gray(0.0),
+        }
+    }
+}
+
+impl DefaultAppearance {
+    /// Reads the last `Tf` and colour operators of `da`, others being ignored.
+    pub fn parse(da: &[u8]) -> Self {
+        let mut appearance = Self::default();
+        let mut operands: Vec<&[u8]> = Vec::new();
+        for token in da
+            .split(|b| b.is_ascii_whitespace())
+            .filter(|t| !t.is_empty())
+        {
+            match token {
+                b"Tf" => {
+                    if let [.., font, size] = operands[..]
+                        && let Some(font) = font.strip_prefix(b"/")
+                        && let Some(size) =
+                            std::str::from_utf8(size).ok().and_then(|s| s.parse().ok())
+                    {
+                        appearance.font = font.to_vec();
+                        appearance.size = size;
+                    }
+                }
+                b"g" | b"rg" | b"k" => {
+                    let count = match token {
+                        b"g" => 1,
+                        b"rg" => 3,
+                        _ => 4,
+                    };
+                    let start = operands.len().saturating_sub(count);
+                    let numbers: Option<Vec<f64>> = operands[start..]
+                        .iter()
+                        .map(|o| std::str::from_utf8(o).ok()?.parse().ok())
+                        .collect();
+                    if let Some(numbers) = numbers
+                        && let Ok(Some(color)) =
+                            Color::from_array(&PdfArray::from_numbers(&numbers))
+                    {
+                        appearance.color = color;
+                    }
+                }
+                _ => {
+                    operands.push(token);
+                    continue;
+                }
+            }
+            operands.clear();
+        }
+        appearance
+    }
+
+    /// The base 14 font the font name stands for, Helvetica for unknown names.
+    ///
+    /// Besides base font names, the names that form authoring tools give base 14 fonts in
+    /// `/DR` are recognized.
+    pub fn base14(&self) -> Base14Font {
+        let abbreviation = match &self.font[..] {
+            b"Helv" => Some(Base14Font::Helvetica),
+            b"HeBo" => Some(Base14Font::HelveticaBold),
+            b"HeOb" => Some(Base14Font::HelveticaOblique),
+            b"HeBO" => Some(Base14Font::HelveticaBoldOblique),
+            b"TiRo" => Some(Base14Font::TimesRoman),
+            b"TiBo" => Some(Base14Font::TimesBold),
+            b"TiIt" => Some(Base14Font::TimesItalic),
+            b"TiBI" => Some(Base14Font::TimesBoldItalic),
+            b"Cour" => Some(Base14Font::Courier),
+            b"CoBo" => Some(Base14Font::CourierBold),
+            b"CoOb" => Some(Base14Font::CourierOblique),
+            b"CoBO" => Some(Base14Font::CourierBoldOblique),
+            b"Symb" => Some(Base14Font::Symbol),
+            b"ZaDb" => Some(Base14Font::ZapfDingbats),
+            _ => None,
+        };
+        abbreviation
+            .or_else(|| Base14Font::from_name(&self.font))
+            .unwrap_or(Base14Font::Helvetica)
+    }
+}
+
+/// Content and resources of an appearance stream, before it is added as a Form XObject.
+#[derive(Default)]
+pub(crate) struct Appearance {
+    pub content: ContentBuilder,
+    pub resources: PdfDict,
+}
+
+impl Appearance {
+    /// Adds the font dictionary or reference `font` to the `/Font` resources as `name`.
+    pub fn add_font(&mut self, name: &[u8], font: impl Into<PdfObject>) {
+        let fonts = self
+            .resources
+            .entry(PdfName::from_raw_bytes(b"Font"))
+            .or_insert_with(PdfDict::empty);
+        if let Ok(fonts) = fonts.as_dict_mut() {
+            fonts.insert(PdfName::from_raw_bytes(name), font);
+        }
+    }
+
+    /// Adds a graphics state with `entries` to the `/ExtGState` resources as `name`.
+    pub fn add_graphics_state(&mut self, name: &[u8], entries: PdfDict) {
+        let states = self
+            .resources
+            .entry(PdfName::from_raw_bytes(b"ExtGState"))
+            .or_insert_with(PdfDict::empty);
+        if let Ok(states) = states.as_dict_mut() {
+            states.insert(PdfName::from_raw_bytes(name), entries);
+        }
+    }
+
+    /// Adds the Form XObject of the appearance, drawn in the coordinates of `bbox`.
+    pub fn write(self, file: &mut PdfFile, bbox: &PdfRect) -> IndirectData {
+        let mut dict = PdfDict::empty();
+        dict.insert(
+            PdfName::from_raw_bytes(b"Type"),
+            PdfName::from_raw_bytes(b"XObject"),
+        );
+        dict.insert(
+            PdfName::from_raw_bytes(b"Subtype"),
+            PdfName::from_raw_bytes(b"Form"),
+        );
+        dict.insert(PdfName::from_raw_bytes(b"BBox"), PdfArray::from(*bbox));
+        dict.insert(PdfName::from_raw_bytes(b"Resources"), self.resources);
+        file.add_object(PdfStream::new(dict, self.content.finish()))
+    }
+}
+
+/// Sets the border width and dash pattern of `style`, `false` if it draws no border.
+pub(crate) fn border(content: &mut ContentBuilder, style: &BorderStyle) -> bool {
+    if style.width <= 0.0 {
+        return false;
+    }
+    content.line_width(style.width);
+    if let BorderLine::Dashed(dashes) = &style.line {
+        content.dash(dashes, 0.0);
+    }
+    true
+}
+
+pub(crate) fn inset(rect: &PdfRect, by: f64) -> PdfRect {
+    let by = by.min(rect.width() / 2.0).min(rect.height() / 2.0);
+    PdfRect::new(rect.llx + by, rect.lly + by, rect.urx - by, rect.ury - by)
+}
+
+/// Point at `t` along the segment from `a` to `b`.
+fn between(a: (f64, f64), b: (f64, f64), t: f64) -> (f64, f64) {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// Strokes a line across each quadrilateral, at `height` from its bottom in heights of the
+/// quadrilateral, with a width proportional to the height.
+fn quad_lines(content: &mut ContentBuilder, quads: &[Quad], height: f64) {
+    for quad in quads {
+        let [upper_left, upper_right, lower_left, lower_right] = quad.points;
+        let quad_height = distance(upper_left, lower_left);
+        let start = between(lower_left, upper_left, height);
+        let end = between(lower_right, upper_right, height);
+        content
+            .line_width((quad_height / 14.0).max(0.5))
+            .move_to(start.0, start.1)
+            .line_to(end.0, end.1)
+            .stroke();
+    }
+}
+
+impl Annotation {
+    /// The appearance generated from the properties of the annotation, `None` for types drawn
+    /// otherwise.
+    fn generated_appearance(&self) -> PdfResult<Option<Appearance>> {
+        let mut appearance = Appearance::default();
+        let rect = self.rect()?;
+        let mut graphics_state = PdfDict::empty();
+        if let Some(opacity) = self.dict().get(b"CA") {
+            let opacity = opacity.as_numeric_ref()?.to_f64()?;
+            graphics_state.insert(PdfName::from_raw_bytes(b"CA"), PdfNumeric::from(opacity));
+            graphics_state.insert(PdfName::from_raw_bytes(b"ca"), PdfNumeric::from(opacity));
+        }
+        let subtype = self.subtype()?;
+        if subtype == AnnotationType::Highlight {
+            // Multiplied so that the marked text shows through
+            graphics_state.insert(
+                PdfName::from_raw_bytes(b"BM"),
+                PdfName::from_raw_bytes(b"Multiply"),
+            );
+        }
+        let content = &mut appearance.content;
+        if !graphics_state.is_empty() {
+            content.graphics_state(b"GS0");
+        }
+
+        let color = self.color()?;
+        match subtype {
+            AnnotationType::Highlight => {
+                content.fill_color(&color.unwrap_or(Color::Rgb(1.0, 1.0, 0.0)));
+                for quad in self.quad_points()? {
+                    let [upper_left, upper_right, lower_left, lower_right] = quad.points;
+                    content
+                        .move_to(upper_left.0, upper_left.1)
+                        .line_to(upper_right.0, upper_right.1)
+                        .line_to(lower_right.0, lower_right.1)
+                        .line_to(lower_left.0, lower_left.1)
+                        .close_path()
+                        .fill();
+                }
+            }
+            AnnotationType::Underline => {
+                content.stroke_color(&color.unwrap_or(Color::Gray(0.0)));
+                quad_lines(content, &self.quad_points()?, 0.1);
+            }
+            AnnotationType::StrikeOut => {
+                content.stroke_color(&color.unwrap_or(Color::Gray(0.0)));
+                quad_lines(content, &self.quad_points()?, 0.5);
+            }
+            AnnotationType::Square | AnnotationType::Circle => {
+                let style = self.border_style()?;
+                let stroked = color.is_some() && border(content, &style);
+                let interior = self.interior_color()?;
+                if let Some(color) = &color {
+                    content.stroke_color(color);
+                }
+                if let Some(interior) = &interior {
+                    content.fill_color(interior);
+                }
+                let shape = inset(&rect, if stroked { style.width / 2.0 } else { 0.0 });
+                if subtype == AnnotationType::Square {
+                    content.rect(&shape);
+                } else {
+                    content.ellipse(&shape);
+                }
+                match (stroked, interior.is_some()) {
+                    (true, true) => content.fill_and_stroke(),
+                    (true, false) => content.stroke(),
+                    (false, true) => content.fill(),
+                    (false, false) => content.op(&[], "n"),
+                };
+            }
+            AnnotationType::Ink => {
+                content.stroke_color(&color.unwrap_or(Color::Gray(0.0)));
+                if border(content, &self.border_style()?) {
+                    content.round_lines();
+                    for path in self.ink_list()? {
+                        let Some(((x, y), rest)) = path.split_first() else {
+                            continue;
+                        };
+                        content.move_to(*x, *y);
+                        for (x, y) in rest {
+                            content.line_to(*x, *y);
+                        }
+                        content.stroke();
+                    }
+                }
+            }
+            AnnotationType::FreeText => {
+                let da = match self.dict().get(b"DA") {
+                    Some(da) => DefaultAppearance::parse(da.as_string_ref()?.bytes()),
+                    None => DefaultAppearance::default(),
+                };
+                let style = self.border_style()?;
+                if let Some(background) = &color {
+                    content.fill_color(background).rect(&rect).fill();
+                }
+                let stroked = border(content, &style);
+                if stroked {
+                    content
+                        .stroke_color(&da.color)
+                        .rect(&inset(&rect, style.width / 2.0))
+                        .stroke();
+                }
+                let text = self.contents()?.unwrap_or_default();
+                let width = if stroked { style.width } else { 0.0 };
+                let area = inset(&rect, width + TEXT_PADDING);
+                let size = if da.size > 0.0 { da.size } else { 12.0 };
+                let font = da.base14();
+                content.rect(&area).clip();
+                appearance.add_font(&da.font, font.font_dict());
+                let content = &mut appearance.content;
+                content
+                    .begin_text()
+                    .font(&da.font, size)
+                    .fill_color(&da.color)
+                    .next_line_at(area.llx, area.ury - size * ASCENT);
+                for (i, line) in font.wrap(&text, size, area.width()).iter().enumerate() {
+                    if i > 0 {
+                        content.next_line_at(0.0, -size * LINE_HEIGHT);
+                    }
+                    content.show_text(&font.encode(line));
+                }
+                content.end_text();
+            }
+            AnnotationType::Stamp => {
+                let color = color.unwrap_or(Color::Rgb(0.8, 0.0, 0.0));
+                let width = (rect.height() / 15.0).max(1.0);
+                content
+                    .stroke_color(&color)
+                    .line_width(width)
+                    .rect(&inset(&rect, width / 2.0))
+                    .stroke();
+                let name = match self.dict().get_name(b"Name")? {
+                    Some(name) => String::from_utf8_lossy(name.bytes()).into_owned(),
+                    None => "Draft".to_string(),
+                };
+                let text = name.to_uppercase();
+                let font = Base14Font::HelveticaBold;
+                let area = inset(&rect, width * 2.0);
+                let size = (area.height() * 0.7)
+                    .min(area.width() / font.text_width(&text, 1.0).max(f64::EPSILON));
+                appearance.add_font(b"HeBo", font.font_dict());
+                let x = area.llx + (area.width() - font.text_width(&text, size)) / 2.0;
+                let y = area.lly + (area.height() - size * ASCENT) / 2.0;
+                appearance
+                    .content
+                    .begin_text()
+                    .font(b"HeBo", size)
+                    .fill_color(&color)
+                    .next_line_at(x, y)
+                    .show_text(&font.encode(&text))
+                    .end_text();
+            }
+            AnnotationType::Text => {
+                // A note icon: a sheet with lines of text
+                let sheet = inset(&rect, 0.5);
+                content
+                    .line_width(1.0)
+                    .stroke_color(&Color::Gray(0.0))
+                    .fill_color(&color.unwrap_or(Color::Rgb(1.0, 0.9, 0.3)))
+                    .rect(&sheet)
+                    .fill_and_stroke();
+                for i in 1..4 {
+                    let y = sheet.ury - sheet.height() * f64::from(i) / 4.0;
+                    let margin = sheet.width() / 5.0;
+                    content
+                        .move_to(sheet.llx + margin, y)
+                        .line_to(sheet.urx - margin, y)
+                        .stroke();
+                }
+            }
+            _ => return Ok(None),
+        }
+        if !graphics_state.is_empty() {
+            appearance.add_graphics_state(b"GS0", graphics_state);
+        }
+        Ok(Some(appearance))
+    }
+}
+
+impl PdfFile<'_> {
+    /// Generates the normal appearance of `annotation` from its properties, for viewers that
+    /// do not draw annotations themselves, and sets it as its `/AP /N`.
+    ///
+    /// Returns `false` for links, widgets and other types left as they are. A previous
+    /// appearance stream is left for clean-up passes to remove; the annotation still has to be
+    /// added or written back.
+    pub fn generate_appearance(&mut self, annotation: &mut Annotation) -> PdfResult<bool> {
+        let Some(appearance) = annotation.generated_appearance()? else {
+            return Ok(false);
+        };
+        let stream = appearance.write(self, &annotation.rect()?);
+        let mut ap = PdfDict::empty();
+        ap.insert(PdfName::from_raw_bytes(b"N"), stream);
+        annotation
+            .dict_mut()
+            .insert(PdfName::from_raw_bytes(b"AP"), ap);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DefaultAppearance;
+    use crate::{
+        Annotation, AnnotationType, Color, PdfFile, PdfName, PdfRect, PdfString, Quad, Resolve,
+        font::Base14Font, pdf_file::test_file,
+    };
+
+    #[test]
+    fn default_appearance() {
+        let da = DefaultAppearance::parse(b"0 0 1 rg /TiBo 9.5 Tf");
+        assert_eq!(da.font, b"TiBo");
+        assert_eq!(da.size, 9.5);
+        assert_eq!(da.color, Color::Rgb(0.0, 0.0, 1.0));
+        assert_eq!(da.base14(), Base14Font::TimesBold);
+        assert_eq!(
+            DefaultAppearance::parse(b"garbage Tf"),
+            DefaultAppearance::default()
+        );
+    }
+
+    fn appearance(file: &PdfFile, annotation: &Annotation) -> (PdfRect, String) {
+        let ap = annotation.dict().get_dict(b"AP").unwrap().unwrap();
+        let stream = file
+            .deref(ap.get_or_null(b"N"))
+            .unwrap()
+            .as_stream()
+            .unwrap();
+        let bbox = PdfRect::try_from(stream.info().get_or_null(b"BBox")).unwrap();
+        (bbox, String::from_utf8(stream.data().to_vec()).unwrap())
+    }
+
+    #[test]
+    fn generate() {
+        let bytes = test_file::build(&["<< /Type /Catalog >>"], "/Root 1 0 R");
+        let mut file = PdfFile::load(&bytes).unwrap();
+
+        let rect = PdfRect::new(10.0, 10.0, 110.0, 22.0);
+        let mut highlight = Annotation::text_markup(
+            AnnotationType::Highlight,
+            &[Quad::from_rect(&rect)],
+            Color::Rgb(1.0, 1.0, 0.0),
+        );
+        assert_eq!(file.generate_appearance(&mut highlight), Ok(true));
+        let (bbox, content) = appearance(&file, &highlight);
+        assert_eq!(bbox, rect);
+        assert_eq!(
+            content,
+            "/GS0 gs\n1 1 0 rg\n10 22 m\n110 22 l\n110 10 l\n10 10 l\nh\nf\n"
+        );
+
+        let mut square = Annotation::new(AnnotationType::Square, rect);
+        square.set_color(Some(Color::Gray(0.0)));
+        square.set_interior_color(Some(Color::Gray(0.5)));
+        file.generate_appearance(&mut square).unwrap();
+        let (_, content) = appearance(&file, &square);
+        assert!(content.ends_with("10.5 10.5 99 11 re\nB\n"), "{content}");
+
+        let mut free_text = Annotation::new(AnnotationType::FreeText, rect);
+        free_text.set_contents(Some("Hello world"));
+        free_text.dict_mut().insert(
+            PdfName::from_raw_bytes(b"DA"),
+            PdfString::from_raw_bytes(b"/Cour 10 Tf 1 0 0 rg"),
+        );
+        file.generate_appearance(&mut free_text).unwrap();
+        let (_, content) = appearance(&file, &free_text);
+        assert!(content.contains("/Cour 10 Tf\n1 0 0 rg\n"), "{content}");
+        // 6 units per character leave room for 15 characters a line
+        assert!(content.contains("(Hello world) Tj"), "{content}");
+
+        let mut link = Annotation::new(AnnotationType::Link, rect);
+        assert_eq!(file.generate_appearance(&mut link), Ok(false));
+        assert!(link.dict().get(b"AP").is_none());
+    }
+}
@@ -0,0 +1,713 @@
+mod appearance;
+
+pub(crate) use appearance::{
+    ASCENT, Appearance, DefaultAppearance, LINE_HEIGHT, TEXT_PADDING, border, inset,
+};
+
+use crate::{
+    Destination, IndirectData, PdfArray, PdfDate, PdfDict, PdfFile, PdfName, PdfNumeric, PdfObject,
+    PdfRect, PdfString, Resolve,
+    pages::Page,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+};
+
+/// Entries read by [`Annotation`], resolved when the annotation is loaded so that its getters
+/// need no file.
+const RESOLVED: [&[u8]; 11] = [
+    b"Rect",
+    b"Contents",
+    b"C",
+    b"IC",
+    b"F",
+    b"QuadPoints",
+    b"BS",
+    b"Border",
+    b"T",
+    b"M",
+    b"InkList",
+];
+
+/// The names of all annotation subtypes (12.5.6).
+pub(crate) const SUBTYPES: [&[u8]; 28] = [
+    b"Text",
+    b"Link",
+    b"FreeText",
+    b"Line",
+    b"Square",
+    b"Circle",
+    b"Polygon",
+    b"PolyLine",
+    b"Highlight",
+    b"Underline",
+    b"Squiggly",
+    b"StrikeOut",
+    b"Caret",
+    b"Stamp",
+    b"Ink",
+    b"Popup",
+    b"FileAttachment",
+    b"Sound",
+    b"Movie",
+    b"Screen",
+    b"Widget",
+    b"PrinterMark",
+    b"TrapNet",
+    b"Watermark",
+    b"3D",
+    b"Redact",
+    b"Projection",
+    b"RichMedia",
+];
+
+/// Annotation subtypes (12.5.6), those without their own variant kept by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotationType {
+    Link,
+    Text,
+    Highlight,
+    Underline,
+    StrikeOut,
+    FreeText,
+    Square,
+    Circle,
+    Ink,
+    Stamp,
+    Widget,
+    Other(PdfName),
+}
+
+const NAMED_TYPES: [(AnnotationType, &[u8]); 11] = [
+    (AnnotationType::Link, b"Link"),
+    (AnnotationType::Text, b"Text"),
+    (AnnotationType::Highlight, b"Highlight"),
+    (AnnotationType::Underline, b"Underline"),
+    (AnnotationType::StrikeOut, b"StrikeOut"),
+    (AnnotationType::FreeText, b"FreeText"),
+    (AnnotationType::Square, b"Square"),
+    (AnnotationType::Circle, b"Circle"),
+    (AnnotationType::Ink, b"Ink"),
+    (AnnotationType::Stamp, b"Stamp"),
+    (AnnotationType::Widget, b"Widget"),
+];
+
+impl AnnotationType {
+    pub fn from_name(name: &PdfName) -> Self {
+        NAMED_TYPES
+            .into_iter()
+            .find(|(_, n)| *n == name.bytes())
+            .map_or_else(|| Self::Other(name.clone()), |(t, _)| t)
+    }
+
+    pub fn name(&self) -> PdfName {
+        match self {
+            Self::Other(name) => name.clone(),
+            _ => {
+                let (_, name) = NAMED_TYPES.iter().find(|(t, _)| t == self).unwrap();
+                PdfName::from_raw_bytes(name)
+            }
+        }
+    }
+
+    /// Whether the annotation marks up text given by `/QuadPoints`.
+    pub fn is_text_markup(&self) -> bool {
+        matches!(self, Self::Highlight | Self::Underline | Self::StrikeOut)
+    }
+}
+
+/// Annotation flags, `/F` (12.5.3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AnnotationFlags(u32);
+
+impl AnnotationFlags {
+    pub const INVISIBLE: Self = Self(1);
+    pub const HIDDEN: Self = Self(1 << 1);
+    pub const PRINT: Self = Self(1 << 2);
+    pub const NO_ZOOM: Self = Self(1 << 3);
+    pub const NO_ROTATE: Self = Self(1 << 4);
+    pub const NO_VIEW: Self = Self(1 << 5);
+    pub const READ_ONLY: Self = Self(1 << 6);
+    pub const LOCKED: Self = Self(1 << 7);
+    pub const TOGGLE_NO_VIEW: Self = Self(1 << 8);
+    pub const LOCKED_CONTENTS: Self = Self(1 << 9);
+
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl std::ops::BitOr for AnnotationFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// Colour of an annotation, in the colour space given by its number of components.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Gray(f64),
+    Rgb(f64, f64, f64),
+    Cmyk(f64, f64, f64, f64),
+}
+
+impl Color {
+    /// Reads a colour array, `None` for the empty array of transparent colours.
+    pub fn from_array(array: &PdfArray) -> PdfResult<Option<Self>> {
+        Ok(match array.as_numbers()?[..] {
+            [] => None,
+            [g] => Some(Self::Gray(g)),
+            [r, g, b] => Some(Self::Rgb(r, g, b)),
+            [c, m, y, k] => Some(Self::Cmyk(c, m, y, k)),
+            _ => {
+                return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                    .expecting("a colour of 0, 1, 3 or 4 components"));
+            }
+        })
+    }
+
+    pub fn components(&self) -> Vec<f64> {
+        match *self {
+            Self::Gray(g) => vec![g],
+            Self::Rgb(r, g, b) => vec![r, g, b],
+            Self::Cmyk(c, m, y, k) => vec![c, m, y, k],
+        }
+    }
+}
+
+impl From<Color> for PdfArray {
+    fn from(color: Color) -> Self {
+        PdfArray::from_numbers(&color.components())
+    }
+}
+
+/// Quadrilateral of `/QuadPoints`, its corners in the order upper left, upper right, lower left,
+/// lower right of the text it covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quad {
+    pub points: [(f64, f64); 4],
+}
+
+impl Quad {
+    /// The quadrilateral of upright text covering `rect`.
+    pub fn from_rect(rect: &PdfRect) -> Self {
+        Self {
+            points: [
+                (rect.llx, rect.ury),
+                (rect.urx, rect.ury),
+                (rect.llx, rect.lly),
+                (rect.urx, rect.lly),
+            ],
+        }
+    }
+
+    /// Smallest rectangle holding the corners.
+    pub fn bounds(&self) -> PdfRect {
+        let (xs, ys): (Vec<_>, Vec<_>) = self.points.into_iter().unzip();
+        let min = |v: &[f64]| v.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = |v: &[f64]| v.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        PdfRect::new(min(&xs), min(&ys), max(&xs), max(&ys))
+    }
+}
+
+/// Line style of a border (12.5.4).
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorderLine {
+    Solid,
+    /// Dashes and gaps, in user space units.
+    Dashed(Vec<f64>),
+    Beveled,
+    Inset,
+    Underline,
+}
+
+/// Border of an annotation, from its `/BS` border style or its older `/Border` array.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BorderStyle {
+    /// Width in user space units, 0 drawing no border.
+    pub width: f64,
+    pub line: BorderLine,
+}
+
+impl Default for BorderStyle {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            line: BorderLine::Solid,
+        }
+    }
+}
+
+impl BorderStyle {
+    fn from_dict(dict: &PdfDict) -> PdfResult<Self> {
+        let width = match dict.get(b"W") {
+            Some(width) => width.as_numeric_ref()?.to_f64()?,
+            None => 1.0,
+        };
+        let line = match dict.get_name(b"S")?.map(PdfName::bytes) {
+            Some(b"D") => BorderLine::Dashed(match dict.get_array(b"D")? {
+                Some(dash) => dash.as_numbers()?,
+                None => vec![3.0],
+            }),
+            Some(b"B") => BorderLine::Beveled,
+            Some(b"I") => BorderLine::Inset,
+            Some(b"U") => BorderLine::Underline,
+            _ => BorderLine::Solid,
+        };
+        Ok(Self { width, line })
+    }
+
+    /// Reads `[horizontal_radius vertical_radius width dash]`, the corner radii being dropped.
+    fn from_border_array(array: &PdfArray) -> PdfResult<Self> {
+        let width = match array.get(2) {
+            Some(width) => width.as_numeric_ref()?.to_f64()?,
+            None => 1.0,
+        };
+        let line = match array.get(3) {
+            Some(dash) => BorderLine::Dashed(dash.as_array_ref()?.as_numbers()?),
+            None => BorderLine::Solid,
+        };
+        Ok(Self { width, line })
+    }
+
+    fn to_dict(&self) -> PdfDict {
+        let mut dict = PdfDict::empty();
+        dict.insert(
+            PdfName::from_raw_bytes(b"Type"),
+            PdfName::from_raw_bytes(b"Border"),
+        );
+        dict.insert(PdfName::from_raw_bytes(b"W"), PdfNumeric::from(self.width));
+        let style: &[u8] = match &self.line {
+            BorderLine::Solid => b"S",
+            BorderLine::Dashed(dash) => {
+                dict.insert(PdfName::from_raw_bytes(b"D"), PdfArray::from_numbers(dash));
+                b"D"
+            }
+            BorderLine::Beveled => b"B",
+            BorderLine::Inset => b"I",
+            BorderLine::Underline => b"U",
+        };
+        dict.insert(
+            PdfName::from_raw_bytes(b"S"),
+            PdfName::from_raw_bytes(style),
+        );
+        dict
+    }
+}
+
+/// An annotation dictionary, with typed access to the entries common to its subtypes.
+///
+/// Entries without typed access are read and changed through [`Annotation::dict`] and
+/// [`Annotation::dict_mut`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    reference: Option<IndirectData>,
+    dict: PdfDict,
+}
+
+impl Annotation {
+    /// A new annotation of type `subtype` covering `rect`, printed with the page.
+    pub fn new(subtype: AnnotationType, rect: PdfRect) -> Self {
+        let mut dict = PdfDict::empty();
+        dict.insert(
+            PdfName::from_raw_bytes(b"Type"),
+            PdfName::from_raw_bytes(b"Annot"),
+        );
+        dict.insert(PdfName::from_raw_bytes(b"Subtype"), subtype.name());
+        dict.insert(PdfName::from_raw_bytes(b"Rect"), PdfArray::from(rect));
+        let mut annotation = Self {
+            reference: None,
+            dict,
+        };
+        annotation.set_flags(AnnotationFlags::PRINT);
+        annotation
+    }
+
+    /// A new text markup annotation, such as a highlight, over `quads` in `color`.
+    pub fn text_markup(subtype: AnnotationType, quads: &[Quad], color: Color) -> Self {
+        let mut annotation = Self::new(subtype, PdfRect::default());
+        annotation.set_quad_points(quads);
+        annotation.set_color(Some(color));
+        annotation
+    }
+
+    /// An annotation read from `dict`, the object `reference` if it has its own.
+    pub(crate) fn from_dict(reference: Option<IndirectData>, dict: PdfDict) -> Self {
+        Self { reference, dict }
+    }
+
+    /// The annotation object, `None` for annotations not yet added and those written directly
+    /// in `/Annots`.
+    pub fn reference(&self) -> Option<&IndirectData> {
+        self.reference.as_ref()
+    }
+    pub fn dict(&self) -> &PdfDict {
+        &self.dict
+    }
+    pub fn dict_mut(&mut self) -> &mut PdfDict {
+        &mut self.dict
+    }
+
+    pub fn subtype(&self) -> PdfResult<AnnotationType> {
+        let subtype = self.dict.get_name(b"Subtype")?.ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::WrongType).expecting("an annotation /Subtype")
+        })?;
+        Ok(AnnotationType::from_name(subtype))
+    }
+
+    pub fn rect(&self) -> PdfResult<PdfRect> {
+        PdfRect::try_from(self.dict.get_or_null(b"Rect"))
+    }
+    pub fn set_rect(&mut self, rect: PdfRect) {
+        self.set(b"Rect", Some(PdfArray::from(rect)));
+    }
+
+    /// The text shown for the annotation, or describing it for those showing none.
+    pub fn contents(&self) -> PdfResult<Option<String>> {
+        self.text(b"Contents")
+    }
+    pub fn set_contents(&mut self, contents: Option<&str>) {
+        self.set(b"Contents", contents.map(PdfString::from_text));
+    }
+
+    /// The author of the annotation, `/T`.
+    pub fn author(&self) -> PdfResult<Option<String>> {
+        self.text(b"T")
+    }
+    pub fn set_author(&mut self, author: Option<&str>) {
+        self.set(b"T", author.map(PdfString::from_text));
+    }
+
+    /// When the annotation was last modified, `None` if absent or not a date, which the
+    /// specification allows.
+    pub fn modified(&self) -> Option<PdfDate> {
+        self.dict.get_date(b"M").ok().flatten()
+    }
+    pub fn set_modified(&mut self, date: Option<PdfDate>) {
+        self.set(b"M", date.map(PdfString::from));
+    }
+
+    /// The colour of the annotation's border, background or icon, depending on its type.
+    pub fn color(&self) -> PdfResult<Option<Color>> {
+        self.color_at(b"C")
+    }
+    pub fn set_color(&mut self, color: Option<Color>) {
+        self.set(b"C", color.map(PdfArray::from));
+    }
+
+    /// The colour filling squares and circles, `/IC`.
+    pub fn interior_color(&self) -> PdfResult<Option<Color>> {
+        self.color_at(b"IC")
+    }
+    pub fn set_interior_color(&mut self, color: Option<Color>) {
+        self.set(b"IC", color.map(PdfArray::from));
+    }
+
+    pub fn flags(&self) -> PdfResult<AnnotationFlags> {
+        let flags = self.dict.get_int(b"F")?.unwrap_or(0);
+        // Bits past the 32 defined are ignored
+        Ok(AnnotationFlags::from_bits(flags as u32))
+    }
+    pub fn set_flags(&mut self, flags: AnnotationFlags) {
+        let flags = (flags.bits() != 0).then(|| PdfNumeric::PdfInt(flags.bits().into()));
+        self.set(b"F", flags);
+    }
+
+    /// The quadrilaterals covered by a text markup or link annotation, empty if there are none.
+    pub fn quad_points(&self) -> PdfResult<Vec<Quad>> {
+        let Some(points) = self.dict.get_array(b"QuadPoints")? else {
+            return Ok(Vec::new());
+        };
+        let points = points.as_numbers()?;
+        if !points.len().is_multiple_of(8) {
+            return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                .expecting("8 numbers per quadrilateral"));
+        }
+        Ok(points
+            .chunks_exact(8)
+            .map(|q| Quad {
+                points: [(q[0], q[1]), (q[2], q[3]), (q[4], q[5]), (q[6], q[7])],
+            })
+            .collect())
+    }
+    /// Sets `/QuadPoints` and `/Rect` to their bounds.
+    pub fn set_quad_points(&mut self, quads: &[Quad]) {
+        let points: Vec<_> = quads
+            .iter()
+            .flat_map(|q| q.points.into_iter().flat_map(|(x, y)| [x, y]))
+            .collect();
+        self.set(b"QuadPoints", Some(PdfArray::from_numbers(&points)));
+        if let Some(bounds) = quads.iter().map(Quad::bounds).reduce(|a, b| {
+            PdfRect::new(
+                a.llx.min(b.llx),
+                a.lly.min(b.lly),
+                a.urx.max(b.urx),
+                a.ury.max(b.ury),
+            )
+        }) {
+            self.set_rect(bounds);
+        }
+    }
+
+    /// The strokes of an ink annotation, as paths of points.
+    pub fn ink_list(&self) -> PdfResult<Vec<Vec<(f64, f64)>>> {
+        let Some(list) = self.dict.get_array(b"InkList")? else {
+            return Ok(Vec::new());
+        };
+        list.iter()
+            .map(|path| {
+                let path = path.as_array_ref()?.as_numbers()?;
+                Ok(path.chunks_exact(2).map(|p| (p[0], p[1])).collect())
+            })
+            .collect()
+    }
+    pub fn set_ink_list(&mut self, paths: &[Vec<(f64, f64)>]) {
+        let list: PdfArray = paths
+            .iter()
+            .map(|path| {
+                let numbers: Vec<_> = path.iter().flat_map(|(x, y)| [*x, *y]).collect();
+                PdfArray::from_numbers(&numbers)
+            })
+            .collect();
+        self.set(b"InkList", Some(list));
+    }
+
+    /// The border, from `/BS` or else `/Border`, a solid line 1 unit wide if neither is given.
+    pub fn border_style(&self) -> PdfResult<BorderStyle> {
+        if let Some(style) = self.dict.get_dict(b"BS")? {
+            return BorderStyle::from_dict(style);
+        }
+        match self.dict.get_array(b"Border")? {
+            Some(border) => BorderStyle::from_border_array(border),
+            None => Ok(BorderStyle::default()),
+        }
+    }
+    /// Sets `/BS`, removing the `/Border` it overrides.
+    pub fn set_border_style(&mut self, style: Option<&BorderStyle>) {
+        self.dict.remove(b"Border");
+        self.set(b"BS", style.map(BorderStyle::to_dict));
+    }
+
+    fn text(&self, key: &[u8]) -> PdfResult<Option<String>> {
+        self.dict
+            .get(key)
+            .map(|text| text.as_string_ref()?.to_text())
+            .transpose()
+    }
+
+    fn color_at(&self, key: &[u8]) -> PdfResult<Option<Color>> {
+        match self.dict.get_array(key)? {
+            Some(color) => Color::from_array(color),
+            None => Ok(None),
+        }
+    }
+
+    fn set(&mut self, key: &[u8], value: Option<impl Into<PdfObject>>) {
+        match value {
+            Some(value) => self.dict.insert(PdfName::from_raw_bytes(key), value),
+            None => self.dict.remove(key),
+        };
+    }
+}
+
+impl Page {
+    /// The annotations of `/Annots`, in order.
+    pub fn annotations(&self, file: &PdfFile) -> PdfResult<Vec<Annotation>> {
+        let Some(annots) = self.dict().get(b"Annots") else {
+            return Ok(Vec::new());
+        };
+        file.deref(annots)?
+            .as_array()?
+            .iter()
+            .map(|annot| {
+                let mut dict = file.deref(annot)?.as_dict()?;
+                for key in RESOLVED {
+                    if let Some(value) = dict.get_mut(key)
+                        && value.as_reference().is_some()
+                    {
+                        *value = file.deref(value)?;
+                    }
+                }
+                Ok(Annotation {
+                    reference: annot.as_reference().cloned(),
+                    dict,
+                })
+            })
+            .collect()
+    }
+}
+
+impl PdfFile<'_> {
+    /// Adds `annotation` to the page at `page`, returning its object.
+    pub fn add_annotation(
+        &mut self,
+        page: usize,
+        annotation: &Annotation,
+    ) -> PdfResult<IndirectData> {
+        let mut page = self.page(page)?;
+        let mut dict = annotation.dict.clone();
+        dict.insert(PdfName::from_raw_bytes(b"P"), page.reference().clone());
+        let reference = self.add_object(dict);
+
+        // `/Annots` may be an array object, changed in place
+        match page.dict().get(b"Annots") {
+            Some(annots) => {
+                let mut array = self.deref(annots)?.as_array()?;
+                array.push(reference.clone());
+                match annots.as_reference().cloned() {
+                    Some(annots) => self.set_object(&annots, array),
+                    None => {
+                        page.dict_mut()
+                            .insert(PdfName::from_raw_bytes(b"Annots"), array);
+                        self.set_page(&page);
+                    }
+                }
+            }
+            None => {
+                let mut array = PdfArray::new();
+                array.push(reference.clone());
+                page.dict_mut()
+                    .insert(PdfName::from_raw_bytes(b"Annots"), array);
+                self.set_page(&page);
+            }
+        }
+        Ok(reference)
+    }
+
+    /// Writes back an annotation read from a page and changed.
+    ///
+    /// Annotations written directly in `/Annots` have no object of their own and are changed by
+    /// rewriting the page's `/Annots` instead.
+    pub fn set_annotation(&mut self, annotation: &Annotation) -> PdfResult<()> {
+        let reference = annotation.reference.as_ref().ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("an annotation object")
+        })?;
+        self.set_object(reference, annotation.dict.clone());
+        Ok(())
+    }
+
+    /// Where a link annotation leads, from its `/Dest` or go-to action.
+    pub fn link_destination(&self, annotation: &Annotation) -> PdfResult<Option<Destination>> {
+        self.target_destination(&annotation.dict, &self.pages()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Annotation, AnnotationFlags, AnnotationType, BorderLine, Color, Destination, PdfFile,
+        PdfRect, Quad, pdf_file::test_file,
+    };
+
+    #[test]
+    fn read() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+                "<< /Type /Page /Parent 2 0 R /Annots [4 0 R 5 0 R << /Subtype /Ink /Rect [0 0 9 9] /InkList [[1 2 3 4]] /Border [0 0 2 [4 2]] >>] >>",
+                "<< /Type /Annot /Subtype /Text /Rect [10 10 30 30] /Contents 6 0 R /T (Ann) /C [1 0 0] /F 4 /M (D:20240102030405Z) >>",
+                "<< /Type /Annot /Subtype /Highlight /Rect [0 0 1 1] /QuadPoints [10 20 50 20 10 10 50 10] /C [1] /BS << /W 0 >> /A << /S /GoTo /D [3 0 R /Fit] >> >>",
+                "(Looks good)",
+            ],
+            "/Root 1 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        let annotations = file.page(0).unwrap().annotations(&file).unwrap();
+        assert_eq!(annotations.len(), 3);
+
+        let text = &annotations[0];
+        assert_eq!(text.subtype(), Ok(AnnotationType::Text));
+        assert_eq!(text.rect(), Ok(PdfRect::new(10.0, 10.0, 30.0, 30.0)));
+        assert_eq!(text.contents(), Ok(Some("Looks good".to_string())));
+        assert_eq!(text.author(), Ok(Some("Ann".to_string())));
+        assert_eq!(text.color(), Ok(Some(Color::Rgb(1.0, 0.0, 0.0))));
+        assert_eq!(text.flags(), Ok(AnnotationFlags::PRINT));
+        assert!(text.modified().is_some());
+        assert_eq!(text.border_style().unwrap().width, 1.0);
+
+        let highlight = &annotations[1];
+        assert_eq!(highlight.subtype(), Ok(AnnotationType::Highlight));
+        assert_eq!(
+            highlight.quad_points(),
+            Ok(vec![Quad::from_rect(&PdfRect::new(10.0, 10.0, 50.0, 20.0))])
+        );
+        assert_eq!(highlight.color(), Ok(Some(Color::Gray(1.0))));
+        assert_eq!(highlight.border_style().unwrap().width, 0.0);
+        assert_eq!(
+            file.link_destination(highlight),
+            Ok(Some(Destination::new(0)))
+        );
+
+        let ink = &annotations[2];
+        assert!(ink.reference().is_none());
+        assert_eq!(ink.ink_list(), Ok(vec![vec![(1.0, 2.0), (3.0, 4.0)]]));
+        let border = ink.border_style().unwrap();
+        assert_eq!(border.width, 2.0);
+        assert_eq!(border.line, BorderLine::Dashed(vec![4.0, 2.0]));
+    }
+
+    #[test]
+    fn create_and_modify() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+                "<< /Type /Page /Parent 2 0 R >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        let quads = [
+            Quad::from_rect(&PdfRect::new(10.0, 700.0, 200.0, 712.0)),
+            Quad::from_rect(&PdfRect::new(10.0, 686.0, 120.0, 698.0)),
+        ];
+        let mut highlight =
+            Annotation::text_markup(AnnotationType::Highlight, &quads, Color::Rgb(1.0, 1.0, 0.0));
+        highlight.set_contents(Some("Check this"));
+        file.add_annotation(0, &highlight).unwrap();
+        let note = Annotation::new(AnnotationType::Text, PdfRect::new(0.0, 0.0, 20.0, 20.0));
+        file.add_annotation(0, &note).unwrap();
+
+        let written = file.to_bytes().unwrap();
+        let mut file = PdfFile::load(&written).unwrap();
+        let page = file.page(0).unwrap();
+        let mut annotations = page.annotations(&file).unwrap();
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].quad_points(), Ok(quads.to_vec()));
+        assert_eq!(
+            annotations[0].rect(),
+            Ok(PdfRect::new(10.0, 686.0, 200.0, 712.0))
+        );
+        assert_eq!(
+            annotations[0]
+                .dict()
+                .get(b"P")
+                .and_then(|p| p.as_reference()),
+            Some(page.reference())
+        );
+        assert_eq!(annotations[1].subtype(), Ok(AnnotationType::Text));
+
+        annotations[1].set_flags(AnnotationFlags::PRINT | AnnotationFlags::LOCKED);
+        file.set_annotation(&annotations[1]).unwrap();
+        let annotations = page.annotations(&file).unwrap();
+        assert!(
+            annotations[1]
+                .flags()
+                .unwrap()
+                .contains(AnnotationFlags::LOCKED)
+        );
+    }
+}
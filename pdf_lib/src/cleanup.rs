@@ -0,0 +1,369 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::{
+    IndirectData, PdfArray, PdfDict, PdfFile, PdfName, PdfNumeric, PdfObject, PdfObjectKind,
+    PdfStream, Resolve, Writable, annotation,
+    pdf_error::{PdfErrorKind, PdfResult},
+};
+
+/// Adds the references in `object` to `references`.
+pub(crate) fn collect_references(object: &PdfObject, references: &mut Vec<IndirectData>) {
+    match object.kind() {
+        PdfObjectKind::Ref => references.extend(object.as_reference().cloned()),
+        PdfObjectKind::Array(array) => array
+            .iter()
+            .for_each(|item| collect_references(item, references)),
+        PdfObjectKind::Dict(dict) => dict
+            .values()
+            .for_each(|value| collect_references(value, references)),
+        PdfObjectKind::Stream(stream) => stream
+            .info()
+            .values()
+            .for_each(|value| collect_references(value, references)),
+        _ => {}
+    }
+}
+
+/// `object` with its references to the keys of `moved` replaced by the values, and with the
+/// entries of its dictionaries sorted by key if `sorted`, for objects to be compared whatever
+/// the order of their keys.
+pub(crate) fn rewrite(
+    object: &PdfObject,
+    moved: &HashMap<IndirectData, IndirectData>,
+    sorted: bool,
+) -> PdfObject {
+    match object.kind() {
+        PdfObjectKind::Ref => {
+            let reference = object
+                .as_reference()
+                .expect("references have their numbers");
+            moved.get(reference).unwrap_or(reference).clone().into()
+        }
+        PdfObjectKind::Array(array) => {
+            let mut rewritten = PdfArray::new();
+            for item in array.iter() {
+                rewritten.push(rewrite(item, moved, sorted));
+            }
+            rewritten.into()
+        }
+        PdfObjectKind::Dict(dict) => rewrite_dict(dict, moved, sorted).into(),
+        PdfObjectKind::Stream(stream) => {
+            let info = rewrite_dict(stream.info(), moved, sorted);
+            PdfStream::with_len(info, stream.len(), stream.data().to_vec()).into()
+        }
+        _ => object.clone(),
+    }
+}
+
+fn rewrite_dict(
+    dict: &PdfDict,
+    moved: &HashMap<IndirectData, IndirectData>,
+    sorted: bool,
+) -> PdfDict {
+    let mut entries: Vec<_> = dict.iter().collect();
+    if sorted {
+        entries.sort_by(|a, b| a.0.bytes().cmp(b.0.bytes()));
+    }
+    let mut rewritten = PdfDict::empty();
+    for (key, value) in entries {
+        rewritten.insert(key.clone(), rewrite(value, moved, sorted));
+    }
+    rewritten
+}
+
+/// `moved` with each object mapped to its final place, following the moves of the objects
+/// it was moved to.
+fn follow_moves(
+    moved: &HashMap<IndirectData, IndirectData>,
+) -> HashMap<IndirectData, IndirectData> {
+    moved
+        .iter()
+        .map(|(reference, mut target)| {
+            while let Some(next) = moved.get(target) {
+                target = next;
+            }
+            (reference.clone(), target.clone())
+        })
+        .collect()
+}
+
+/// Whether `object` is a node of the page tree, which must not be shared however alike.
+pub(crate) fn is_page_tree_node(object: &PdfObject) -> bool {
+    let dict = match object.kind() {
+        PdfObjectKind::Dict(dict) => dict,
+        _ => return false,
+    };
+    matches!(
+        dict.get_name(b"Type").ok().flatten().map(PdfName::bytes),
+        Some(b"Page" | b"Pages")
+    )
+}
+
+/// Whether `object` stands for something of its own that merging it with an equal object would
+/// lose: a node of the page tree, an annotation, a form field, an outline item or a structure
+/// element.
+fn has_identity(object: &PdfObject) -> bool {
+    let dict = match object.kind() {
+        PdfObjectKind::Dict(dict) => dict,
+        _ => return false,
+    };
+    let name = |key: &[u8]| dict.get_name(key).ok().flatten().map(PdfName::bytes);
+    let is_field = dict.contains_key(b"FT")
+        || (dict.contains_key(b"T")
+            && (dict.contains_key(b"Kids") || dict.contains_key(b"Parent")));
+    is_page_tree_node(object)
+        || matches!(name(b"Type"), Some(b"Annot" | b"StructElem"))
+        || name(b"Subtype").is_some_and(|subtype| annotation::SUBTYPES.contains(&subtype))
+        || is_field
+        || (dict.contains_key(b"Title") && dict.contains_key(b"Parent"))
+}
+
+impl PdfFile<'_> {
+    /// The indirect objects that can be reached from the trailer, by increasing object number.
+    ///
+    /// References to missing objects are skipped.
+    pub fn reachable_objects(&self) -> PdfResult<Vec<IndirectData>> {
+        let mut stack = Vec::new();
+        self.trailer()
+            .values()
+            .for_each(|value| collect_references(value, &mut stack));
+        let mut reachable = BTreeMap::new();
+        while let Some(reference) = stack.pop() {
+            if reachable.contains_key(&reference.object()) {
+                continue;
+            }
+            let object = match self.resolve(&reference) {
+                Ok(object) => object,
+                Err(e) if matches!(e.kind(), PdfErrorKind::UnresolvedReference(..)) => continue,
+                Err(e) => return Err(e),
+            };
+            collect_references(&object, &mut stack);
+            reachable.insert(reference.object(), reference);
+        }
+        Ok(reachable.into_values().collect())
+    }
+
+    /// Removes the objects that cannot be reached from the trailer, returning how many.
+    pub fn collect_garbage(&mut self) -> PdfResult<usize> {
+        let reachable: HashSet<_> = self.reachable_objects()?.into_iter().collect();
+        let garbage: Vec<_> = self
+            .objects()
+            .into_iter()
+            .filter(|r| !reachable.contains(r))
+            .collect();
+        for reference in &garbage {
+            self.delete_object(reference);
+        }
+        Ok(garbage.len())
+    }
+
+    /// Merges the objects equal to another, such as a font or an image embedded several
+    /// times, rewriting the references to them. Returns how many objects were removed.
+    ///
+    /// Streams are equal if their dictionaries and encoded data are, dictionaries whatever
+    /// the order of their keys. Objects referring to equal objects become equal themselves, so
+    /// merging is repeated until no more objects are. Nodes of the page tree, annotations, form
+    /// fields, outline items and structure elements are never merged, however alike, nor are
+    /// null objects, which stand for objects to come.
+    pub fn deduplicate(&mut self) -> PdfResult<usize> {
+        let mut objects = Vec::new();
+        for reference in self.objects() {
+            let object = self.resolve(&reference)?;
+            if !has_identity(&object) && !matches!(object.kind(), PdfObjectKind::Null(_)) {
+                objects.push((reference, object));
+            }
+        }
+        let mut moved = HashMap::new();
+        loop {
+            let mut kept: HashMap<Vec<u8>, IndirectData> = HashMap::new();
+            let mut merged = false;
+            for (reference, object) in &objects {
+                if moved.contains_key(reference) {
+                    continue;
+                }
+                let key = rewrite(object, &moved, true).to_bytes();
+                match kept.get(&key) {
+                    Some(first) => {
+                        moved.insert(reference.clone(), first.clone());
+                        merged = true;
+                    }
+                    None => {
+                        kept.insert(key, reference.clone());
+                    }
+                }
+            }
+            if !merged {
+                break;
+            }
+            // An object kept in an earlier pass may have been merged in this one
+            moved = follow_moves(&moved);
+        }
+        if moved.is_empty() {
+            return Ok(0);
+        }
+        for reference in moved.keys() {
+            self.delete_object(reference);
+        }
+        self.move_objects(&moved)?;
+        Ok(moved.len())
+    }
+
+    /// Numbers the objects from 1 in their current order, with generation 0, so that the
+    /// cross-reference table has no free entries but object 0.
+    pub fn renumber(&mut self) -> PdfResult<()> {
+        let moved: HashMap<_, _> = self
+            .objects()
+            .into_iter()
+            .enumerate()
+            .map(|(i, reference)| (reference, IndirectData::new(i + 1, 0)))
+            .collect();
+        self.move_objects(&moved)?;
+        let size = moved.len() as i64 + 1;
+        self.trailer_mut()
+            .insert(PdfName::from_raw_bytes(b"Size"), PdfNumeric::PdfInt(size));
+        Ok(())
+    }
+
+    /// Removes unreachable objects, merges equal ones and renumbers the rest, for the document
+    /// to be written as small as it can be.
+    pub fn clean_up(&mut self) -> PdfResult<()> {
+        self.collect_garbage()?;
+        self.deduplicate()?;
+        self.renumber()
+    }
+
+    /// Rewrites the references of the document's objects and trailer with `moved`, the objects
+    /// that are keys of `moved` being written at their values.
+    fn move_objects(&mut self, moved: &HashMap<IndirectData, IndirectData>) -> PdfResult<()> {
+        let mut rewritten = Vec::new();
+        for reference in self.objects() {
+            let object = self.resolve(&reference)?;
+            let target = moved.get(&reference).unwrap_or(&reference).clone();
+            self.delete_object(&reference);
+            rewritten.push((target, rewrite(&object, moved, false)));
+        }
+        for (reference, object) in rewritten {
+            self.set_object(&reference, object);
+        }
+        let trailer = rewrite_dict(self.trailer(), moved, false);
+        *self.trailer_mut() = trailer;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IndirectData, PdfFile, Resolve, pdf_file::test_file};
+
+    #[test]
+    fn clean_up() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+                "<< /Type /Page /Parent 2 0 R /Resources << /Font << /F1 5 0 R >> >> /Contents 7 0 R >>",
+                "<< /Type /Page /Parent 2 0 R /Resources << /Font << /F1 6 0 R >> >> /Contents 8 0 R >>",
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding 9 0 R >>",
+                "<< /BaseFont /Helvetica /Encoding 10 0 R /Type /Font /Subtype /Type1 >>",
+                "<< /Length 3 >> stream\nabc\nendstream",
+                "<< /Length 3 >> stream\nabc\nendstream",
+                "<< /Type /Encoding /Differences [1 /a] >>",
+                "<< /Type /Encoding /Differences [1 /a] >>",
+                "(orphan)",
+                "<< /Next 13 0 R >>",
+                "<< /Next 12 0 R >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        assert_eq!(file.collect_garbage(), Ok(3));
+        assert_eq!(file.objects().len(), 10);
+        // The encodings are merged first, then the fonts referring to them
+        assert_eq!(file.deduplicate(), Ok(3));
+        file.renumber().unwrap();
+        let written = file.to_bytes().unwrap();
+        let file = PdfFile::load(&written).unwrap();
+
+        let numbers: Vec<_> = file.objects().iter().map(IndirectData::object).collect();
+        assert_eq!(numbers, [1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(file.trailer().get_int(b"Size"), Ok(Some(8)));
+        let pages = file.pages().unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_ne!(pages[0].reference(), pages[1].reference());
+        assert_eq!(
+            pages[0].dict().get(b"Contents"),
+            pages[1].dict().get(b"Contents")
+        );
+        let font = |i: usize| {
+            let resources = pages[i].dict().get_dict(b"Resources").unwrap().unwrap();
+            resources
+                .get_dict(b"Font")
+                .unwrap()
+                .unwrap()
+                .get(b"F1")
+                .cloned()
+        };
+        assert_eq!(font(0), font(1));
+        let font = file.deref(&font(0).unwrap()).unwrap().as_dict().unwrap();
+        assert!(file.deref(font.get_or_null(b"Encoding")).is_ok());
+    }
+
+    #[test]
+    fn deduplicate_follows_moves() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /X [5 0 R 6 0 R 10 0 R] /Y [4 0 R 9 0 R] >>",
+                "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+                "<< /Type /Page /Parent 2 0 R >>",
+                "null",
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding 8 0 R >>",
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding 7 0 R >>",
+                "<< /Type /Encoding /Differences [1 /a] >>",
+                "<< /Type /Encoding /Differences [1 /a] >>",
+                "null",
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding 7 0 R >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        // The encodings and font 10 are merged first, then font 6 kept at that pass
+        assert_eq!(file.deduplicate(), Ok(3));
+        let catalog = file.catalog().unwrap();
+        let fonts = catalog.get_array(b"X").unwrap().unwrap();
+        for font in fonts.iter() {
+            assert_eq!(font.as_reference(), Some(&IndirectData::new(5, 0)));
+            assert!(file.deref(font).is_ok());
+        }
+        let nulls = catalog.get_array(b"Y").unwrap().unwrap();
+        assert_ne!(nulls[0], nulls[1]);
+    }
+
+    #[test]
+    fn deduplicate_keeps_identities() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /Outlines 7 0 R /AcroForm << /Fields [10 0 R 11 0 R] >> /StructTreeRoot 12 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+                "<< /Type /Page /Parent 2 0 R /Annots [4 0 R 5 0 R 6 0 R 13 0 R] >>",
+                "<< /Subtype /Link /Rect [0 0 5 5] /Dest [3 0 R /Fit] >>",
+                "<< /Subtype /Link /Rect [0 0 5 5] /Dest [3 0 R /Fit] >>",
+                "<< /Type /Annot /Subtype /Custom /Rect [0 0 5 5] >>",
+                "<< /Type /Outlines /First 8 0 R /Last 9 0 R /Count 2 >>",
+                "<< /Title (Same) /Parent 7 0 R /Dest [3 0 R /Fit] >>",
+                "<< /Title (Same) /Parent 7 0 R /Dest [3 0 R /Fit] >>",
+                "<< /FT /Tx /T (same) >>",
+                "<< /FT /Tx /T (same) >>",
+                "<< /Type /StructTreeRoot /K [14 0 R 15 0 R] >>",
+                "<< /Type /Annot /Subtype /Custom /Rect [0 0 5 5] >>",
+                "<< /Type /StructElem /S /P /P 12 0 R >>",
+                "<< /Type /StructElem /S /P /P 12 0 R >>",
+                "<< /Type /Encoding /Differences [1 /a] >>",
+                "<< /Type /Encoding /Differences [1 /a] >>",
+            ],
+            "/Root 1 0 R /Extra [16 0 R 17 0 R]",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        assert_eq!(file.deduplicate(), Ok(1));
+        assert!(file.resolve(&IndirectData::new(17, 0)).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+use std::io::Write;
+
+use crate::{Color, PdfMatrix, PdfName, PdfRect, PdfString, Writable};
+
+/// Control point distance of the Bézier curves approximating a quarter circle of radius 1.
+const KAPPA: f64 = 0.552_284_8;
+
+/// Builds content stream data one operator per line (8.2, 9.4).
+///
+/// Numbers are written with at most 4 decimals, which is finer than device resolution.
+#[derive(Debug, Clone, Default)]
+pub struct ContentBuilder {
+    data: Vec<u8>,
+}
+
+impl ContentBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The content stream data.
+    pub fn finish(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Writes `operator` after `operands`.
+    pub fn op(&mut self, operands: &[f64], operator: &str) -> &mut Self {
+        for operand in operands {
+            self.number(*operand);
+            self.data.push(b' ');
+        }
+        self.data.extend_from_slice(operator.as_bytes());
+        self.data.push(b'\n');
+        self
+    }
+
+    fn number(&mut self, n: f64) {
+        let n = if n.is_finite() { n } else { 0.0 };
+        let text = format!("{n:.4}");
+        let text = text.trim_end_matches('0').trim_end_matches('.');
+        let text = if text == "-0" { "0" } else { text };
+        self.data.extend_from_slice(text.as_bytes());
+    }
+
+    pub fn save(&mut self) -> &mut Self {
+        self.op(&[], "q")
+    }
+    pub fn restore(&mut self) -> &mut Self {
+        self.op(&[], "Q")
+    }
+    pub fn transform(&mut self, m: &PdfMatrix) -> &mut Self {
+        self.op(&[m.a, m.b, m.c, m.d, m.e, m.f], "cm")
+    }
+    pub fn line_width(&mut self, width: f64) -> &mut Self {
+        self.op(&[width], "w")
+    }
+    /// Sets the dash pattern, an empty one drawing solid lines.
+    pub fn dash(&mut self, dashes: &[f64], phase: f64) -> &mut Self {
+        self.data.push(b'[');
+        for (i, dash) in dashes.iter().enumerate() {
+            if i > 0 {
+                self.data.push(b' ');
+            }
+            self.number(*dash);
+        }
+        self.data.extend_from_slice(b"] ");
+        self.op(&[phase], "d")
+    }
+    /// Round caps and joins, as for freehand strokes.
+    pub fn round_lines(&mut self) -> &mut Self {
+        self.op(&[1.0], "J").op(&[1.0], "j")
+    }
+    /// Sets the graphics state parameters of the `/ExtGState` resource `name`.
+    pub fn graphics_state(&mut self, name: &[u8]) -> &mut Self {
+        self.name(name).op(&[], "gs")
+    }
+
+    pub fn stroke_color(&mut self, color: &Color) -> &mut Self {
+        let operator = match color {
+            Color::Gray(_) => "G",
+            Color::Rgb(..) => "RG",
+            Color::Cmyk(..) => "K",
+        };
+        self.op(&color.components(), operator)
+    }
+    pub fn fill_color(&mut self, color: &Color) -> &mut Self {
+        let operator = match color {
+            Color::Gray(_) => "g",
+            Color::Rgb(..) => "rg",
+            Color::Cmyk(..) => "k",
+        };
+        self.op(&color.components(), operator)
+    }
+
+    pub fn move_to(&mut self, x: f64, y: f64) -> &mut Self {
+        self.op(&[x, y], "m")
+    }
+    pub fn line_to(&mut self, x: f64, y: f64) -> &mut Self {
+        self.op(&[x, y], "l")
+    }
+    pub fn curve_to(&mut self, points: [(f64, f64); 3]) -> &mut Self {
+        let [(x1, y1), (x2, y2), (x3, y3)] = points;
+        self.op(&[x1, y1, x2, y2, x3, y3], "c")
+    }
+    pub fn close_path(&mut self) -> &mut Self {
+        self.op(&[], "h")
+    }
+    pub fn rect(&mut self, rect: &PdfRect) -> &mut Self {
+        self.op(&[rect.llx, rect.lly, rect.width(), rect.height()], "re")
+    }
+    /// Adds the ellipse inscribed in `rect`, as four Bézier curves.
+    pub fn ellipse(&mut self, rect: &PdfRect) -> &mut Self {
+        let (cx, cy) = ((rect.llx + rect.urx) / 2.0, (rect.lly + rect.ury) / 2.0);
+        let (rx, ry) = (rect.width() / 2.0, rect.height() / 2.0);
+        let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+        self.move_to(cx + rx, cy)
+            .curve_to([(cx + rx, cy + ky), (cx + kx, cy + ry), (cx, cy + ry)])
+            .curve_to([(cx - kx, cy + ry), (cx - rx, cy + ky), (cx - rx, cy)])
+            .curve_to([(cx - rx, cy - ky), (cx - kx, cy - ry), (cx, cy - ry)])
+            .curve_to([(cx + kx, cy - ry), (cx + rx, cy - ky), (cx + rx, cy)])
+            .close_path()
+    }
+    pub fn stroke(&mut self) -> &mut Self {
+        self.op(&[], "S")
+    }
+    pub fn fill(&mut self) -> &mut Self {
+        self.op(&[], "f")
+    }
+    pub fn fill_and_stroke(&mut self) -> &mut Self {
+        self.op(&[], "B")
+    }
+    /// Intersects the clipping path with the current path, which is then discarded.
+    pub fn clip(&mut self) -> &mut Self {
+        self.op(&[], "W").op(&[], "n")
+    }
+
+    pub fn begin_text(&mut self) -> &mut Self {
+        self.op(&[], "BT")
+    }
+    pub fn end_text(&mut self) -> &mut Self {
+        self.op(&[], "ET")
+    }
+    /// Selects the `/Font` resource `name` at `size`.
+    pub fn font(&mut self, name: &[u8], size: f64) -> &mut Self {
+        self.name(name).op(&[size], "Tf")
+    }
+    pub fn leading(&mut self, leading: f64) -> &mut Self {
+        self.op(&[leading], "TL")
+    }
+    /// Moves to the start of the next line, offset by `(x, y)` from the start of the current one.
+    pub fn next_line_at(&mut self, x: f64, y: f64) -> &mut Self {
+        self.op(&[x, y], "Td")
+    }
+    /// Shows the character codes `codes` of the current font.
+    pub fn show_text(&mut self, codes: &[u8]) -> &mut Self {
+        PdfString::from_raw_bytes(codes)
+            .write_to(&mut self.data)
+            .expect("writing to a Vec cannot fail");
+        self.data.push(b' ');
+        self.op(&[], "Tj")
+    }
+    /// Paints the Form XObject or image of the `/XObject` resource `name`.
+    pub fn x_object(&mut self, name: &[u8]) -> &mut Self {
+        self.name(name).op(&[], "Do")
+    }
+
+    /// Starts a marked-content sequence tagged `tag`, such as `/Tx` around the text of fields.
+    pub fn begin_marked_content(&mut self, tag: &[u8]) -> &mut Self {
+        self.name(tag).op(&[], "BMC")
+    }
+    pub fn end_marked_content(&mut self) -> &mut Self {
+        self.op(&[], "EMC")
+    }
+
+    /// Writes a name operand.
+    fn name(&mut self, name: &[u8]) -> &mut Self {
+        PdfName::from_raw_bytes(name)
+            .write_to(&mut self.data)
+            .expect("writing to a Vec cannot fail");
+        self.data.push(b' ');
+        self
+    }
+
+    /// Appends content data built separately, such as an operator sequence copied as is.
+    pub fn raw(&mut self, data: &[u8]) -> &mut Self {
+        self.data
+            .write_all(data)
+            .expect("writing to a Vec cannot fail");
+        if !data.ends_with(b"\n") {
+            self.data.push(b'\n');
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContentBuilder;
+    use crate::{Color, PdfRect};
+
+    #[test]
+    fn operators() {
+        let mut content = ContentBuilder::new();
+        content
+            .save()
+            .fill_color(&Color::Rgb(1.0, 0.5, 0.0))
+            .rect(&PdfRect::new(0.0, 0.0, 10.0, 2.5))
+            .fill()
+            .dash(&[3.0, 1.5], 0.0)
+            .begin_text()
+            .font(b"Helv", 12.0)
+            .next_line_at(1.0, -0.33333)
+            .show_text(b"a(b)")
+            .end_text()
+            .restore();
+        assert_eq!(
+            String::from_utf8(content.finish()).unwrap(),
+            "q\n1 0.5 0 rg\n0 0 10 2.5 re\nf\n[3 1.5] 0 d\nBT\n/Helv 12 Tf\n1 -0.3333 Td\n\
+             (a\\(b\\)) Tj\nET\nQ\n"
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use crate::{
+    NameTree, PdfArray, PdfDict, PdfFile, PdfName, PdfNull, PdfNumeric, PdfObject, PdfRect,
+    PdfString, Resolve,
+    pages::Page,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+};
+
+/// How a destination shows its page (12.3.2.2), `None` keeping the current value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DestinationView {
+    Xyz {
+        left: Option<f64>,
+        top: Option<f64>,
+        zoom: Option<f64>,
+    },
+    Fit,
+    FitH {
+        top: Option<f64>,
+    },
+    FitV {
+        left: Option<f64>,
+    },
+    FitR(PdfRect),
+    FitB,
+    FitBH {
+        top: Option<f64>,
+    },
+    FitBV {
+        left: Option<f64>,
+    },
+}
+
+/// An explicit destination, with the index of its page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Destination {
+    pub page: usize,
+    pub view: DestinationView,
+}
+
+fn optional_number(object: Option<&PdfObject>) -> PdfResult<Option<f64>> {
+    match object {
+        None => Ok(None),
+        Some(o) if o.as_null_ref().is_ok() => Ok(None),
+        Some(o) => o.as_numeric_ref()?.to_f64().map(Some),
+    }
+}
+
+fn optional_object(number: Option<f64>) -> PdfObject {
+    match number {
+        Some(n) => PdfNumeric::from(n).into(),
+        None => PdfNull {}.into(),
+    }
+}
+
+impl Destination {
+    /// Destination showing the whole page at `page`.
+    pub fn new(page: usize) -> Self {
+        Self {
+            page,
+            view: DestinationView::Fit,
+        }
+    }
+
+    /// Reads an explicit destination array, `None` if its page is not one of `pages`.
+    ///
+    /// The page may also be given by its index, as in destinations to other documents.
+    pub fn from_array(array: &PdfArray, pages: &[Page]) -> PdfResult<Option<Self>> {
+        let page = match array.get(0) {
+            Some(page) => match page.as_reference() {
+                Some(reference) => pages.iter().position(|p| p.reference() == reference),
+                None => Some(page.as_numeric_ref()?.to_usize()?).filter(|i| *i < pages.len()),
+            },
+            None => None,
+        };
+        let Some(page) = page else {
+            return Ok(None);
+        };
+        let kind = array
+            .get(1)
+            .map(PdfObject::as_name_ref)
+            .transpose()?
+            .map_or(&b"XYZ"[..], PdfName::bytes);
+        let arg = |i| optional_number(array.get(i));
+        let view = match kind {
+            b"XYZ" => DestinationView::Xyz {
+                left: arg(2)?,
+                top: arg(3)?,
+                zoom: arg(4)?.filter(|z| *z != 0.0),
+            },
+            b"Fit" => DestinationView::Fit,
+            b"FitH" => DestinationView::FitH { top: arg(2)? },
+            b"FitV" => DestinationView::FitV { left: arg(2)? },
+            b"FitR" => {
+                let corner =
+                    |i| arg(i)?.ok_or_else(|| PdfError::with_kind(PdfErrorKind::WrongType));
+                DestinationView::FitR(PdfRect::new(corner(2)?, corner(3)?, corner(4)?, corner(5)?))
+            }
+            b"FitB" => DestinationView::FitB,
+            b"FitBH" => DestinationView::FitBH { top: arg(2)? },
+            b"FitBV" => DestinationView::FitBV { left: arg(2)? },
+            _ => {
+                return Err(
+                    PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a destination type")
+                );
+            }
+        };
+        Ok(Some(Self { page, view }))
+    }
+
+    /// The destination array, its page referenced among `pages`.
+    pub fn to_array(&self, pages: &[Page]) -> PdfResult<PdfArray> {
+        let page = pages.get(self.page).ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a page index in range")
+        })?;
+        let mut array = PdfArray::new();
+        array.push(page.reference().clone());
+        let (kind, args): (&[u8], Vec<Option<f64>>) = match &self.view {
+            DestinationView::Xyz { left, top, zoom } => (b"XYZ", vec![*left, *top, *zoom]),
+            DestinationView::Fit => (b"Fit", vec![]),
+            DestinationView::FitH { top } => (b"FitH", vec![*top]),
+            DestinationView::FitV { left } => (b"FitV", vec![*left]),
+            DestinationView::FitR(r) => (
+                b"FitR",
+                vec![Some(r.llx), Some(r.lly), Some(r.urx), Some(r.ury)],
+            ),
+            DestinationView::FitB => (b"FitB", vec![]),
+            DestinationView::FitBH { top } => (b"FitBH", vec![*top]),
+            DestinationView::FitBV { left } => (b"FitBV", vec![*left]),
+        };
+        array.push(PdfName::from_raw_bytes(kind));
+        for arg in args {
+            array.push(optional_object(arg));
+        }
+        Ok(array)
+    }
+}
+
+impl PdfFile<'_> {
+    /// Resolves a destination as given by `/Dest` entries and go-to actions: an explicit array,
+    /// or the name or string of a named destination.
+    ///
+    /// `None` if the destination does not exist or does not lead to a page of the document.
+    pub fn resolve_destination(&self, destination: &PdfObject) -> PdfResult<Option<Destination>> {
+        self.resolve_destination_in(destination, &self.pages()?)
+    }
+
+    pub(crate) fn resolve_destination_in(
+        &self,
+        destination: &PdfObject,
+        pages: &[Page],
+    ) -> PdfResult<Option<Destination>> {
+        let destination = self.deref(destination)?;
+        let named = if let Ok(name) = destination.as_name_ref() {
+            self.named_destination(name.bytes(), true)?
+        } else if let Ok(string) = destination.as_string_ref() {
+            self.named_destination(string.bytes(), false)?
+        } else {
+            return Destination::from_array(destination.as_array_ref()?, pages);
+        };
+        match named {
+            Some(named) => self.named_destination_in(named, pages),
+            None => Ok(None),
+        }
+    }
+
+    /// Where an outline item or link annotation leads, from its `/Dest` or its go-to action `/A`.
+    pub(crate) fn target_destination(
+        &self,
+        dict: &PdfDict,
+        pages: &[Page],
+    ) -> PdfResult<Option<Destination>> {
+        match (dict.get(b"Dest"), dict.get(b"A")) {
+            (Some(dest), _) => self.resolve_destination_in(dest, pages),
+            (None, Some(action)) => {
+                let action = self.deref(action)?.as_dict()?;
+                match action.get_name(b"S")?.map(PdfName::bytes) {
+                    Some(b"GoTo") => self.resolve_destination_in(action.get_or_null(b"D"), pages),
+                    _ => Ok(None),
+                }
+            }
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Reads a named destination: its array, or a dictionary with the array in `/D`.
+    fn named_destination_in(
+        &self,
+        named: PdfObject,
+        pages: &[Page],
+    ) -> PdfResult<Option<Destination>> {
+        let named = match named.as_dict_ref() {
+            Ok(dict) => self.deref(dict.get_or_null(b"D"))?,
+            Err(_) => named,
+        };
+        Destination::from_array(named.as_array_ref()?, pages)
+    }
+
+    /// The destination named `name`, from the catalog's `/Dests` dictionary for names and from
+    /// its `/Names` `/Dests` name tree for strings.
+    fn named_destination(&self, name: &[u8], is_name: bool) -> PdfResult<Option<PdfObject>> {
+        if is_name {
+            let catalog = self.catalog()?;
+            let Some(dests) = catalog.get(b"Dests") else {
+                return Ok(None);
+            };
+            let dests = self.deref(dests)?.as_dict()?;
+            return dests.get(name).map(|d| self.deref(d)).transpose();
+        }
+        match self.name_tree(b"Dests")? {
+            Some(tree) => tree.get(&PdfString::from_raw_bytes(name)),
+            None => Ok(None),
+        }
+    }
+
+    /// The named destinations of the `/Names` `/Dests` name tree in name order, skipping those
+    /// that do not lead to a page of the document.
+    pub fn named_destinations(&self) -> PdfResult<Vec<(PdfString, Destination)>> {
+        let Some(tree) = self.name_tree(b"Dests")? else {
+            return Ok(Vec::new());
+        };
+        let pages = self.pages()?;
+        let mut destinations = Vec::new();
+        for entry in tree.iter() {
+            let (name, destination) = entry?;
+            if let Some(destination) = self.named_destination_in(destination, &pages)? {
+                destinations.push((name, destination));
+            }
+        }
+        Ok(destinations)
+    }
+
+    /// The destinations of the catalog's `/Dests` dictionary, named by name objects, skipping
+    /// those that do not lead to a page of the document.
+    pub(crate) fn catalog_destinations(&self) -> PdfResult<Vec<(PdfName, Destination)>> {
+        let catalog = self.catalog()?;
+        let Some(dests) = catalog.get(b"Dests") else {
+            return Ok(Vec::new());
+        };
+        let dests = self.deref(dests)?.as_dict()?;
+        let pages = self.pages()?;
+        let mut destinations = Vec::new();
+        for (name, destination) in dests.iter() {
+            let destination = self.deref(destination)?;
+            if let Some(destination) = self.named_destination_in(destination, &pages)? {
+                destinations.push((name.clone(), destination));
+            }
+        }
+        Ok(destinations)
+    }
+
+    /// Replaces the `/Names` `/Dests` name tree with a balanced tree of `destinations`,
+    /// removing it if there are none.
+    ///
+    /// The nodes of the previous tree are left for clean-up passes to remove.
+    pub fn set_named_destinations(
+        &mut self,
+        destinations: &[(PdfString, Destination)],
+    ) -> PdfResult<()> {
+        if destinations.is_empty() {
+            return self.set_name_tree(b"Dests", None);
+        }
+        let pages = self.pages()?;
+        let entries = destinations
+            .iter()
+            .map(|(name, destination)| Ok((name.clone(), destination.to_array(&pages)?.into())))
+            .collect::<PdfResult<Vec<_>>>()?;
+        let root = NameTree::write(self, entries);
+        self.set_name_tree(b"Dests", Some(root))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Destination, DestinationView, PdfFile, PdfObject, PdfString, pdf_file::test_file};
+
+    #[test]
+    fn named_destinations() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /Names << /Dests 5 0 R >> /Dests << /old [4 0 R /Fit] >> >>",
+                "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+                "<< /Type /Page /Parent 2 0 R >>",
+                "<< /Type /Page /Parent 2 0 R >>",
+                "<< /Names [(a) [3 0 R /FitH 50] (b) << /D [4 0 R /Fit] >> (gone) [9 /Fit]] >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        let a = Destination {
+            page: 0,
+            view: DestinationView::FitH { top: Some(50.0) },
+        };
+        let name = |s: &str| PdfString::from_raw_bytes(s.as_bytes());
+        assert_eq!(
+            file.named_destinations(),
+            Ok(vec![
+                (name("a"), a.clone()),
+                (name("b"), Destination::new(1))
+            ])
+        );
+        let by_string: PdfObject = name("b").into();
+        assert_eq!(
+            file.resolve_destination(&by_string),
+            Ok(Some(Destination::new(1)))
+        );
+        let by_name: PdfObject = crate::PdfName::from_raw_bytes(b"old").into();
+        assert_eq!(
+            file.resolve_destination(&by_name),
+            Ok(Some(Destination::new(1)))
+        );
+
+        file.set_named_destinations(&[(name("z"), Destination::new(0)), (name("a"), a.clone())])
+            .unwrap();
+        let written = file.to_bytes().unwrap();
+        let file = PdfFile::load(&written).unwrap();
+        assert_eq!(
+            file.named_destinations(),
+            Ok(vec![(name("a"), a), (name("z"), Destination::new(0))])
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashSet;
+
+use md5::{Digest, Md5};
+
+use crate::{
+    IndirectData, NameTree, PdfArray, PdfDate, PdfDict, PdfFile, PdfName, PdfNumeric, PdfObject,
+    PdfStream, PdfString, Resolve,
+    filter::Filter,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+};
+
+/// How a file relates to the document or page associating it, `/AFRelationship` (14.13.2).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AfRelationship {
+    /// The original content the document was made from.
+    Source,
+    /// Data the document shows, such as the XML of an invoice.
+    Data,
+    /// Another representation of the same content.
+    Alternative,
+    /// Supplements the content, such as a larger image.
+    Supplement,
+    EncryptedPayload,
+    FormData,
+    Schema,
+    Unspecified,
+    Other(PdfName),
+}
+
+const RELATIONSHIPS: [(AfRelationship, &[u8]); 8] = [
+    (AfRelationship::Source, b"Source"),
+    (AfRelationship::Data, b"Data"),
+    (AfRelationship::Alternative, b"Alternative"),
+    (AfRelationship::Supplement, b"Supplement"),
+    (AfRelationship::EncryptedPayload, b"EncryptedPayload"),
+    (AfRelationship::FormData, b"FormData"),
+    (AfRelationship::Schema, b"Schema"),
+    (AfRelationship::Unspecified, b"Unspecified"),
+];
+
+impl AfRelationship {
+    pub fn from_name(name: &PdfName) -> Self {
+        RELATIONSHIPS
+            .into_iter()
+            .find(|(_, n)| *n == name.bytes())
+            .map_or_else(|| Self::Other(name.clone()), |(r, _)| r)
+    }
+
+    pub fn name(&self) -> PdfName {
+        match self {
+            Self::Other(name) => name.clone(),
+            _ => {
+                let (_, name) = RELATIONSHIPS.iter().find(|(r, _)| r == self).unwrap();
+                PdfName::from_raw_bytes(name)
+            }
+        }
+    }
+}
+
+/// A file embedded in the document (7.11.4), described by its file specification and the
+/// parameters of its stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedFile {
+    /// File name, from `/UF` or else `/F`.
+    pub name: String,
+    pub description: Option<String>,
+    /// MIME type, such as `text/xml`.
+    pub mime_type: Option<String>,
+    pub relationship: Option<AfRelationship>,
+    /// Size of the decoded data in bytes, as recorded.
+    pub size: Option<usize>,
+    /// MD5 digest of the decoded data, as recorded.
+    pub checksum: Option<[u8; 16]>,
+    pub creation_date: Option<PdfDate>,
+    pub mod_date: Option<PdfDate>,
+    file_spec: Option<IndirectData>,
+    stream: Option<IndirectData>,
+}
+
+impl EmbeddedFile {
+    /// A file to add as `name`, with its other attributes unset.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            mime_type: None,
+            relationship: None,
+            size: None,
+            checksum: None,
+            creation_date: None,
+            mod_date: None,
+            file_spec: None,
+            stream: None,
+        }
+    }
+
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    pub fn with_relationship(mut self, relationship: AfRelationship) -> Self {
+        self.relationship = Some(relationship);
+        self
+    }
+
+    /// The file specification object, `None` for files not read from a document.
+    pub fn file_spec(&self) -> Option<&IndirectData> {
+        self.file_spec.as_ref()
+    }
+
+    /// Whether `data` has the recorded size and checksum, those not recorded matching any data.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        self.size.is_none_or(|size| size == data.len())
+            && self
+                .checksum
+                .is_none_or(|checksum| checksum == <[u8; 16]>::from(Md5::digest(data)))
+    }
+}
+
+impl PdfFile<'_> {
+    /// The embedded files of the `/EmbeddedFiles` name tree and of the `/AF` arrays of the
+    /// catalog and pages, each file specification listed once.
+    pub fn embedded_files(&self) -> PdfResult<Vec<EmbeddedFile>> {
+        let mut specs = Vec::new();
+        if let Some(tree) = self.name_tree(b"EmbeddedFiles")? {
+            for entry in tree.iter() {
+                specs.push(entry?.1);
+            }
+        }
+        let mut holders = vec![self.catalog()?];
+        holders.extend(self.pages()?.into_iter().map(|p| p.dict().clone()));
+        for holder in holders {
+            if let Some(associated) = holder.get(b"AF") {
+                specs.extend(self.deref(associated)?.as_array()?.iter().cloned());
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut files = Vec::new();
+        for spec in specs {
+            // Name tree values are resolved, keeping the reference they were read through
+            let reference = spec.indirect().or(spec.as_reference()).cloned();
+            if let Some(reference) = &reference
+                && !seen.insert(reference.clone())
+            {
+                continue;
+            }
+            let dict = self.deref(&spec)?.as_dict()?;
+            if let Some(mut file) = self.embedded_file(&dict)? {
+                file.file_spec = reference;
+                files.push(file);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Reads a file specification, `None` if it has no embedded file.
+    fn embedded_file(&self, spec: &PdfDict) -> PdfResult<Option<EmbeddedFile>> {
+        let Some(streams) = spec.get(b"EF") else {
+            return Ok(None);
+        };
+        let streams = self.deref(streams)?.as_dict()?;
+        let Some(stream) = streams.get(b"UF").or_else(|| streams.get(b"F")) else {
+            return Ok(None);
+        };
+        let text = |key: &[u8]| -> PdfResult<Option<String>> {
+            spec.get(key)
+                .map(|text| self.deref(text)?.as_string()?.to_text())
+                .transpose()
+        };
+        let mut file = EmbeddedFile::new(text(b"UF")?.or(text(b"F")?).unwrap_or_default());
+        file.description = text(b"Desc")?;
+        file.relationship = spec
+            .get_name(b"AFRelationship")?
+            .map(AfRelationship::from_name);
+        file.stream = stream.as_reference().cloned();
+
+        let info = self.deref(stream)?.as_stream()?.info().clone();
+        file.mime_type = info
+            .get_name(b"Subtype")?
+            .map(|t| String::from_utf8_lossy(t.bytes()).into_owned());
+        if let Some(params) = info.get(b"Params") {
+            let params = self.deref(params)?.as_dict()?;
+            file.size = params
+                .get_int(b"Size")?
+                .and_then(|size| usize::try_from(size).ok());
+            file.checksum = match params.get(b"CheckSum") {
+                Some(checksum) => self.deref(checksum)?.as_string()?.bytes().try_into().ok(),
+                None => None,
+            };
+            // Dates that cannot be read are dropped, as in the document information
+            let date = |key: &[u8]| params.get_date(key).ok().flatten();
+            file.creation_date = date(b"CreationDate");
+            file.mod_date = date(b"ModDate");
+        }
+        Ok(Some(file))
+    }
+
+    /// The decoded data of an embedded file read from the document.
+    pub fn embedded_file_data(&self, file: &EmbeddedFile) -> PdfResult<Vec<u8>> {
+        let stream = file.stream.as_ref().ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::InvalidData)
+                .expecting("a file embedded in the document")
+        })?;
+        self.resolve(stream)?.as_stream()?.decoded_data()
+    }
+
+    /// Embeds `data` as `file`, Flate-compressed, returning its file specification.
+    ///
+    /// The size and checksum are those of `data`. The file is added to the `/EmbeddedFiles`
+    /// name tree, replacing a file of the same name, and to the catalog's `/AF` when it has a
+    /// relationship, as PDF/A-3 requires of associated files.
+    pub fn add_embedded_file(
+        &mut self,
+        file: &EmbeddedFile,
+        data: &[u8],
+    ) -> PdfResult<IndirectData> {
+        let mut params = PdfDict::empty();
+        params.insert(
+            PdfName::from_raw_bytes(b"Size"),
+            PdfNumeric::PdfInt(data.len() as i64),
+        );
+        params.insert(
+            PdfName::from_raw_bytes(b"CheckSum"),
+            PdfString::from_raw_bytes(&Md5::digest(data)),
+        );
+        for (key, date) in [
+            (&b"CreationDate"[..], &file.creation_date),
+            (b"ModDate", &file.mod_date),
+        ] {
+            if let Some(date) = date {
+                params.insert(PdfName::from_raw_bytes(key), PdfString::from(*date));
+            }
+        }
+        let mut info = PdfDict::empty();
+        info.insert(
+            PdfName::from_raw_bytes(b"Type"),
+            PdfName::from_raw_bytes(b"EmbeddedFile"),
+        );
+        if let Some(mime_type) = &file.mime_type {
+            info.insert(
+                PdfName::from_raw_bytes(b"Subtype"),
+                PdfName::from_raw_bytes(mime_type.as_bytes()),
+            );
+        }
+        info.insert(PdfName::from_raw_bytes(b"Params"), params);
+        let stream = self.add_object(PdfStream::encoded(info, data, Filter::Flate)?);
+
+        let name = self.text_string(&file.name);
+        let mut spec = PdfDict::empty();
+        spec.insert(
+            PdfName::from_raw_bytes(b"Type"),
+            PdfName::from_raw_bytes(b"Filespec"),
+        );
+        // A file specification string is bytes, the text only being given by `/UF`
+        spec.insert(
+            PdfName::from_raw_bytes(b"F"),
+            PdfString::from_raw_bytes(file.name.as_bytes()),
+        );
+        spec.insert(PdfName::from_raw_bytes(b"UF"), name.clone());
+        if let Some(description) = &file.description {
+            spec.insert(
+                PdfName::from_raw_bytes(b"Desc"),
+                self.text_string(description),
+            );
+        }
+        if let Some(relationship) = &file.relationship {
+            spec.insert(
+                PdfName::from_raw_bytes(b"AFRelationship"),
+                relationship.name(),
+            );
+        }
+        let mut streams = PdfDict::empty();
+        streams.insert(PdfName::from_raw_bytes(b"F"), stream.clone());
+        streams.insert(PdfName::from_raw_bytes(b"UF"), stream);
+        spec.insert(PdfName::from_raw_bytes(b"EF"), streams);
+        let spec = self.add_object(spec);
+
+        // The tree is rewritten, its previous nodes left for clean-up passes to remove
+        let mut entries: Vec<(PdfString, PdfObject)> = Vec::new();
+        if let Some(tree) = self.name_tree(b"EmbeddedFiles")? {
+            for entry in tree.iter() {
+                let (key, value) = entry?;
+                let value = value.indirect().cloned().map_or(value, PdfObject::from);
+                entries.push((key, value));
+            }
+        }
+        entries.push((name, spec.clone().into()));
+        let root = NameTree::write(self, entries);
+        self.set_name_tree(b"EmbeddedFiles", Some(root))?;
+
+        if file.relationship.is_some() {
+            let catalog_reference = self.catalog_reference()?;
+            let mut catalog = self.catalog()?;
+            let mut associated = match catalog.get(b"AF") {
+                Some(associated) => self.deref(associated)?.as_array()?,
+                None => PdfArray::new(),
+            };
+            associated.push(spec.clone());
+            catalog.insert(PdfName::from_raw_bytes(b"AF"), associated);
+            self.set_object(&catalog_reference, catalog);
+        }
+        Ok(spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AfRelationship, EmbeddedFile, PdfDate, PdfFile, Resolve, pdf_file::test_file};
+
+    #[test]
+    fn add_and_extract() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /Names << /EmbeddedFiles << /Names [(old.txt) 4 0 R] >> >> >>",
+                "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+                "<< /Type /Page /Parent 2 0 R /AF [4 0 R] >>",
+                "<< /Type /Filespec /F (old.txt) /Desc (Notes) /EF << /F 5 0 R >> >>",
+                "<< /Type /EmbeddedFile /Length 11 /Filter /ASCIIHexDecode /Params << /Size 4 >> >> stream\n6E6F746573>\nendstream",
+            ],
+            "/Root 1 0 R",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        let files = file.embedded_files().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "old.txt");
+        assert_eq!(files[0].description.as_deref(), Some("Notes"));
+        let data = file.embedded_file_data(&files[0]).unwrap();
+        assert_eq!(data, b"notes");
+        assert!(!files[0].matches(&data));
+
+        let xml = b"<?xml version=\"1.0\"?><rsm:CrossIndustryInvoice/>".repeat(10);
+        let mut invoice = EmbeddedFile::new("factur-x.xml")
+            .with_mime_type("text/xml")
+            .with_relationship(AfRelationship::Alternative);
+        invoice.mod_date = Some(PdfDate::new(2024, 5, 6, 7, 8, 9).unwrap());
+        file.add_embedded_file(&invoice, &xml).unwrap();
+
+        let written = file.to_bytes().unwrap();
+        let file = PdfFile::load(&written).unwrap();
+        let files = file.embedded_files().unwrap();
+        let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["factur-x.xml", "old.txt"]);
+        let added = &files[0];
+        assert_eq!(added.mime_type.as_deref(), Some("text/xml"));
+        assert_eq!(added.relationship, Some(AfRelationship::Alternative));
+        assert_eq!(added.size, Some(xml.len()));
+        assert_eq!(added.mod_date, invoice.mod_date);
+        assert!(added.checksum.is_some());
+        let data = file.embedded_file_data(added).unwrap();
+        assert_eq!(data, xml);
+        assert!(added.matches(&data));
+        let catalog = file.catalog().unwrap();
+        assert_eq!(catalog.get_array(b"AF").unwrap().unwrap().len(), 1);
+
+        let mut file = PdfFile::load(&written).unwrap();
+        let spec = file
+            .add_embedded_file(&EmbeddedFile::new("résumé.txt"), b"cv")
+            .unwrap();
+        let spec = file.resolve(&spec).unwrap().as_dict().unwrap();
+        let name = |key: &[u8]| spec.get(key).unwrap().as_string_ref().unwrap();
+        assert_eq!(name(b"F").bytes(), "résumé.txt".as_bytes());
+        assert_eq!(name(b"UF").to_text(), Ok("résumé.txt".to_string()));
+    }
+}
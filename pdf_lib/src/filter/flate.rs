@@ -0,0 +1,339 @@
+use miniz_oxide::{deflate::compress_to_vec_zlib, inflate::decompress_to_vec_zlib_with_limit};
+
+use crate::{PdfDict, pdf_error::*};
+
+use super::{FilterData, FilterError};
+
+/// Compression level of encoded streams, from 0 (stored) to 10.
+pub const DEFAULT_LEVEL: u8 = 6;
+
+/// Largest data decoded from a Flate stream, in bytes, so that a small stream cannot inflate
+/// to exhaust memory. Larger data fails to decode.
+const MAX_DECODED_LEN: usize = 1 << 28;
+
+/// Largest row of predicted data, in bytes, bounding the rows allocated by the predictors.
+const MAX_ROW_LEN: usize = 1 << 24;
+
+/// Parameters of the predictors of Flate and LZW data (7.4.4.4).
+struct Predictor {
+    predictor: i64,
+    /// Bytes per pixel, at least 1.
+    pixel_len: usize,
+    /// Bytes per row, without the PNG tag byte.
+    row_len: usize,
+    bits_per_component: i64,
+}
+
+impl Predictor {
+    fn from_params(params: &PdfDict) -> PdfResult<Self> {
+        let int =
+            |key: &[u8], default| -> PdfResult<i64> { Ok(params.get_int(key)?.unwrap_or(default)) };
+        let (colors, bits, columns) = (
+            int(b"Colors", 1)?,
+            int(b"BitsPerComponent", 8)?,
+            int(b"Columns", 1)?,
+        );
+        if colors < 1 || columns < 1 || ![1, 2, 4, 8, 16].contains(&bits) {
+            return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                .expecting("valid predictor parameters"));
+        }
+        let bits_per_pixel = colors.checked_mul(bits).ok_or_else(Self::error)?;
+        let row_bits = bits_per_pixel
+            .checked_mul(columns)
+            .ok_or_else(Self::error)?;
+        let row_len = usize::try_from(row_bits)
+            .map_err(|_| Self::error())?
+            .div_ceil(8);
+        if row_len > MAX_ROW_LEN {
+            return Err(Self::error());
+        }
+        Ok(Self {
+            predictor: int(b"Predictor", 1)?,
+            pixel_len: (bits_per_pixel as usize).div_ceil(8),
+            row_len,
+            bits_per_component: bits,
+        })
+    }
+
+    fn error() -> PdfError {
+        PdfError::with_kind(PdfErrorKind::Filter(FilterError::Predictor))
+    }
+
+    /// Undoes the predictor of decoded data.
+    fn undo(&self, data: Vec<u8>) -> PdfResult<Vec<u8>> {
+        match self.predictor {
+            1 => Ok(data),
+            2 => self.tiff(data, false),
+            10..=15 => {
+                let mut output = Vec::with_capacity(data.len());
+                let mut previous = vec![0; self.row_len];
+                for row in data.chunks(self.row_len + 1) {
+                    let (&tag, row) = row.split_first().ok_or_else(Self::error)?;
+                    let mut current = row.to_vec();
+                    // A short last row is kept as far as it goes
+                    current.resize(self.row_len, 0);
+                    for i in 0..self.row_len {
+                        let left = if i >= self.pixel_len {
+                            current[i - self.pixel_len]
+                        } else {
+                            0
+                        };
+                        let up = previous[i];
+                        let up_left = if i >= self.pixel_len {
+                            previous[i - self.pixel_len]
+                        } else {
+                            0
+                        };
+                        current[i] = current[i].wrapping_add(match tag {
+                            0 => 0,
+                            1 => left,
+                            2 => up,
+                            3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                            4 => paeth(left, up, up_left),
+                            _ => return Err(Self::error()),
+                        });
+                    }
+                    output.extend_from_slice(&current[..row.len()]);
+                    previous = current;
+                }
+                Ok(output)
+            }
+            _ => Err(Self::error()),
+        }
+    }
+
+    /// Applies the predictor to data about to be encoded, PNG predictors using the `Up`
+    /// algorithm for every row, which suits tables such as cross-reference streams.
+    fn apply(&self, data: Vec<u8>) -> PdfResult<Vec<u8>> {
+        match self.predictor {
+            1 => Ok(data),
+            2 => self.tiff(data, true),
+            10..=15 => {
+                let mut output = Vec::with_capacity(data.len() + data.len() / self.row_len + 1);
+                let mut previous: &[u8] = &[];
+                for row in data.chunks(self.row_len) {
+                    output.push(2);
+                    output.extend(
+                        row.iter()
+                            .enumerate()
+                            .map(|(i, b)| b.wrapping_sub(previous.get(i).copied().unwrap_or(0))),
+                    );
+                    previous = row;
+                }
+                Ok(output)
+            }
+            _ => Err(Self::error()),
+        }
+    }
+
+    /// TIFF predictor 2, differences between horizontally adjacent bytes: only 8-bit
+    /// components are supported.
+    fn tiff(&self, mut data: Vec<u8>, encode: bool) -> PdfResult<Vec<u8>> {
+        if self.bits_per_component != 8 {
+            return Err(Self::error());
+        }
+        for row in data.chunks_mut(self.row_len) {
+            let indices: Vec<_> = (self.pixel_len..row.len()).collect();
+            if encode {
+                for &i in indices.iter().rev() {
+                    row[i] = row[i].wrapping_sub(row[i - self.pixel_len]);
+                }
+            } else {
+                for i in indices {
+                    row[i] = row[i].wrapping_add(row[i - self.pixel_len]);
+                }
+            }
+        }
+        Ok(data)
+    }
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(up_left);
+    let (a, b, c) = (
+        (estimate - i16::from(left)).abs(),
+        (estimate - i16::from(up)).abs(),
+        (estimate - i16::from(up_left)).abs(),
+    );
+    if a <= b && a <= c {
+        left
+    } else if b <= c {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Compresses `data` in the zlib format of `FlateDecode` at `level`.
+fn deflate(data: &[u8], level: u8) -> Vec<u8> {
+    compress_to_vec_zlib(data, level.min(10))
+}
+
+/// Decompresses zlib `data`, failing if it is invalid or decodes to more than `limit` bytes.
+fn inflate(data: &[u8], limit: usize) -> PdfResult<Vec<u8>> {
+    decompress_to_vec_zlib_with_limit(data, limit)
+        .map_err(|_| PdfError::with_kind(PdfErrorKind::Filter(FilterError::FlateDecode)))
+}
+
+/// Applies the predictor of `params` to `data`, then compresses it at `level`.
+pub(crate) fn encode_flate(data: Vec<u8>, params: &PdfDict, level: u8) -> PdfResult<Vec<u8>> {
+    let data = Predictor::from_params(params)?.apply(data)?;
+    Ok(deflate(&data, level))
+}
+
+/// The whole input of a filter, which Flate handles at once.
+fn collect<I: Iterator<Item = PdfResult<u8>>>(inner: &mut FilterData<I>) -> PdfResult<Vec<u8>> {
+    inner.iter.by_ref().collect()
+}
+
+/// Flate data, produced at once on the first call to `next`.
+pub struct EncodeFlate<I> {
+    inner: FilterData<I>,
+    output: Option<std::vec::IntoIter<u8>>,
+}
+impl<I> EncodeFlate<I> {
+    pub fn new(inner: FilterData<I>) -> Self {
+        Self {
+            inner,
+            output: None,
+        }
+    }
+}
+
+impl<I: Iterator<Item = PdfResult<u8>>> Iterator for EncodeFlate<I> {
+    type Item = PdfResult<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.output.is_none() {
+            let encoded = collect(&mut self.inner)
+                .and_then(|data| encode_flate(data, &self.inner.params, DEFAULT_LEVEL));
+            match encoded {
+                Ok(encoded) => self.output = Some(encoded.into_iter()),
+                Err(e) => {
+                    self.output = Some(Vec::new().into_iter());
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.output.as_mut()?.next().map(Ok)
+    }
+}
+
+/// Data decoded from Flate, produced at once on the first call to `next`, and failing past
+/// [`MAX_DECODED_LEN`] bytes.
+pub struct DecodeFlate<I> {
+    inner: FilterData<I>,
+    output: Option<std::vec::IntoIter<u8>>,
+}
+impl<I> DecodeFlate<I> {
+    pub fn new(inner: FilterData<I>) -> Self {
+        Self {
+            inner,
+            output: None,
+        }
+    }
+}
+
+impl<I: Iterator<Item = PdfResult<u8>>> Iterator for DecodeFlate<I> {
+    type Item = PdfResult<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.output.is_none() {
+            let decoded = collect(&mut self.inner).and_then(|data| {
+                let data = inflate(&data, MAX_DECODED_LEN)?;
+                Predictor::from_params(&self.inner.params)?.undo(data)
+            });
+            match decoded {
+                Ok(decoded) => self.output = Some(decoded.into_iter()),
+                Err(e) => {
+                    self.output = Some(Vec::new().into_iter());
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.output.as_mut()?.next().map(Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PdfDict, PdfErrorKind, PdfName, PdfNumeric, filter::FilterError};
+
+    use super::super::Filter;
+
+    fn run(bytes: &[u8], params: PdfDict, encode: bool) -> Result<Vec<u8>, PdfErrorKind> {
+        let bytes = bytes.iter().copied().map(Ok);
+        let result: Result<Vec<_>, _> = if encode {
+            Filter::Flate.encode(bytes, params).collect()
+        } else {
+            Filter::Flate.decode(bytes, params).collect()
+        };
+        result.map_err(|e| e.kind().clone())
+    }
+
+    fn params(entries: &[(&[u8], i64)]) -> PdfDict {
+        let mut dict = PdfDict::empty();
+        for (key, value) in entries {
+            dict.insert(PdfName::from_raw_bytes(key), PdfNumeric::PdfInt(*value));
+        }
+        dict
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = b"BT /F1 12 Tf (Hello) Tj ET ".repeat(20);
+        let encoded = run(&data, PdfDict::empty(), true).unwrap();
+        assert!(encoded.len() < data.len());
+        assert_eq!(run(&encoded, PdfDict::empty(), false), Ok(data));
+        assert_eq!(
+            run(b"not flate", PdfDict::empty(), false),
+            Err(PdfErrorKind::Filter(FilterError::FlateDecode))
+        );
+    }
+
+    #[test]
+    fn predictors() {
+        // Rows of a cross-reference stream with /W [1 2 1]
+        let rows = [1, 0, 15, 0, 1, 0, 200, 0, 2, 3, 1, 7];
+        for predictor in [2, 12, 15] {
+            let params = params(&[(b"Predictor", predictor), (b"Columns", 4)]);
+            let encoded = run(&rows, params.clone(), true).unwrap();
+            assert_eq!(run(&encoded, params, false), Ok(rows.to_vec()));
+        }
+
+        // Each PNG algorithm, decoded against hand-computed rows
+        let params = params(&[(b"Predictor", 15), (b"Columns", 2), (b"Colors", 1)]);
+        let predicted = [1, 5, 3, 2, 1, 1, 3, 4, 4, 4, 3, 1, 0, 9, 9];
+        let encoded = super::deflate(&predicted, 6);
+        assert_eq!(
+            run(&encoded, params, false),
+            Ok(vec![5, 8, 6, 9, 7, 12, 10, 13, 9, 9])
+        );
+    }
+
+    #[test]
+    fn decoded_len_limit() {
+        let zeros = vec![0; 100_000];
+        let bomb = super::deflate(&zeros, 10);
+        assert!(bomb.len() < 1000);
+        assert_eq!(super::inflate(&bomb, 100_000), Ok(zeros));
+        assert_eq!(
+            super::inflate(&bomb, 99_999).map_err(|e| e.kind().clone()),
+            Err(PdfErrorKind::Filter(FilterError::FlateDecode))
+        );
+    }
+
+    #[test]
+    fn unreasonable_predictor_params() {
+        let predictor_error = Err(PdfErrorKind::Filter(FilterError::Predictor));
+        let encoded = super::deflate(&[0, 1, 2], 6);
+        for (colors, columns) in [(i64::MAX, 1), (1 << 40, 1 << 40), (1, 1 << 40)] {
+            let params = params(&[
+                (b"Predictor", 15),
+                (b"Colors", colors),
+                (b"Columns", columns),
+            ]);
+            assert_eq!(run(&encoded, params, false), predictor_error);
+        }
+    }
+}
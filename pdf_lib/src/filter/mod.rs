@@ -1,22 +1,47 @@
 use core::str;
+use std::fmt::Display;
 
 use super::{PdfDict, PdfName, WHITESPACES};
 use crate::pdf_error::*;
 
 use ascii85::*;
 use asciihex::*;
+use flate::*;
+pub(crate) use flate::{DEFAULT_LEVEL, encode_flate};
 mod ascii85;
 mod asciihex;
+mod flate;
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum FilterError {
     ASCIIHexDecode(u8),
     MissingEOD,
     ASCII85Decode,
+    FlateDecode,
+    /// Predicted data that does not fit its predictor parameters.
+    Predictor,
+    /// The filter is valid but not implemented.
+    Unsupported(String),
+}
+
+impl Display for FilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterError::ASCIIHexDecode(b) => {
+                write!(f, "invalid ASCIIHex byte {:?}", char::from(*b))
+            }
+            FilterError::MissingEOD => write!(f, "missing end of data marker"),
+            FilterError::ASCII85Decode => write!(f, "invalid ASCII85 data"),
+            FilterError::FlateDecode => write!(f, "invalid Flate data"),
+            FilterError::Predictor => write!(f, "invalid predicted data"),
+            FilterError::Unsupported(name) => write!(f, "unsupported filter {name}"),
+        }
+    }
 }
 
 macro_rules! filter_impl {
     ($Name:tt, $($Var:tt $raw:literal $($Param:literal)*),+) => {
+        #[allow(clippy::upper_case_acronyms)]
         #[derive(Debug, Clone, Copy)]
         pub enum $Name{
             $($Var),+
@@ -75,7 +100,6 @@ filter_impl!(Filter,
 
 pub struct FilterData<I> {
     iter: I,
-    #[allow(dead_code)]
     params: PdfDict,
 }
 
@@ -89,6 +113,9 @@ impl<I: Iterator<Item = PdfResult<u8>>> Iterator for FilterData<I> {
 pub enum Encode<I> {
     ASCIIHex(EncodeASCIIHex<I>),
     ASCII85(EncodeASCII85<I>),
+    Flate(EncodeFlate<I>),
+    /// Yields a single [`FilterError::Unsupported`] error.
+    Unsupported(Option<Filter>),
 }
 
 impl<I: FilterIter> Iterator for Encode<I> {
@@ -98,12 +125,17 @@ impl<I: FilterIter> Iterator for Encode<I> {
         match self {
             Encode::ASCIIHex(inner) => inner.next(),
             Encode::ASCII85(inner) => inner.next(),
+            Encode::Flate(inner) => inner.next(),
+            Encode::Unsupported(filter) => filter.take().map(|f| Err(f.unsupported())),
         }
     }
 }
 pub enum Decode<I> {
     ASCIIHex(DecodeASCIIHex<I>),
     ASCII85(DecodeASCII85<I>),
+    Flate(DecodeFlate<I>),
+    /// Yields a single [`FilterError::Unsupported`] error.
+    Unsupported(Option<Filter>),
 }
 impl<I: FilterIter> Iterator for Decode<I> {
     type Item = PdfResult<u8>;
@@ -112,11 +144,18 @@ impl<I: FilterIter> Iterator for Decode<I> {
         match self {
             Decode::ASCIIHex(inner) => inner.next(),
             Decode::ASCII85(inner) => inner.next(),
+            Decode::Flate(inner) => inner.next(),
+            Decode::Unsupported(filter) => filter.take().map(|f| Err(f.unsupported())),
         }
     }
 }
 
 impl Filter {
+    fn unsupported(&self) -> PdfError {
+        let name = String::from_utf8_lossy(PdfName::from(self).bytes()).into_owned();
+        PdfError::with_kind(PdfErrorKind::Filter(FilterError::Unsupported(name)))
+    }
+
     pub fn encode<I: IntoIterator<Item = PdfResult<u8>>>(
         &self,
         bytes: I,
@@ -129,14 +168,8 @@ impl Filter {
         match self {
             Filter::ASCIIHex => Encode::ASCIIHex(EncodeASCIIHex::new(inner)),
             Filter::ASCII85 => Encode::ASCII85(EncodeASCII85::new(inner)),
-            Filter::LZW => todo!(),
-            Filter::Flate => todo!(),
-            Filter::RunLength => todo!(),
-            Filter::CCITTFax => todo!(),
-            Filter::JBIG2 => todo!(),
-            Filter::DCT => todo!(),
-            Filter::JPX => todo!(),
-            Filter::Crypt => todo!(),
+            Filter::Flate => Encode::Flate(EncodeFlate::new(inner)),
+            filter => Encode::Unsupported(Some(*filter)),
         }
     }
 
@@ -152,14 +185,8 @@ impl Filter {
         match self {
             Filter::ASCIIHex => Decode::ASCIIHex(DecodeASCIIHex::new(inner)),
             Filter::ASCII85 => Decode::ASCII85(DecodeASCII85::new(inner)),
-            Filter::LZW => todo!(),
-            Filter::Flate => todo!(),
-            Filter::RunLength => todo!(),
-            Filter::CCITTFax => todo!(),
-            Filter::JBIG2 => todo!(),
-            Filter::DCT => todo!(),
-            Filter::JPX => todo!(),
-            Filter::Crypt => todo!(),
+            Filter::Flate => Decode::Flate(DecodeFlate::new(inner)),
+            filter => Decode::Unsupported(Some(*filter)),
         }
     }
 }
@@ -173,7 +200,7 @@ where
     I: Iterator<Item = PdfResult<u8>>,
 {
     fn next_non_whitespace(&mut self) -> Option<PdfResult<u8>> {
-        while let Some(b) = self.next() {
+        for b in self.by_ref() {
             let Ok(b) = b else { return Some(b) };
             if !WHITESPACES.contains(&b) {
                 return Some(Ok(b));
@@ -0,0 +1,300 @@
+use crate::{PdfDict, PdfName};
+
+/// The standard 14 Type 1 fonts, which every reader provides (9.6.2.2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Base14Font {
+    Helvetica,
+    HelveticaBold,
+    HelveticaOblique,
+    HelveticaBoldOblique,
+    TimesRoman,
+    TimesBold,
+    TimesItalic,
+    TimesBoldItalic,
+    Courier,
+    CourierBold,
+    CourierOblique,
+    CourierBoldOblique,
+    Symbol,
+    ZapfDingbats,
+}
+
+const NAMES: [(Base14Font, &str); 14] = [
+    (Base14Font::Helvetica, "Helvetica"),
+    (Base14Font::HelveticaBold, "Helvetica-Bold"),
+    (Base14Font::HelveticaOblique, "Helvetica-Oblique"),
+    (Base14Font::HelveticaBoldOblique, "Helvetica-BoldOblique"),
+    (Base14Font::TimesRoman, "Times-Roman"),
+    (Base14Font::TimesBold, "Times-Bold"),
+    (Base14Font::TimesItalic, "Times-Italic"),
+    (Base14Font::TimesBoldItalic, "Times-BoldItalic"),
+    (Base14Font::Courier, "Courier"),
+    (Base14Font::CourierBold, "Courier-Bold"),
+    (Base14Font::CourierOblique, "Courier-Oblique"),
+    (Base14Font::CourierBoldOblique, "Courier-BoldOblique"),
+    (Base14Font::Symbol, "Symbol"),
+    (Base14Font::ZapfDingbats, "ZapfDingbats"),
+];
+
+/// Widths of the characters 0x20 to 0x7E, in thousandths of the font size, from the fonts' AFM
+/// files. The oblique faces of Helvetica share the widths of the upright ones.
+#[rustfmt::skip]
+const HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+#[rustfmt::skip]
+const HELVETICA_BOLD: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+#[rustfmt::skip]
+const TIMES_ROMAN: [u16; 95] = [
+    250, 333, 408, 500, 500, 833, 778, 180, 333, 333, 500, 564, 250, 333, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 278, 278, 564, 564, 564, 444,
+    921, 722, 667, 667, 722, 611, 556, 722, 722, 333, 389, 722, 611, 889, 722, 722,
+    556, 722, 667, 556, 611, 722, 722, 944, 722, 722, 611, 333, 278, 333, 469, 500,
+    333, 444, 500, 444, 500, 444, 333, 500, 500, 278, 278, 500, 278, 778, 500, 500,
+    500, 500, 333, 389, 278, 500, 500, 722, 500, 500, 444, 480, 200, 480, 541,
+];
+#[rustfmt::skip]
+const TIMES_BOLD: [u16; 95] = [
+    250, 333, 555, 500, 500, 1000, 833, 278, 333, 333, 500, 570, 250, 333, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 333, 333, 570, 570, 570, 500,
+    930, 722, 667, 722, 722, 667, 611, 778, 778, 389, 500, 778, 667, 944, 722, 778,
+    611, 778, 722, 556, 667, 722, 722, 1000, 722, 722, 667, 333, 278, 333, 581, 500,
+    333, 500, 556, 444, 556, 444, 333, 500, 556, 278, 333, 556, 278, 833, 556, 500,
+    556, 556, 444, 389, 333, 556, 500, 722, 500, 500, 444, 394, 220, 394, 520,
+];
+#[rustfmt::skip]
+const TIMES_ITALIC: [u16; 95] = [
+    250, 333, 420, 500, 500, 833, 778, 214, 333, 333, 500, 675, 250, 333, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 333, 333, 675, 675, 675, 500,
+    920, 611, 611, 667, 722, 611, 611, 722, 722, 333, 444, 667, 556, 833, 667, 722,
+    611, 722, 611, 500, 556, 722, 611, 833, 611, 556, 556, 389, 278, 389, 422, 500,
+    333, 500, 500, 444, 500, 444, 278, 500, 500, 278, 278, 444, 278, 722, 500, 500,
+    500, 500, 389, 389, 278, 500, 444, 667, 444, 444, 389, 400, 275, 400, 541,
+];
+#[rustfmt::skip]
+const TIMES_BOLD_ITALIC: [u16; 95] = [
+    250, 389, 555, 500, 500, 833, 778, 278, 333, 333, 500, 570, 250, 333, 250, 278,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 333, 333, 570, 570, 570, 500,
+    832, 667, 667, 667, 722, 667, 667, 722, 778, 389, 500, 667, 611, 889, 722, 722,
+    611, 722, 667, 556, 611, 722, 667, 889, 667, 611, 611, 333, 278, 333, 570, 500,
+    333, 500, 500, 444, 500, 444, 333, 500, 556, 278, 278, 500, 278, 778, 556, 500,
+    500, 500, 389, 389, 278, 556, 444, 667, 500, 444, 389, 348, 220, 348, 570,
+];
+
+/// Width of the glyphs of the symbolic fonts, whose widths are not tabulated here. Most
+/// ZapfDingbats glyphs are close to it.
+const SYMBOLIC_WIDTH: u16 = 800;
+
+/// WinAnsiEncoding codes 0x80 to 0x9F, the others matching Latin-1 (Annex D).
+const WIN_ANSI_SYMBOLS: [Option<char>; 32] = [
+    Some('€'),
+    None,
+    Some('‚'),
+    Some('ƒ'),
+    Some('„'),
+    Some('…'),
+    Some('†'),
+    Some('‡'),
+    Some('ˆ'),
+    Some('‰'),
+    Some('Š'),
+    Some('‹'),
+    Some('Œ'),
+    None,
+    Some('Ž'),
+    None,
+    None,
+    Some('‘'),
+    Some('’'),
+    Some('“'),
+    Some('”'),
+    Some('•'),
+    Some('–'),
+    Some('—'),
+    Some('˜'),
+    Some('™'),
+    Some('š'),
+    Some('›'),
+    Some('œ'),
+    None,
+    Some('ž'),
+    Some('Ÿ'),
+];
+
+impl Base14Font {
+    /// The font of the `/BaseFont` name `name`.
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        NAMES
+            .into_iter()
+            .find(|(_, n)| n.as_bytes() == name)
+            .map(|(font, _)| font)
+    }
+
+    pub fn name(self) -> &'static str {
+        NAMES.iter().find(|(f, _)| *f == self).unwrap().1
+    }
+
+    pub fn is_symbolic(self) -> bool {
+        matches!(self, Self::Symbol | Self::ZapfDingbats)
+    }
+
+    /// A simple font dictionary for the font, in WinAnsiEncoding unless symbolic.
+    pub fn font_dict(self) -> PdfDict {
+        let mut dict = PdfDict::empty();
+        dict.insert(
+            PdfName::from_raw_bytes(b"Type"),
+            PdfName::from_raw_bytes(b"Font"),
+        );
+        dict.insert(
+            PdfName::from_raw_bytes(b"Subtype"),
+            PdfName::from_raw_bytes(b"Type1"),
+        );
+        dict.insert(
+            PdfName::from_raw_bytes(b"BaseFont"),
+            PdfName::from_raw_bytes(self.name().as_bytes()),
+        );
+        if !self.is_symbolic() {
+            dict.insert(
+                PdfName::from_raw_bytes(b"Encoding"),
+                PdfName::from_raw_bytes(b"WinAnsiEncoding"),
+            );
+        }
+        dict
+    }
+
+    /// The codes of `text` in the encoding of [`Base14Font::font_dict`], characters it cannot
+    /// encode becoming `?`.
+    ///
+    /// Symbolic fonts take the codes of ASCII characters as they are.
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        text.chars()
+            .map(|c| {
+                if self.is_symbolic() {
+                    return u8::try_from(c).ok().filter(u8::is_ascii).unwrap_or(b' ');
+                }
+                win_ansi_code(c).unwrap_or(b'?')
+            })
+            .collect()
+    }
+
+    /// Width of the code `code` in thousandths of the font size.
+    ///
+    /// Codes past ASCII are measured as `o`, their widths not being tabulated here.
+    pub fn code_width(self, code: u8) -> u16 {
+        let widths = match self {
+            Self::Helvetica | Self::HelveticaOblique => &HELVETICA,
+            Self::HelveticaBold | Self::HelveticaBoldOblique => &HELVETICA_BOLD,
+            Self::TimesRoman => &TIMES_ROMAN,
+            Self::TimesBold => &TIMES_BOLD,
+            Self::TimesItalic => &TIMES_ITALIC,
+            Self::TimesBoldItalic => &TIMES_BOLD_ITALIC,
+            Self::Courier | Self::CourierBold | Self::CourierOblique | Self::CourierBoldOblique => {
+                return 600;
+            }
+            Self::Symbol | Self::ZapfDingbats => return SYMBOLIC_WIDTH,
+        };
+        let code = if (0x20..0x7f).contains(&code) {
+            code
+        } else {
+            b'o'
+        };
+        widths[usize::from(code - 0x20)]
+    }
+
+    /// Width of `text` at `size`, in text space units.
+    pub fn text_width(self, text: &str, size: f64) -> f64 {
+        let width: u32 = self
+            .encode(text)
+            .into_iter()
+            .map(|c| u32::from(self.code_width(c)))
+            .sum();
+        f64::from(width) * size / 1000.0
+    }
+
+    /// Splits `text` into lines at most `width` wide at `size`, breaking at spaces when possible
+    /// and keeping the line breaks of the text.
+    pub fn wrap(self, text: &str, size: f64, width: f64) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.split(['\n', '\r']) {
+            let mut line = String::new();
+            for word in paragraph.split(' ') {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{line} {word}")
+                };
+                if self.text_width(&candidate, size) <= width || line.is_empty() {
+                    line = candidate;
+                } else {
+                    lines.push(std::mem::replace(&mut line, word.to_string()));
+                }
+                // Words wider than the line are broken between characters
+                while self.text_width(&line, size) > width && line.chars().count() > 1 {
+                    let mut split = 1;
+                    for (i, _) in line.char_indices().skip(1) {
+                        if self.text_width(&line[..i], size) > width {
+                            break;
+                        }
+                        split = i;
+                    }
+                    let rest = line.split_off(split);
+                    lines.push(std::mem::replace(&mut line, rest));
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+fn win_ansi_code(c: char) -> Option<u8> {
+    match u32::from(c) {
+        0x20..=0x7e | 0xa0..=0xff => u8::try_from(c).ok(),
+        _ => WIN_ANSI_SYMBOLS
+            .iter()
+            .position(|s| *s == Some(c))
+            .map(|i| 0x80 + i as u8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Base14Font;
+
+    #[test]
+    fn names_and_widths() {
+        assert_eq!(
+            Base14Font::from_name(b"Helvetica-Bold"),
+            Some(Base14Font::HelveticaBold)
+        );
+        assert_eq!(Base14Font::from_name(b"Arial"), None);
+        assert_eq!(Base14Font::TimesItalic.name(), "Times-Italic");
+        assert_eq!(Base14Font::Helvetica.text_width("Hello", 10.0), 22.78);
+        assert_eq!(Base14Font::Courier.text_width("abc", 10.0), 18.0);
+        assert_eq!(Base14Font::Helvetica.encode("€é→"), [0x80, 0xe9, b'?']);
+    }
+
+    #[test]
+    fn wrap() {
+        let font = Base14Font::Courier;
+        // 6 units per character at size 10
+        assert_eq!(
+            font.wrap("one two three\nfour", 10.0, 48.0),
+            ["one two", "three", "four"]
+        );
+        assert_eq!(font.wrap("abcdefghij", 10.0, 24.0), ["abcd", "efgh", "ij"]);
+        assert_eq!(font.wrap("", 10.0, 24.0), [""]);
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::HashSet;
+
+use crate::{
+    Annotation, AnnotationFlags, ContentBuilder, FieldKind, IndirectData, PdfArray, PdfDict,
+    PdfFile, PdfMatrix, PdfName, PdfObject, PdfRect, PdfStream, Resolve, pages::Page,
+    pdf_error::PdfResult,
+};
+
+impl PdfFile<'_> {
+    /// Merges the appearances of form field widgets into the content of their pages, then
+    /// removes the widgets and the interactive form with its fields.
+    ///
+    /// Fields filled without appearances get them generated first. Hidden widgets are removed
+    /// without being drawn.
+    pub fn flatten_forms(&mut self) -> PdfResult<()> {
+        for field in self.form_fields()? {
+            if !matches!(
+                field.kind(),
+                FieldKind::Text
+                    | FieldKind::ComboBox
+                    | FieldKind::ListBox
+                    | FieldKind::CheckBox
+                    | FieldKind::RadioButton
+            ) {
+                continue;
+            }
+            let mut missing = false;
+            for widget in field.widgets() {
+                missing |= !self.resolve(widget)?.as_dict()?.contains_key(b"AP");
+            }
+            if missing {
+                self.update_widgets(&field)?;
+            }
+        }
+        let field_objects = self.field_objects()?;
+        for page in self.pages()? {
+            self.flatten_page(page)?;
+        }
+        let catalog_reference = self.catalog_reference()?;
+        let mut catalog = self.catalog()?;
+        if let Some(form) = catalog.remove(b"AcroForm") {
+            if let Some(form) = form.as_reference() {
+                self.delete_object(form);
+            }
+            for field in field_objects {
+                self.delete_object(&field);
+            }
+            self.set_object(&catalog_reference, catalog);
+        }
+        Ok(())
+    }
+
+    fn flatten_page(&mut self, mut page: Page) -> PdfResult<()> {
+        let Some(annots) = page.dict().get(b"Annots").cloned() else {
+            return Ok(());
+        };
+        let mut kept = PdfArray::new();
+        let mut content = ContentBuilder::new();
+        let mut resources = match page.attribute(b"Resources") {
+            Some(resources) => self.deref(resources)?.as_dict()?,
+            None => PdfDict::empty(),
+        };
+        let mut x_objects = match resources.get(b"XObject") {
+            Some(x_objects) => self.deref(x_objects)?.as_dict()?,
+            None => PdfDict::empty(),
+        };
+        let mut drawn = false;
+        for annot in self.deref(&annots)?.as_array()?.iter() {
+            let dict = self.deref(annot)?.as_dict()?;
+            if dict.get_name(b"Subtype")?.map(PdfName::bytes) != Some(b"Widget") {
+                kept.push(annot.clone());
+                continue;
+            }
+            if let Some(widget) = annot.as_reference() {
+                self.delete_object(&widget.clone());
+            }
+            let annotation = Annotation::from_dict(None, dict);
+            let flags = annotation.flags()?;
+            if flags.contains(AnnotationFlags::HIDDEN) || flags.contains(AnnotationFlags::NO_VIEW) {
+                continue;
+            }
+            let Some(appearance) = self.normal_appearance(annotation.dict())? else {
+                continue;
+            };
+            let Some(placement) = self.placement(&appearance, &annotation.rect()?)? else {
+                continue;
+            };
+            let name = (0..)
+                .map(|i| format!("Fm{i}"))
+                .find(|name| !x_objects.contains_key(name))
+                .expect("names run out after the objects");
+            x_objects.insert(PdfName::from_raw_bytes(name.as_bytes()), appearance);
+            content
+                .save()
+                .transform(&placement)
+                .x_object(name.as_bytes())
+                .restore();
+            drawn = true;
+        }
+
+        if let Some(annots) = annots.as_reference() {
+            self.delete_object(&annots.clone());
+        }
+        let dict = page.dict_mut();
+        if kept.is_empty() {
+            dict.remove(b"Annots");
+        } else {
+            dict.insert(PdfName::from_raw_bytes(b"Annots"), kept);
+        }
+        if drawn {
+            // The page content is wrapped in its own graphics state, for the appearances to be
+            // placed in the default coordinates
+            let mut contents = PdfArray::new();
+            contents.push(self.add_object(PdfStream::new(PdfDict::empty(), b"q\n".to_vec())));
+            if let Some(existing) = page.dict().get(b"Contents").cloned() {
+                match self.deref(&existing)?.as_array() {
+                    Ok(streams) => streams.iter().for_each(|s| contents.push(s.clone())),
+                    Err(_) => contents.push(existing),
+                }
+            }
+            let mut data = b"Q\n".to_vec();
+            data.extend(content.finish());
+            contents.push(self.add_object(PdfStream::new(PdfDict::empty(), data)));
+            resources.insert(PdfName::from_raw_bytes(b"XObject"), x_objects);
+            let dict = page.dict_mut();
+            dict.insert(PdfName::from_raw_bytes(b"Contents"), contents);
+            dict.insert(PdfName::from_raw_bytes(b"Resources"), resources);
+        }
+        self.set_page(&page);
+        Ok(())
+    }
+
+    /// Reference to the normal appearance of an annotation: its `/AP /N` stream, or the one of
+    /// its `/AS` state.
+    fn normal_appearance(&self, annotation: &PdfDict) -> PdfResult<Option<PdfObject>> {
+        let Some(ap) = annotation.get(b"AP") else {
+            return Ok(None);
+        };
+        let Some(normal) = self.deref(ap)?.as_dict()?.get(b"N").cloned() else {
+            return Ok(None);
+        };
+        let appearance = match self.deref(&normal)?.as_dict() {
+            Ok(states) => match annotation.get_name(b"AS")? {
+                Some(state) => states.get(state.bytes()).cloned(),
+                None => None,
+            },
+            Err(_) => Some(normal),
+        };
+        // Streams are indirect objects, which Form XObjects must be
+        Ok(appearance.filter(|a| a.as_reference().is_some()))
+    }
+
+    /// The matrix placing the Form XObject `appearance` in `rect` (12.5.5): its bounding box,
+    /// transformed by its own matrix, is scaled and moved onto the rectangle.
+    ///
+    /// `None` for appearances whose transformed box is empty.
+    fn placement(&self, appearance: &PdfObject, rect: &PdfRect) -> PdfResult<Option<PdfMatrix>> {
+        let stream = self.deref(appearance)?.as_stream()?;
+        let bbox = PdfRect::try_from(stream.info().get_or_null(b"BBox"))?;
+        let matrix = match stream.info().get(b"Matrix") {
+            Some(matrix) => PdfMatrix::try_from(&self.deref(matrix)?)?,
+            None => PdfMatrix::IDENTITY,
+        };
+        let placed = matrix.apply_rect(&bbox);
+        if placed.width() == 0.0 || placed.height() == 0.0 {
+            return Ok(None);
+        }
+        Ok(Some(
+            PdfMatrix::translate(-placed.llx, -placed.lly)
+                .then(&PdfMatrix::scale(
+                    rect.width() / placed.width(),
+                    rect.height() / placed.height(),
+                ))
+                .then(&PdfMatrix::translate(rect.llx, rect.lly)),
+        ))
+    }
+
+    /// The objects of the field tree of the catalog's `/AcroForm`.
+    fn field_objects(&self) -> PdfResult<Vec<IndirectData>> {
+        let mut nodes = Vec::new();
+        if let Some(form) = self.catalog()?.get(b"AcroForm")
+            && let Some(fields) = self.deref(form)?.as_dict()?.get(b"Fields")
+        {
+            let fields = self.deref(fields)?.as_array()?;
+            nodes.extend(fields.iter().filter_map(|f| f.as_reference().cloned()));
+        }
+        let mut visited = HashSet::new();
+        while let Some(node) = nodes.pop() {
+            if !visited.insert(node.clone()) {
+                continue;
+            }
+            if let Some(kids) = self.resolve(&node)?.as_dict()?.get(b"Kids") {
+                let kids = self.deref(kids)?.as_array()?;
+                nodes.extend(kids.iter().filter_map(|k| k.as_reference().cloned()));
+            }
+        }
+        Ok(visited.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IndirectData, PdfFile, Resolve, pdf_file::test_file};
+
+    #[test]
+    fn flatten() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /AcroForm << /Fields [4 0 R 5 0 R 7 0 R] /DA (/Helv 0 Tf 0 g) >> >>",
+                "<< /Type /Pages /Kids [3 0 R] /Count 1 /Resources << /Font << /F1 9 0 R >> >> >>",
+                "<< /Type /Page /Parent 2 0 R /Contents 8 0 R /Annots [4 0 R 5 0 R 6 0 R 7 0 R] >>",
+                "<< /T (name) /FT /Tx /V (Ada) /Subtype /Widget /Rect [100 200 200 220] >>",
+                "<< /T (ok) /FT /Btn /V /Yes /AS /Yes /Subtype /Widget /Rect [10 10 30 30] /AP << /N << /Yes 10 0 R /Off 10 0 R >> >> >>",
+                "<< /Subtype /Link /Rect [0 0 5 5] >>",
+                "<< /T (hidden) /FT /Tx /F 2 /Subtype /Widget /Rect [0 0 5 5] >>",
+                "<< /Length 0 >> stream\n\nendstream",
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Times-Roman >>",
+                "<< /Subtype /Form /BBox [0 0 10 10] /Matrix [0 1 -1 0 0 0] /Length 0 >> stream\n\nendstream",
+            ],
+            "/Root 1 0 R",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        file.flatten_forms().unwrap();
+        let written = file.to_bytes().unwrap();
+        let file = PdfFile::load(&written).unwrap();
+
+        assert!(file.catalog().unwrap().get(b"AcroForm").is_none());
+        assert!(file.form_fields().unwrap().is_empty());
+        for removed in [4, 5, 7] {
+            assert!(file.resolve(&IndirectData::new(removed, 0)).is_err());
+        }
+        let page = file.page(0).unwrap();
+        let annots = page.dict().get_array(b"Annots").unwrap().unwrap();
+        assert_eq!(annots.len(), 1);
+
+        let resources = page.dict().get_dict(b"Resources").unwrap().unwrap();
+        assert!(
+            resources
+                .get_dict(b"Font")
+                .unwrap()
+                .unwrap()
+                .get(b"F1")
+                .is_some()
+        );
+        let x_objects = resources.get_dict(b"XObject").unwrap().unwrap();
+        assert_eq!(x_objects.len(), 2);
+
+        let contents = page.dict().get_array(b"Contents").unwrap().unwrap();
+        assert_eq!(contents.len(), 3);
+        let last = file.deref(&contents[2]).unwrap().as_stream().unwrap();
+        let drawing = String::from_utf8(last.data().to_vec()).unwrap();
+        // The text field's box [0 0 100 20] moved onto its rectangle, the rotated check box
+        // appearance, placed at [-10 0 0 10], moved and doubled onto [10 10 30 30]
+        assert_eq!(
+            drawing,
+            "Q\nq\n1 0 0 1 100 200 cm\n/Fm0 Do\nQ\nq\n2 0 0 2 30 10 cm\n/Fm1 Do\nQ\n"
+        );
+    }
+}
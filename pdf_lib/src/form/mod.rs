@@ -0,0 +1,965 @@
+mod flatten;
+
+use std::collections::HashSet;
+
+use crate::{
+    Annotation, Color, IndirectData, PdfArray, PdfDict, PdfFile, PdfName, PdfObject, PdfRect,
+    PdfString, Resolve,
+    annotation::{ASCENT, Appearance, DefaultAppearance, LINE_HEIGHT, TEXT_PADDING, border, inset},
+    font::Base14Font,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+};
+
+/// How deep fields may be nested, to stop on malformed field trees.
+const MAX_FIELD_DEPTH: usize = 32;
+/// Font size of text fields sized to fit, when several lines are shown.
+const MULTILINE_AUTO_SIZE: f64 = 12.0;
+/// Appearance state of buttons that are off (12.7.5.2.3).
+const OFF: &str = "Off";
+
+/// Field flags, `/Ff` (12.7.4.1), the meaning of most bits depending on the field type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FieldFlags(u32);
+
+impl FieldFlags {
+    pub const READ_ONLY: Self = Self(1);
+    pub const REQUIRED: Self = Self(1 << 1);
+    pub const NO_EXPORT: Self = Self(1 << 2);
+    pub const MULTILINE: Self = Self(1 << 12);
+    pub const PASSWORD: Self = Self(1 << 13);
+    pub const NO_TOGGLE_TO_OFF: Self = Self(1 << 14);
+    pub const RADIO: Self = Self(1 << 15);
+    pub const PUSH_BUTTON: Self = Self(1 << 16);
+    pub const COMBO: Self = Self(1 << 17);
+    pub const EDIT: Self = Self(1 << 18);
+    pub const SORT: Self = Self(1 << 19);
+    pub const MULTI_SELECT: Self = Self(1 << 21);
+    pub const COMB: Self = Self(1 << 24);
+
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Kinds of fields, from their `/FT` and flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    CheckBox,
+    RadioButton,
+    PushButton,
+    ComboBox,
+    ListBox,
+    Signature,
+}
+
+/// Value of a field, `/V`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue {
+    /// Text of text fields and of combo boxes.
+    Text(String),
+    /// Appearance state of check boxes and radio buttons, [`FieldValue::off`] for none.
+    State(String),
+    /// Selected options of choice fields, by export value.
+    Choices(Vec<String>),
+}
+
+impl FieldValue {
+    /// The state of check boxes and radio buttons that are off.
+    pub fn off() -> Self {
+        Self::State(OFF.to_string())
+    }
+
+    /// Reads a `/V` leniently, so that one malformed value does not hide the other fields: text
+    /// is decoded lossily, choices that are not strings are skipped, and values of other types
+    /// are `None`.
+    fn from_object(object: &PdfObject) -> Option<Self> {
+        if let Ok(text) = object.as_string_ref() {
+            Some(Self::Text(text.to_text_lossy()))
+        } else if let Ok(name) = object.as_name_ref() {
+            Some(Self::State(
+                String::from_utf8_lossy(name.bytes()).into_owned(),
+            ))
+        } else if let Ok(array) = object.as_array_ref() {
+            let choices = array
+                .iter()
+                .filter_map(|c| c.as_string_ref().ok())
+                .map(PdfString::to_text_lossy)
+                .collect();
+            Some(Self::Choices(choices))
+        } else {
+            None
+        }
+    }
+}
+
+/// A terminal field of the interactive form, with its inherited attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormField {
+    reference: IndirectData,
+    name: String,
+    kind: FieldKind,
+    flags: FieldFlags,
+    value: Option<FieldValue>,
+    default_appearance: Vec<u8>,
+    /// Alignment of text, `/Q`: 0 left, 1 centred, 2 right.
+    quadding: i64,
+    /// Maximum length of text fields, `/MaxLen`.
+    max_len: Option<i64>,
+    widgets: Vec<IndirectData>,
+    dict: PdfDict,
+}
+
+impl FormField {
+    pub fn reference(&self) -> &IndirectData {
+        &self.reference
+    }
+    /// The fully qualified name, the partial names of the field and its ancestors joined by
+    /// periods.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn kind(&self) -> FieldKind {
+        self.kind
+    }
+    pub fn flags(&self) -> FieldFlags {
+        self.flags
+    }
+    pub fn is_read_only(&self) -> bool {
+        self.flags.contains(FieldFlags::READ_ONLY)
+    }
+    pub fn is_required(&self) -> bool {
+        self.flags.contains(FieldFlags::REQUIRED)
+    }
+    pub fn value(&self) -> Option<&FieldValue> {
+        self.value.as_ref()
+    }
+    /// The widget annotations showing the field, the field itself when they are merged.
+    pub fn widgets(&self) -> &[IndirectData] {
+        &self.widgets
+    }
+    /// The field dictionary itself, without inherited attributes.
+    pub fn dict(&self) -> &PdfDict {
+        &self.dict
+    }
+
+    /// Options of choice fields, as export values and the text shown for them.
+    pub fn options(&self) -> PdfResult<Vec<(String, String)>> {
+        let Some(options) = self.dict.get_array(b"Opt")? else {
+            return Ok(Vec::new());
+        };
+        options
+            .iter()
+            .map(|option| match option.as_array_ref() {
+                Ok(pair) => {
+                    let text = |i| -> PdfResult<String> {
+                        pair.get(i)
+                            .ok_or_else(|| {
+                                PdfError::with_kind(PdfErrorKind::InvalidData)
+                                    .expecting("an export value and its text")
+                            })?
+                            .as_string_ref()?
+                            .to_text()
+                    };
+                    Ok((text(0)?, text(1)?))
+                }
+                Err(_) => {
+                    let text = option.as_string_ref()?.to_text()?;
+                    Ok((text.clone(), text))
+                }
+            })
+            .collect()
+    }
+
+    /// Whether a required field lacks a value, check boxes and radio buttons that are off
+    /// counting as lacking one.
+    pub fn is_missing_value(&self) -> bool {
+        match &self.value {
+            None => true,
+            Some(FieldValue::Text(text)) => text.is_empty(),
+            Some(FieldValue::State(state)) => state == OFF,
+            Some(FieldValue::Choices(choices)) => choices.is_empty(),
+        }
+    }
+
+    /// Text shown by the widgets of text and choice fields.
+    fn shown_text(&self) -> PdfResult<String> {
+        let text = match &self.value {
+            Some(FieldValue::Text(text)) => text.clone(),
+            Some(FieldValue::Choices(choices)) => {
+                let options = self.options()?;
+                let shown = |choice: &String| {
+                    options
+                        .iter()
+                        .find(|(export, _)| export == choice)
+                        .map_or_else(|| choice.clone(), |(_, text)| text.clone())
+                };
+                choices.first().map(shown).unwrap_or_default()
+            }
+            Some(FieldValue::State(_)) | None => String::new(),
+        };
+        if self.kind == FieldKind::Text && self.flags.contains(FieldFlags::PASSWORD) {
+            return Ok("*".repeat(text.chars().count()));
+        }
+        Ok(text)
+    }
+}
+
+/// Attributes passed down the field tree (12.7.4.1).
+#[derive(Clone, Default)]
+struct Inherited {
+    name: String,
+    field_type: Option<PdfName>,
+    value: Option<PdfObject>,
+    default_appearance: Option<Vec<u8>>,
+    flags: i64,
+    quadding: i64,
+    max_len: Option<i64>,
+}
+
+impl PdfFile<'_> {
+    fn acro_form(&self) -> PdfResult<Option<PdfDict>> {
+        self.catalog()?
+            .get(b"AcroForm")
+            .map(|form| self.deref(form)?.as_dict())
+            .transpose()
+    }
+
+    /// The terminal fields of the catalog's `/AcroForm`, in the order of the field tree.
+    pub fn form_fields(&self) -> PdfResult<Vec<FormField>> {
+        let Some(form) = self.acro_form()? else {
+            return Ok(Vec::new());
+        };
+        let inherited = Inherited {
+            default_appearance: match form.get(b"DA") {
+                Some(da) => Some(self.deref(da)?.as_string()?.bytes().to_vec()),
+                None => None,
+            },
+            quadding: form.get_int(b"Q")?.unwrap_or(0),
+            ..Inherited::default()
+        };
+        let roots = match form.get(b"Fields") {
+            Some(fields) => self.deref(fields)?.as_array()?,
+            None => PdfArray::new(),
+        };
+        let mut fields = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack: Vec<_> = roots
+            .iter()
+            .rev()
+            .map(|f| (f.clone(), inherited.clone(), 0))
+            .collect();
+        while let Some((node, mut inherited, depth)) = stack.pop() {
+            let reference = node.as_reference().cloned().ok_or_else(|| {
+                PdfError::with_kind(PdfErrorKind::WrongType).expecting("a reference to a field")
+            })?;
+            if !visited.insert(reference.clone()) || depth > MAX_FIELD_DEPTH {
+                return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                    .expecting("a field tree visiting each field once"));
+            }
+            let dict = self.resolve(&reference)?.as_dict()?;
+            if let Some(partial) = dict.get(b"T") {
+                let partial = self.deref(partial)?.as_string()?.to_text()?;
+                inherited.name = if inherited.name.is_empty() {
+                    partial
+                } else {
+                    format!("{}.{partial}", inherited.name)
+                };
+            }
+            if let Some(field_type) = dict.get_name(b"FT")? {
+                inherited.field_type = Some(field_type.clone());
+            }
+            if let Some(value) = dict.get(b"V") {
+                inherited.value = Some(self.deref(value)?);
+            }
+            if let Some(da) = dict.get(b"DA") {
+                inherited.default_appearance = Some(self.deref(da)?.as_string()?.bytes().to_vec());
+            }
+            if let Some(flags) = dict.get_int(b"Ff")? {
+                inherited.flags = flags;
+            }
+            if let Some(quadding) = dict.get_int(b"Q")? {
+                inherited.quadding = quadding;
+            }
+            if let Some(max_len) = dict.get_int(b"MaxLen")? {
+                inherited.max_len = Some(max_len);
+            }
+
+            // Kids with a partial name are fields, the others widgets of this field
+            let mut kid_fields = Vec::new();
+            let mut widgets = Vec::new();
+            if let Some(kids) = dict.get(b"Kids") {
+                for kid in self.deref(kids)?.as_array()?.iter() {
+                    let kid_dict = self.deref(kid)?.as_dict()?;
+                    let kid_reference = kid.as_reference().cloned().ok_or_else(|| {
+                        PdfError::with_kind(PdfErrorKind::WrongType)
+                            .expecting("a reference to a field")
+                    })?;
+                    if kid_dict.contains_key(b"T") {
+                        kid_fields.push(kid.clone());
+                    } else {
+                        widgets.push(kid_reference);
+                    }
+                }
+            } else {
+                widgets.push(reference.clone());
+            }
+            for kid in kid_fields.into_iter().rev() {
+                stack.push((kid, inherited.clone(), depth + 1));
+            }
+            if widgets.is_empty() {
+                continue;
+            }
+            // Fields without a type cannot be filled, and are left out
+            let Some(field_type) = &inherited.field_type else {
+                continue;
+            };
+            let flags = u32::try_from(inherited.flags).map_err(|_| {
+                PdfError::with_kind(PdfErrorKind::InvalidData)
+                    .expecting("field flags /Ff of 32 bits")
+                    .in_object(reference.object(), reference.generation())
+            })?;
+            let flags = FieldFlags::from_bits(flags);
+            let kind = match field_type.bytes() {
+                b"Tx" => FieldKind::Text,
+                b"Btn" if flags.contains(FieldFlags::PUSH_BUTTON) => FieldKind::PushButton,
+                b"Btn" if flags.contains(FieldFlags::RADIO) => FieldKind::RadioButton,
+                b"Btn" => FieldKind::CheckBox,
+                b"Ch" if flags.contains(FieldFlags::COMBO) => FieldKind::ComboBox,
+                b"Ch" => FieldKind::ListBox,
+                b"Sig" => FieldKind::Signature,
+                _ => continue,
+            };
+            let value = inherited.value.as_ref().and_then(FieldValue::from_object);
+            fields.push(FormField {
+                reference,
+                name: inherited.name.clone(),
+                kind,
+                flags,
+                value,
+                default_appearance: inherited.default_appearance.clone().unwrap_or_default(),
+                quadding: inherited.quadding,
+                max_len: inherited.max_len,
+                widgets,
+                dict,
+            });
+        }
+        Ok(fields)
+    }
+
+    /// The terminal field of fully qualified name `name`.
+    pub fn form_field(&self, name: &str) -> PdfResult<Option<FormField>> {
+        Ok(self.form_fields()?.into_iter().find(|f| f.name == name))
+    }
+
+    /// Names of the required fields still lacking a value.
+    pub fn missing_required_fields(&self) -> PdfResult<Vec<String>> {
+        Ok(self
+            .form_fields()?
+            .into_iter()
+            .filter(|f| f.is_required() && f.is_missing_value())
+            .map(|f| f.name)
+            .collect())
+    }
+
+    /// Sets the value of the field `name` and updates the appearances of its widgets.
+    ///
+    /// Fails for read-only fields, push buttons and signatures, and for values that do not fit
+    /// the field: states that none of its widgets have, options it does not offer unless it is
+    /// editable, or text longer than its `/MaxLen`. Check boxes and radio buttons without
+    /// appearances take any state, their appearances being generated for it.
+    pub fn set_field_value(&mut self, name: &str, value: FieldValue) -> PdfResult<()> {
+        let mut field = self.form_field(name)?.ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("the name of a field")
+        })?;
+        if field.is_read_only() {
+            return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                .expecting("a field that is not read-only"));
+        }
+        let invalid = |expected| PdfError::with_kind(PdfErrorKind::InvalidData).expecting(expected);
+        let object: PdfObject = match (field.kind, &value) {
+            (FieldKind::Text, FieldValue::Text(text)) => {
+                if let Some(max) = field.max_len
+                    && text.chars().count() as i64 > max
+                {
+                    return Err(invalid("text within the field's /MaxLen"));
+                }
+                self.text_string(text).into()
+            }
+            (FieldKind::CheckBox | FieldKind::RadioButton, FieldValue::State(state)) => {
+                // Widgets without appearances get ones for the state set
+                let states = self.on_states(&field)?;
+                if state != OFF && !states.is_empty() && !states.contains(state) {
+                    return Err(invalid("a state of the field's widgets"));
+                }
+                PdfName::from_raw_bytes(state.as_bytes()).into()
+            }
+            (FieldKind::ComboBox | FieldKind::ListBox, FieldValue::Text(text)) => {
+                self.check_choices(&field, std::slice::from_ref(text))?;
+                self.text_string(text).into()
+            }
+            (FieldKind::ComboBox | FieldKind::ListBox, FieldValue::Choices(choices)) => {
+                self.check_choices(&field, choices)?;
+                match &choices[..] {
+                    [choice] => self.text_string(choice).into(),
+                    _ => choices
+                        .iter()
+                        .map(|c| PdfObject::from(self.text_string(c)))
+                        .collect::<PdfArray>()
+                        .into(),
+                }
+            }
+            _ => {
+                return Err(PdfError::with_kind(PdfErrorKind::WrongType)
+                    .expecting("a value of the field's kind"));
+            }
+        };
+        field.dict.insert(PdfName::from_raw_bytes(b"V"), object);
+        self.set_object(&field.reference, field.dict.clone());
+        field.value = Some(value);
+        self.update_widgets(&field)
+    }
+
+    /// Checks or unchecks the check box `name`, using the on state of its widgets.
+    pub fn set_checked(&mut self, name: &str, checked: bool) -> PdfResult<()> {
+        let field = self.form_field(name)?.ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("the name of a field")
+        })?;
+        let state = match checked {
+            true => self
+                .on_states(&field)?
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| "Yes".to_string()),
+            false => OFF.to_string(),
+        };
+        self.set_field_value(name, FieldValue::State(state))
+    }
+
+    fn check_choices(&self, field: &FormField, choices: &[String]) -> PdfResult<()> {
+        if field.flags.contains(FieldFlags::EDIT) {
+            return Ok(());
+        }
+        let options = field.options()?;
+        let offered = |c: &String| options.iter().any(|(export, _)| export == c);
+        if !choices.iter().all(offered)
+            || (choices.len() > 1 && !field.flags.contains(FieldFlags::MULTI_SELECT))
+        {
+            return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                .expecting("options offered by the field"));
+        }
+        Ok(())
+    }
+
+    /// The on states of the widgets of a button field: the keys of their `/AP /N` other than
+    /// `Off`.
+    fn on_states(&self, field: &FormField) -> PdfResult<Vec<String>> {
+        let mut states = Vec::new();
+        for widget in &field.widgets {
+            if let Some(state) = self.widget_on_state(&self.resolve(widget)?.as_dict()?)?
+                && !states.contains(&state)
+            {
+                states.push(state);
+            }
+        }
+        Ok(states)
+    }
+
+    fn widget_on_state(&self, widget: &PdfDict) -> PdfResult<Option<String>> {
+        let Some(ap) = widget.get(b"AP") else {
+            return Ok(None);
+        };
+        let ap = self.deref(ap)?.as_dict()?;
+        let Some(normal) = ap.get(b"N") else {
+            return Ok(None);
+        };
+        let normal = self.deref(normal)?;
+        let Ok(states) = normal.as_dict_ref() else {
+            return Ok(None);
+        };
+        Ok(states
+            .keys()
+            .find(|k| k.bytes() != OFF.as_bytes())
+            .map(|k| String::from_utf8_lossy(k.bytes()).into_owned()))
+    }
+
+    /// Regenerates the appearances of the widgets of `field` for its value, and sets the
+    /// appearance state of buttons.
+    fn update_widgets(&mut self, field: &FormField) -> PdfResult<()> {
+        for widget in &field.widgets {
+            // Merged fields and widgets are one dictionary, already changed
+            let mut dict = self.resolve(widget)?.as_dict()?;
+            match field.kind {
+                FieldKind::CheckBox | FieldKind::RadioButton => {
+                    let state = match &field.value {
+                        Some(FieldValue::State(state)) => state.clone(),
+                        _ => OFF.to_string(),
+                    };
+                    let on = match self.widget_on_state(&dict)? {
+                        Some(on) => on,
+                        None => {
+                            let on = match field.kind {
+                                FieldKind::CheckBox if state != OFF => state.clone(),
+                                _ => "Yes".to_string(),
+                            };
+                            self.generate_button_appearance(field, &mut dict, &on)?;
+                            on
+                        }
+                    };
+                    let shown = if on == state { on } else { OFF.to_string() };
+                    dict.insert(
+                        PdfName::from_raw_bytes(b"AS"),
+                        PdfName::from_raw_bytes(shown.as_bytes()),
+                    );
+                }
+                FieldKind::Text | FieldKind::ComboBox | FieldKind::ListBox => {
+                    let appearance = self.text_appearance(field, &dict)?;
+                    let stream = appearance.0.write(self, &appearance.1);
+                    let mut ap = PdfDict::empty();
+                    ap.insert(PdfName::from_raw_bytes(b"N"), stream);
+                    dict.insert(PdfName::from_raw_bytes(b"AP"), ap);
+                }
+                FieldKind::PushButton | FieldKind::Signature => continue,
+            }
+            self.set_object(widget, dict);
+        }
+        Ok(())
+    }
+
+    /// The background and border of a widget from its `/MK` characteristics, returning the
+    /// area left inside the border.
+    fn widget_frame(&self, widget: &PdfDict, appearance: &mut Appearance) -> PdfResult<PdfRect> {
+        let annotation = Annotation::from_dict(None, widget.clone());
+        let rect = annotation.rect()?;
+        let bbox = PdfRect::new(0.0, 0.0, rect.width(), rect.height());
+        let characteristics = match widget.get(b"MK") {
+            Some(mk) => self.deref(mk)?.as_dict()?,
+            None => PdfDict::empty(),
+        };
+        let color = |key: &[u8]| -> PdfResult<Option<Color>> {
+            match characteristics.get(key) {
+                Some(color) => Color::from_array(self.deref(color)?.as_array_ref()?),
+                None => Ok(None),
+            }
+        };
+        let content = &mut appearance.content;
+        if let Some(background) = color(b"BG")? {
+            content.fill_color(&background).rect(&bbox).fill();
+        }
+        let style = annotation.border_style()?;
+        match color(b"BC")? {
+            Some(border_color) if border(content, &style) => {
+                content
+                    .stroke_color(&border_color)
+                    .rect(&inset(&bbox, style.width / 2.0))
+                    .stroke();
+                Ok(inset(&bbox, style.width))
+            }
+            _ => Ok(bbox),
+        }
+    }
+
+    /// The font of the default appearance `da`: the one of that name in the form's `/DR`
+    /// resources, measured as the base 14 font it names, or else a base 14 font.
+    fn field_font(&self, da: &DefaultAppearance) -> PdfResult<(PdfObject, Base14Font)> {
+        let resources = match self.acro_form()?.and_then(|f| f.get(b"DR").cloned()) {
+            Some(resources) => self.deref(&resources)?.as_dict()?,
+            None => PdfDict::empty(),
+        };
+        let font = match resources.get(b"Font") {
+            Some(fonts) => self.deref(fonts)?.as_dict()?.get(&da.font).cloned(),
+            None => None,
+        };
+        let Some(font) = font else {
+            return Ok((da.base14().font_dict().into(), da.base14()));
+        };
+        let base_font = self.deref(&font)?.as_dict()?;
+        let measured = base_font
+            .get_name(b"BaseFont")?
+            .and_then(|name| Base14Font::from_name(name.bytes()))
+            .unwrap_or_else(|| da.base14());
+        Ok((font, measured))
+    }
+
+    /// The appearance of a text or choice field widget, and its bounding box.
+    fn text_appearance(
+        &self,
+        field: &FormField,
+        widget: &PdfDict,
+    ) -> PdfResult<(Appearance, PdfRect)> {
+        let mut appearance = Appearance::default();
+        let area = self.widget_frame(widget, &mut appearance)?;
+        let rect = Annotation::from_dict(None, widget.clone()).rect()?;
+        let bbox = PdfRect::new(0.0, 0.0, rect.width(), rect.height());
+        let da_bytes = match widget.get(b"DA") {
+            Some(da) => self.deref(da)?.as_string()?.bytes().to_vec(),
+            None => field.default_appearance.clone(),
+        };
+        let da = DefaultAppearance::parse(&da_bytes);
+        let (font_object, font) = self.field_font(&da)?;
+        appearance.add_font(&da.font, font_object);
+
+        let text_area = inset(&area, TEXT_PADDING);
+        let multiline = field.kind == FieldKind::Text
+            && field.flags.contains(FieldFlags::MULTILINE)
+            || field.kind == FieldKind::ListBox;
+        let text = field.shown_text()?;
+        let size = match da.size {
+            size if size > 0.0 => size,
+            _ if multiline => MULTILINE_AUTO_SIZE,
+            _ => {
+                let by_width = text_area.width() / font.text_width(&text, 1.0).max(f64::EPSILON);
+                (text_area.height() / LINE_HEIGHT).min(by_width).max(1.0)
+            }
+        };
+        let lines = match field.kind {
+            FieldKind::ListBox => field.options()?.into_iter().map(|(_, t)| t).collect(),
+            _ if multiline => font.wrap(&text, size, text_area.width()),
+            _ => vec![text],
+        };
+
+        let content = &mut appearance.content;
+        content.begin_marked_content(b"Tx").save();
+        content.rect(&area).clip();
+        if field.kind == FieldKind::ListBox {
+            // Selected options are shown on a highlight, as viewers do
+            let selected = match &field.value {
+                Some(FieldValue::Choices(choices)) => choices.clone(),
+                Some(FieldValue::Text(text)) => vec![text.clone()],
+                _ => Vec::new(),
+            };
+            content.fill_color(&Color::Rgb(0.6, 0.75, 0.85));
+            for (i, (export, _)) in field.options()?.iter().enumerate() {
+                if selected.contains(export) {
+                    let top = text_area.ury - size * LINE_HEIGHT * i as f64;
+                    let line = PdfRect::new(area.llx, top - size * LINE_HEIGHT, area.urx, top);
+                    content.rect(&line).fill();
+                }
+            }
+        }
+        content
+            .begin_text()
+            .font(&da.font, size)
+            .fill_color(&da.color);
+        let first_baseline = if multiline {
+            text_area.ury - size * ASCENT
+        } else {
+            // Centred vertically, the glyphs above the baseline taking most of the size
+            text_area.lly + (text_area.height() - size * ASCENT) / 2.0
+        };
+        let mut previous = (0.0, 0.0);
+        for (i, line) in lines.iter().enumerate() {
+            let width = font.text_width(line, size);
+            let x = text_area.llx
+                + match field.quadding {
+                    1 => (text_area.width() - width) / 2.0,
+                    2 => text_area.width() - width,
+                    _ => 0.0,
+                };
+            let y = first_baseline - size * LINE_HEIGHT * i as f64;
+            content
+                .next_line_at(x - previous.0, y - previous.1)
+                .show_text(&font.encode(line));
+            previous = (x, y);
+        }
+        content.end_text().restore().end_marked_content();
+        Ok((appearance, bbox))
+    }
+
+    /// Generates the on and off appearances of a check box or radio button widget, drawing
+    /// its `/MK /CA` caption in ZapfDingbats when on, a check mark or a dot by default.
+    fn generate_button_appearance(
+        &mut self,
+        field: &FormField,
+        widget: &mut PdfDict,
+        on: &str,
+    ) -> PdfResult<()> {
+        let rect = Annotation::from_dict(None, widget.clone()).rect()?;
+        let bbox = PdfRect::new(0.0, 0.0, rect.width(), rect.height());
+        let caption = match widget.get(b"MK") {
+            Some(mk) => match self.deref(mk)?.as_dict()?.get(b"CA") {
+                Some(caption) => Some(self.deref(caption)?.as_string()?.to_text()?),
+                None => None,
+            },
+            None => None,
+        };
+        let caption = caption.unwrap_or_else(|| match field.kind {
+            FieldKind::RadioButton => "l".to_string(),
+            _ => "4".to_string(),
+        });
+        let da = DefaultAppearance::parse(&field.default_appearance);
+        let font = Base14Font::ZapfDingbats;
+
+        let mut off = Appearance::default();
+        self.widget_frame(widget, &mut off)?;
+        let mut on_appearance = Appearance::default();
+        let area = self.widget_frame(widget, &mut on_appearance)?;
+        let size = match da.size {
+            size if size > 0.0 => size,
+            _ => area.width().min(area.height()) * 0.8,
+        };
+        let width = font.text_width(&caption, size);
+        let x = area.llx + (area.width() - width) / 2.0;
+        let y = area.lly + (area.height() - size * ASCENT) / 2.0;
+        on_appearance.add_font(b"ZaDb", font.font_dict());
+        on_appearance
+            .content
+            .begin_text()
+            .font(b"ZaDb", size)
+            .fill_color(&da.color)
+            .next_line_at(x, y)
+            .show_text(&font.encode(&caption))
+            .end_text();
+
+        let mut states = PdfDict::empty();
+        states.insert(
+            PdfName::from_raw_bytes(on.as_bytes()),
+            on_appearance.write(self, &bbox),
+        );
+        states.insert(
+            PdfName::from_raw_bytes(OFF.as_bytes()),
+            off.write(self, &bbox),
+        );
+        let mut ap = PdfDict::empty();
+        ap.insert(PdfName::from_raw_bytes(b"N"), states);
+        widget.insert(PdfName::from_raw_bytes(b"AP"), ap);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        FieldFlags, FieldKind, FieldValue, PdfErrorKind, PdfFile, PdfName, Resolve,
+        pdf_file::test_file,
+    };
+
+    fn form() -> Vec<u8> {
+        test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /AcroForm 4 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+                "<< /Type /Page /Parent 2 0 R /Annots [6 0 R 7 0 R 9 0 R 10 0 R 11 0 R 13 0 R] >>",
+                "<< /Fields [5 0 R 9 0 R 10 0 R 11 0 R 12 0 R] /DA (/Helv 0 Tf 0 g) >>",
+                "<< /T (person) /FT /Tx /Ff 2 /Kids [6 0 R 7 0 R 8 0 R] >>",
+                "<< /T (name) /Parent 5 0 R /Subtype /Widget /Rect [0 0 100 20] /MK << /BC [0] >> >>",
+                "<< /T (id) /Parent 5 0 R /Ff 3 /V (X1) /Subtype /Widget /Rect [0 30 100 50] >>",
+                "<< /T (notes) /Parent 5 0 R /Ff 4098 /Kids [14 0 R] >>",
+                "<< /T (agree) /FT /Btn /Subtype /Widget /Rect [0 60 10 70] /AS /Off /AP << /N << /On 15 0 R /Off 15 0 R >> >> >>",
+                "<< /T (colour) /FT /Ch /Ff 131072 /Opt [[(r) (Red)] [(g) (Green)]] /Subtype /Widget /Rect [0 80 100 100] /DA (/Cour 10 Tf) >>",
+                "<< /T (sig) /FT /Sig /Subtype /Widget /Rect [0 0 0 0] >>",
+                "<< /T (size) /FT /Btn /Ff 32768 /Kids [13 0 R 16 0 R] >>",
+                "<< /Parent 12 0 R /Subtype /Widget /Rect [20 60 30 70] /AP << /N << /S 15 0 R /Off 15 0 R >> >> >>",
+                "<< /Parent 8 0 R /Subtype /Widget /Rect [0 100 100 200] >>",
+                "<< /Subtype /Form /BBox [0 0 10 10] /Length 0 >> stream\n\nendstream",
+                "<< /Parent 12 0 R /Subtype /Widget /Rect [40 60 50 70] /AP << /N << /L 15 0 R /Off 15 0 R >> >> >>",
+            ],
+            "/Root 1 0 R",
+        )
+    }
+
+    #[test]
+    fn read() {
+        let bytes = form();
+        let file = PdfFile::load(&bytes).unwrap();
+        let fields = file.form_fields().unwrap();
+        let names: Vec<_> = fields.iter().map(|f| f.name()).collect();
+        assert_eq!(
+            names,
+            [
+                "person.name",
+                "person.id",
+                "person.notes",
+                "agree",
+                "colour",
+                "sig",
+                "size"
+            ]
+        );
+        let id = &fields[1];
+        assert_eq!(id.kind(), FieldKind::Text);
+        assert!(id.is_read_only() && id.is_required());
+        assert_eq!(id.value(), Some(&FieldValue::Text("X1".to_string())));
+        assert!(fields[2].flags().contains(FieldFlags::MULTILINE));
+        assert_eq!(fields[2].widgets().len(), 1);
+        assert_eq!(fields[3].kind(), FieldKind::CheckBox);
+        assert_eq!(fields[4].kind(), FieldKind::ComboBox);
+        assert_eq!(
+            fields[4].options().unwrap()[1],
+            ("g".to_string(), "Green".to_string())
+        );
+        assert_eq!(fields[5].kind(), FieldKind::Signature);
+        assert_eq!(fields[6].kind(), FieldKind::RadioButton);
+        assert_eq!(fields[6].widgets().len(), 2);
+        assert_eq!(
+            file.missing_required_fields().unwrap(),
+            ["person.name", "person.notes"]
+        );
+    }
+
+    #[test]
+    fn malformed_fields() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /AcroForm 3 0 R >>",
+                "<< /Type /Pages /Kids [] /Count 0 >>",
+                "<< /Fields [4 0 R 6 0 R] /DA (/Helv 0 Tf 0 g) >>",
+                "<< /T (code) /FT /Tx /MaxLen 3 /Kids [5 0 R] >>",
+                "<< /T (part) /Parent 4 0 R /Subtype /Widget /Rect [0 0 50 20] >>",
+                "<< /T (pick) /FT /Ch /V [(a) 7 <FEFF0062D83D>] >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        let pick = file.form_field("pick").unwrap().unwrap();
+        assert_eq!(
+            pick.value(),
+            Some(&FieldValue::Choices(vec![
+                "a".to_string(),
+                "b\u{fffd}".to_string()
+            ]))
+        );
+        // The /MaxLen of the parent applies to its kids
+        let error = file
+            .set_field_value("code.part", FieldValue::Text("abcd".to_string()))
+            .unwrap_err();
+        assert_eq!(error.kind(), &PdfErrorKind::InvalidData);
+        file.set_field_value("code.part", FieldValue::Text("abc".to_string()))
+            .unwrap();
+
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /AcroForm 3 0 R >>",
+                "<< /Type /Pages /Kids [] /Count 0 >>",
+                "<< /Fields [4 0 R] >>",
+                "<< /T (bits) /FT /Btn /Ff 4294967296 >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        let error = file.form_fields().unwrap_err();
+        assert_eq!(error.kind(), &PdfErrorKind::InvalidData);
+        assert_eq!(error.object(), Some((4, 0)));
+    }
+
+    #[test]
+    fn fill() {
+        let bytes = form();
+        let mut file = PdfFile::load(&bytes).unwrap();
+        file.set_field_value("person.name", FieldValue::Text("Ada Lovelace".to_string()))
+            .unwrap();
+        let error = file
+            .set_field_value("person.id", FieldValue::Text("X2".to_string()))
+            .unwrap_err();
+        assert_eq!(error.kind(), &PdfErrorKind::InvalidData);
+        file.set_checked("agree", true).unwrap();
+        file.set_field_value("size", FieldValue::State("L".to_string()))
+            .unwrap();
+        assert!(
+            file.set_field_value("size", FieldValue::State("XL".to_string()))
+                .is_err()
+        );
+        file.set_field_value("colour", FieldValue::Choices(vec!["g".to_string()]))
+            .unwrap();
+        assert!(
+            file.set_field_value("colour", FieldValue::Text("blue".to_string()))
+                .is_err()
+        );
+        assert!(
+            file.set_field_value("sig", FieldValue::Text("x".to_string()))
+                .is_err()
+        );
+
+        let written = file.to_bytes().unwrap();
+        let file = PdfFile::load(&written).unwrap();
+        let name = file.form_field("person.name").unwrap().unwrap();
+        assert_eq!(
+            name.value(),
+            Some(&FieldValue::Text("Ada Lovelace".to_string()))
+        );
+        let widget = file.resolve(&name.widgets()[0]).unwrap().as_dict().unwrap();
+        let ap = widget.get_dict(b"AP").unwrap().unwrap();
+        let stream = file
+            .deref(ap.get_or_null(b"N"))
+            .unwrap()
+            .as_stream()
+            .unwrap();
+        let content = String::from_utf8(stream.data().to_vec()).unwrap();
+        assert!(
+            content.starts_with("1 w\n0 G\n0.5 0.5 99 19 re\nS\n/Tx BMC\n"),
+            "{content}"
+        );
+        assert!(content.contains("(Ada Lovelace) Tj"), "{content}");
+        assert!(stream.info().get(b"Resources").is_some());
+
+        let agree = file.form_field("agree").unwrap().unwrap();
+        assert_eq!(agree.value(), Some(&FieldValue::State("On".to_string())));
+        assert_eq!(
+            agree.dict().get_name(b"AS"),
+            Ok(Some(&PdfName::from_raw_bytes(b"On")))
+        );
+
+        let size = file.form_field("size").unwrap().unwrap();
+        let states: Vec<_> = size
+            .widgets()
+            .iter()
+            .map(|w| {
+                let dict = file.resolve(w).unwrap().as_dict().unwrap();
+                dict.get_name(b"AS").unwrap().unwrap().clone()
+            })
+            .collect();
+        assert_eq!(states, [&b"Off"[..], b"L"].map(PdfName::from_raw_bytes));
+
+        let colour = file.form_field("colour").unwrap().unwrap();
+        let ap = colour.dict().get_dict(b"AP").unwrap().unwrap();
+        let stream = file
+            .deref(ap.get_or_null(b"N"))
+            .unwrap()
+            .as_stream()
+            .unwrap();
+        let content = String::from_utf8(stream.data().to_vec()).unwrap();
+        assert!(content.contains("/Cour 10 Tf\n0 g\n"), "{content}");
+        assert!(content.contains("(Green) Tj"), "{content}");
+        assert_eq!(file.missing_required_fields().unwrap(), ["person.notes"]);
+    }
+
+    #[test]
+    fn generated_check_box() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /AcroForm << /Fields [2 0 R] >> >>",
+                "<< /T (ok) /FT /Btn /Subtype /Widget /Rect [0 0 20 20] >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        file.set_checked("ok", true).unwrap();
+        let field = file.form_field("ok").unwrap().unwrap();
+        assert_eq!(field.value(), Some(&FieldValue::State("Yes".to_string())));
+        let ap = field.dict().get_dict(b"AP").unwrap().unwrap();
+        let states = ap.get_dict(b"N").unwrap().unwrap();
+        let on = file
+            .deref(states.get_or_null(b"Yes"))
+            .unwrap()
+            .as_stream()
+            .unwrap();
+        let content = String::from_utf8(on.data().to_vec()).unwrap();
+        assert!(content.contains("/ZaDb 16 Tf"), "{content}");
+        assert!(content.contains("(4) Tj"), "{content}");
+        assert!(states.get(b"Off").is_some());
+
+        file.set_checked("ok", false).unwrap();
+        let field = file.form_field("ok").unwrap().unwrap();
+        assert_eq!(field.value(), Some(&FieldValue::off()));
+        assert_eq!(
+            field.dict().get_name(b"AS"),
+            Ok(Some(&PdfName::from_raw_bytes(b"Off")))
+        );
+    }
+}
@@ -1,11 +1,42 @@
-use crate::pdf_error::{PdfError, PdfErrorKind};
-
+mod annotation;
+mod cleanup;
+mod content;
+mod destination;
+mod embedded_file;
 mod filter;
+mod font;
+mod form;
+mod merge;
+mod metadata;
+mod name_tree;
+mod outline;
+mod page_labels;
+mod pages;
 mod pdf_error;
 mod pdf_file;
+mod pdf_parser;
 mod types;
 
-pub use pdf_file::PdfFile;
+pub use annotation::{
+    Annotation, AnnotationFlags, AnnotationType, BorderLine, BorderStyle, Color, Quad,
+};
+pub use content::ContentBuilder;
+pub use destination::{Destination, DestinationView};
+pub use embedded_file::{AfRelationship, EmbeddedFile};
+pub use font::Base14Font;
+pub use form::{FieldFlags, FieldKind, FieldValue, FormField};
+pub use merge::{extract_pages, merge};
+pub use metadata::{DocumentInfo, XmpMetadata, XmpValue};
+pub use name_tree::{NameTree, NumberTree, SearchTree, SearchTreeIter, TreeKey};
+pub use outline::OutlineItem;
+pub use page_labels::{PageLabelRange, PageLabelStyle};
+pub use pages::Page;
+pub use pdf_error::{PdfError, PdfErrorKind, PdfResult};
+pub use pdf_file::{
+    LinearizationIssue, ObjectLocation, PdfBody, PdfCrossRefTable, PdfFile, PdfHeader, PdfTrailer,
+    PdfVersion, WriteOptions, XrefResolver,
+};
+pub use pdf_parser::*;
 pub use types::*;
 
 #[allow(dead_code)]
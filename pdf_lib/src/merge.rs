@@ -0,0 +1,813 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    Destination, IndirectData, OutlineItem, PdfArray, PdfDict, PdfFile, PdfName, PdfNull,
+    PdfNumeric, PdfObject, PdfObjectKind, PdfStream, PdfString, Resolve,
+    pages::INHERITABLE,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+};
+
+/// Copies objects of a source document into a target document, each indirect object once and
+/// under a new number.
+pub(crate) struct ObjectCopier<'s, 'a> {
+    source: &'s PdfFile<'a>,
+    /// Numbers in the target of the source objects copied so far.
+    copies: HashMap<IndirectData, IndirectData>,
+    /// Named destinations renamed in the target, whose uses by links, outline items and go-to
+    /// actions are renamed.
+    renamed: HashMap<PdfString, PdfString>,
+    /// Destinations of the catalog's `/Dests`, whose uses by name in links, outline items and
+    /// go-to actions become strings of the target's name tree.
+    renamed_names: HashMap<PdfName, PdfString>,
+    /// Source objects left out, references to them being dropped from arrays and dictionaries.
+    /// Explicit destinations to pages left out become null, or are dropped with their key.
+    excluded: HashSet<IndirectData>,
+}
+
+impl<'s, 'a> ObjectCopier<'s, 'a> {
+    pub fn new(source: &'s PdfFile<'a>) -> Self {
+        Self {
+            source,
+            copies: HashMap::new(),
+            renamed: HashMap::new(),
+            renamed_names: HashMap::new(),
+            excluded: HashSet::new(),
+        }
+    }
+
+    /// Sets the number of the copy of `reference`, to be written by the caller.
+    pub fn reserve(&mut self, target: &mut PdfFile, reference: &IndirectData) -> IndirectData {
+        let copy = target.add_object(PdfNull {});
+        self.copies.insert(reference.clone(), copy.clone());
+        copy
+    }
+
+    /// Copies `object`, then the objects it refers to that are not copied yet.
+    ///
+    /// References to missing objects are copied as references to null objects, and references
+    /// to excluded ones as null.
+    pub fn copy(&mut self, target: &mut PdfFile, object: &PdfObject) -> PdfResult<PdfObject> {
+        let Some(reference) = object.as_reference() else {
+            return self.copy_direct(target, object);
+        };
+        if self.excluded.contains(reference) {
+            return Ok(PdfNull {}.into());
+        }
+        if let Some(copy) = self.copies.get(reference) {
+            return Ok(copy.clone().into());
+        }
+        let copy = self.reserve(target, reference);
+        let value = match self.source.resolve(reference) {
+            Ok(value) => self.copy_direct(target, &value)?,
+            Err(e) if matches!(e.kind(), PdfErrorKind::UnresolvedReference(..)) => {
+                PdfNull {}.into()
+            }
+            Err(e) => return Err(e),
+        };
+        target.set_object(&copy, value);
+        Ok(copy.into())
+    }
+
+    /// Copies the value of `object`, as a direct object.
+    pub fn copy_direct(
+        &mut self,
+        target: &mut PdfFile,
+        object: &PdfObject,
+    ) -> PdfResult<PdfObject> {
+        Ok(match object.kind() {
+            PdfObjectKind::Array(array) if self.is_excluded_destination(array) => PdfNull {}.into(),
+            PdfObjectKind::Array(array) => {
+                let mut copy = PdfArray::new();
+                for item in array.iter() {
+                    if !self.is_excluded(item) {
+                        copy.push(self.copy(target, item)?);
+                    }
+                }
+                copy.into()
+            }
+            PdfObjectKind::Dict(dict) => self.copy_dict(target, dict)?.into(),
+            PdfObjectKind::Stream(stream) => {
+                let mut info = stream.info().clone();
+                info.remove(b"Length");
+                let info = self.copy_dict(target, &info)?;
+                PdfStream::new(info, stream.data().to_vec()).into()
+            }
+            PdfObjectKind::Ref => self.copy(target, object)?,
+            _ => object.clone(),
+        })
+    }
+
+    fn is_excluded(&self, object: &PdfObject) -> bool {
+        object
+            .as_reference()
+            .is_some_and(|r| self.excluded.contains(r))
+    }
+
+    /// Whether `array` is an explicit destination, a page followed by the name of its view,
+    /// to a page left out.
+    fn is_excluded_destination(&self, array: &PdfArray) -> bool {
+        array.get(0).is_some_and(|page| self.is_excluded(page))
+            && array.get(1).is_some_and(|view| view.as_name_ref().is_ok())
+    }
+
+    fn copy_dict(&mut self, target: &mut PdfFile, dict: &PdfDict) -> PdfResult<PdfDict> {
+        let mut copy = PdfDict::empty();
+        let destination_key = destination_key(dict);
+        for (key, value) in dict.iter() {
+            if self.is_excluded(value) {
+                continue;
+            }
+            let is_destination = destination_key == Some(key.bytes());
+            if is_destination
+                && value
+                    .as_array_ref()
+                    .is_ok_and(|a| self.is_excluded_destination(a))
+            {
+                continue;
+            }
+            let renamed = match (value.as_string_ref(), value.as_name_ref()) {
+                (Ok(name), _) if is_destination => self.renamed.get(name).cloned(),
+                (_, Ok(name)) if is_destination => self.renamed_names.get(name).cloned(),
+                _ => None,
+            };
+            let value = match renamed {
+                Some(name) => name.into(),
+                None => self.copy(target, value)?,
+            };
+            copy.insert(key.clone(), value);
+        }
+        Ok(copy)
+    }
+}
+
+/// The key of the destination of `dict` when it is a link annotation, an outline item or a
+/// go-to action, the dictionaries whose named destinations are those of the document.
+fn destination_key(dict: &PdfDict) -> Option<&'static [u8]> {
+    let name = |key: &[u8]| dict.get_name(key).ok().flatten().map(PdfName::bytes);
+    if name(b"Subtype") == Some(b"Link")
+        || dict.contains_key(b"Title") && dict.contains_key(b"Parent")
+    {
+        Some(b"Dest")
+    } else if name(b"S") == Some(b"GoTo") {
+        Some(b"D")
+    } else {
+        None
+    }
+}
+
+/// `name`, or the first of `name_2`, `name_3` and so on that is not in `used`.
+fn unused_name(name: &[u8], used: &HashSet<Vec<u8>>) -> Vec<u8> {
+    if !used.contains(name) {
+        return name.to_vec();
+    }
+    (2..)
+        .map(|i| [name, format!("_{i}").as_bytes()].concat())
+        .find(|n| !used.contains(n))
+        .expect("names run out after the used ones")
+}
+
+/// The items of `items` and their descendants leading to pages added, their destinations moved
+/// to the `positions` of the pages; the children of items left out take their place.
+///
+/// Items without destinations are kept.
+fn move_outline(items: Vec<OutlineItem>, positions: &HashMap<usize, usize>) -> Vec<OutlineItem> {
+    let mut moved = Vec::new();
+    for mut item in items {
+        let children = move_outline(std::mem::take(&mut item.children), positions);
+        if let Some(destination) = &mut item.destination {
+            match positions.get(&destination.page) {
+                Some(position) => destination.page = *position,
+                None => {
+                    moved.extend(children);
+                    continue;
+                }
+            }
+        }
+        item.children = children;
+        moved.push(item);
+    }
+    moved
+}
+
+/// Adds to `excluded` the fields under `node` with no widget in `widgets`, returning whether
+/// `node` has one.
+fn exclude_fields(
+    source: &PdfFile,
+    node: &IndirectData,
+    widgets: &HashSet<IndirectData>,
+    excluded: &mut HashSet<IndirectData>,
+    visited: &mut HashSet<IndirectData>,
+) -> PdfResult<bool> {
+    if !visited.insert(node.clone()) {
+        return Ok(!excluded.contains(node));
+    }
+    let mut kept = widgets.contains(node);
+    if let Some(kids) = source.resolve(node)?.as_dict()?.get(b"Kids") {
+        for kid in source.deref(kids)?.as_array()?.iter() {
+            if let Some(kid) = kid.as_reference() {
+                kept |= exclude_fields(source, kid, widgets, excluded, visited)?;
+            }
+        }
+    }
+    if !kept {
+        excluded.insert(node.clone());
+    }
+    Ok(kept)
+}
+
+/// A document made of the pages of others, with their outlines, named destinations and
+/// interactive forms.
+pub(crate) struct DocumentBuilder {
+    target: PdfFile<'static>,
+    pages_root: IndirectData,
+    pages: Vec<IndirectData>,
+    outline: Vec<OutlineItem>,
+    destinations: Vec<(PdfString, Destination)>,
+    destination_names: HashSet<Vec<u8>>,
+    form: Option<PdfDict>,
+    fields: PdfArray,
+    field_names: HashSet<Vec<u8>>,
+}
+
+impl DocumentBuilder {
+    pub fn new(target: PdfFile<'static>) -> PdfResult<Self> {
+        let pages_root = target
+            .catalog()?
+            .get_or_null(b"Pages")
+            .as_reference()
+            .cloned()
+            .ok_or_else(|| {
+                PdfError::with_kind(PdfErrorKind::WrongType)
+                    .expecting("a reference to the page tree")
+            })?;
+        Ok(Self {
+            target,
+            pages_root,
+            pages: Vec::new(),
+            outline: Vec::new(),
+            destinations: Vec::new(),
+            destination_names: HashSet::new(),
+            form: None,
+            fields: PdfArray::new(),
+            field_names: HashSet::new(),
+        })
+    }
+
+    /// Appends the pages of `source` at `indices`, in that order, with the objects they use.
+    ///
+    /// Pages listed again are added as new page objects sharing the content and resources of
+    /// the first copy, with copies of their annotations other than widgets, which belong to a
+    /// single field. Outline items, link annotations and named destinations leading to pages
+    /// left out are dropped, as are fields without widgets on the pages added. Destinations of
+    /// the catalog's `/Dests` dictionary move to the name tree, their uses by name becoming
+    /// strings. Named destinations and top level fields whose names are taken get a `_2`,
+    /// `_3`... suffix.
+    pub fn append(&mut self, source: &PdfFile, indices: &[usize]) -> PdfResult<()> {
+        let target = &mut self.target;
+        let pages = source.pages()?;
+        let offset = self.pages.len();
+        // Position in the document of the first copy of each page added
+        let mut positions = HashMap::new();
+        for (position, index) in indices.iter().enumerate() {
+            if *index >= pages.len() {
+                return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                    .expecting("a page index in range"));
+            }
+            positions.entry(*index).or_insert(offset + position);
+        }
+
+        let mut copier = ObjectCopier::new(source);
+        let mut widgets = HashSet::new();
+        for (index, page) in pages.iter().enumerate() {
+            let annots = match page.dict().get(b"Annots") {
+                Some(annots) => source.deref(annots)?.as_array()?,
+                None => PdfArray::new(),
+            };
+            let annots = annots.iter().filter_map(PdfObject::as_reference).cloned();
+            if positions.contains_key(&index) {
+                widgets.extend(annots);
+            } else {
+                copier.excluded.insert(page.reference().clone());
+                copier.excluded.extend(annots);
+            }
+        }
+        // Annotations on pages both added and left out are kept
+        copier.excluded.retain(|r| !widgets.contains(r));
+        let form = match source.catalog()?.get(b"AcroForm") {
+            Some(form) => Some(source.deref(form)?.as_dict()?),
+            None => None,
+        };
+        let roots = match form.as_ref().and_then(|f| f.get(b"Fields")) {
+            Some(fields) => source.deref(fields)?.as_array()?,
+            None => PdfArray::new(),
+        };
+        let mut visited = HashSet::new();
+        for root in roots.iter().filter_map(PdfObject::as_reference) {
+            exclude_fields(source, root, &widgets, &mut copier.excluded, &mut visited)?;
+        }
+
+        // Reserved first, for links and annotations to lead to the copies
+        let mut references = HashMap::new();
+        for index in positions.keys() {
+            let page = pages[*index].reference();
+            references.insert(*index, copier.reserve(target, page));
+        }
+
+        for (name, mut destination) in source.named_destinations()? {
+            let Some(position) = positions.get(&destination.page) else {
+                continue;
+            };
+            let unused =
+                PdfString::from_raw_bytes(&unused_name(name.bytes(), &self.destination_names));
+            if unused != name {
+                copier.renamed.insert(name, unused.clone());
+            }
+            self.destination_names.insert(unused.bytes().to_vec());
+            destination.page = *position;
+            self.destinations.push((unused, destination));
+        }
+        // The target has no `/Dests` dictionary, those destinations joining the name tree
+        for (name, mut destination) in source.catalog_destinations()? {
+            let Some(position) = positions.get(&destination.page) else {
+                continue;
+            };
+            let unused =
+                PdfString::from_raw_bytes(&unused_name(name.bytes(), &self.destination_names));
+            copier.renamed_names.insert(name, unused.clone());
+            self.destination_names.insert(unused.bytes().to_vec());
+            destination.page = *position;
+            self.destinations.push((unused, destination));
+        }
+        self.outline
+            .extend(move_outline(source.outline()?, &positions));
+
+        for (position, index) in indices.iter().enumerate() {
+            let page = &pages[*index];
+            let first = positions[index] == offset + position;
+            let reference = if first {
+                references[index].clone()
+            } else {
+                target.add_object(PdfNull {})
+            };
+            let mut dict = page.dict().clone();
+            for key in INHERITABLE {
+                if !dict.contains_key(key)
+                    && let Some(value) = page.attribute(key)
+                {
+                    dict.insert(PdfName::from_raw_bytes(key), value.clone());
+                }
+            }
+            // Threads are left out, their beads leading to every page of the source
+            dict.remove(b"Parent");
+            dict.remove(b"B");
+            let annots = dict.remove(b"Annots");
+            let mut copy = copier.copy_dict(target, &dict)?;
+            copy.insert(PdfName::from_raw_bytes(b"Parent"), self.pages_root.clone());
+
+            let mut copied = PdfArray::new();
+            let annots = match annots {
+                Some(annots) => source.deref(&annots)?.as_array()?,
+                None => PdfArray::new(),
+            };
+            for annot in annots.iter() {
+                let mut annot_dict = source.deref(annot)?.as_dict()?;
+                let subtype = annot_dict.get_name(b"Subtype")?.map(PdfName::bytes);
+                if subtype == Some(b"Link")
+                    && let Some(destination) = source.target_destination(&annot_dict, &pages)?
+                    && !positions.contains_key(&destination.page)
+                {
+                    continue;
+                }
+                if first {
+                    copied.push(copier.copy(target, annot)?);
+                } else if subtype != Some(b"Widget") {
+                    for key in [&b"P"[..], b"Popup", b"Parent", b"IRT"] {
+                        annot_dict.remove(key);
+                    }
+                    let mut annot_copy = copier.copy_dict(target, &annot_dict)?;
+                    annot_copy.insert(PdfName::from_raw_bytes(b"P"), reference.clone());
+                    copied.push(target.add_object(annot_copy));
+                }
+            }
+            if !copied.is_empty() {
+                copy.insert(PdfName::from_raw_bytes(b"Annots"), copied);
+            }
+            target.set_object(&reference, copy);
+            self.pages.push(reference);
+        }
+
+        let Some(form) = form else {
+            return Ok(());
+        };
+        for field in roots.iter() {
+            if copier.is_excluded(field) {
+                continue;
+            }
+            let copy = copier.copy(target, field)?;
+            if let Some(reference) = copy.as_reference() {
+                let mut dict = target.resolve(reference)?.as_dict()?;
+                if let Some(name) = dict.get(b"T") {
+                    let name = target.deref(name)?.as_string()?.to_text()?;
+                    let unused = unused_name(name.as_bytes(), &self.field_names);
+                    let unused = String::from_utf8(unused).expect("suffixes are ASCII");
+                    if unused != name {
+                        dict.insert(PdfName::from_raw_bytes(b"T"), target.text_string(&unused));
+                        target.set_object(reference, dict);
+                    }
+                    self.field_names.insert(unused.into_bytes());
+                }
+            }
+            self.fields.push(copy);
+        }
+        let mut form = copier.copy_dict(target, &form)?;
+        form.remove(b"Fields");
+        match &mut self.form {
+            None => self.form = Some(form),
+            Some(merged) => merge_form(target, merged, &form)?,
+        }
+        Ok(())
+    }
+
+    /// Writes the page tree and the document-level parts, returning the document.
+    pub fn finish(mut self) -> PdfResult<PdfFile<'static>> {
+        let mut root = self.target.resolve(&self.pages_root)?.as_dict()?;
+        let mut kids = PdfArray::new();
+        self.pages.iter().for_each(|p| kids.push(p.clone()));
+        root.insert(PdfName::from_raw_bytes(b"Kids"), kids);
+        root.insert(
+            PdfName::from_raw_bytes(b"Count"),
+            PdfNumeric::PdfInt(self.pages.len() as i64),
+        );
+        self.target.set_object(&self.pages_root, root);
+
+        self.target.set_outline(&self.outline)?;
+        self.target.set_named_destinations(&self.destinations)?;
+        if let Some(mut form) = self.form {
+            form.insert(PdfName::from_raw_bytes(b"Fields"), self.fields);
+            let form = self.target.add_object(form);
+            let catalog_reference = self.target.catalog_reference()?;
+            let mut catalog = self.target.catalog()?;
+            catalog.insert(PdfName::from_raw_bytes(b"AcroForm"), form);
+            self.target.set_object(&catalog_reference, catalog);
+        }
+        Ok(self.target)
+    }
+}
+
+/// Adds the resources of `form` missing from the default resources of `merged`, and asks for
+/// appearances to be regenerated if either does.
+fn merge_form(target: &mut PdfFile, merged: &mut PdfDict, form: &PdfDict) -> PdfResult<()> {
+    if let Some(resources) = form.get(b"DR") {
+        let resources = target.deref(resources)?.as_dict()?;
+        let mut all = match merged.get(b"DR") {
+            Some(all) => target.deref(all)?.as_dict()?,
+            None => PdfDict::empty(),
+        };
+        for (category, entries) in resources.iter() {
+            let entries = target.deref(entries)?.as_dict()?;
+            let mut combined = match all.get(category.bytes()) {
+                Some(combined) => target.deref(combined)?.as_dict()?,
+                None => PdfDict::empty(),
+            };
+            for (name, value) in entries.iter() {
+                if !combined.contains_key(name.bytes()) {
+                    combined.insert(name.clone(), value.clone());
+                }
+            }
+            all.insert(category.clone(), combined);
+        }
+        merged.insert(PdfName::from_raw_bytes(b"DR"), all);
+    }
+    if let Some(need) = form.get(b"NeedAppearances")
+        && matches!(target.deref(need)?.kind(), PdfObjectKind::Boolean(true))
+    {
+        merged.insert(PdfName::from_raw_bytes(b"NeedAppearances"), true);
+    }
+    Ok(())
+}
+
+/// A new document made of the pages of `files` in order, with their outlines, named
+/// destinations and form fields.
+///
+/// The objects each file uses are copied once, under new numbers. Named destinations and top
+/// level fields whose names are taken by a previous file are renamed with a `_2`, `_3`...
+/// suffix. The header is the one of the latest PDF version among the files.
+pub fn merge(files: &[PdfFile]) -> PdfResult<PdfFile<'static>> {
+    let header = files
+        .iter()
+        .map(PdfFile::header)
+        .max_by_key(|h| h.version())
+        .ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a document to merge")
+        })?;
+    let mut builder = DocumentBuilder::new(PdfFile::new(*header))?;
+    for file in files {
+        let indices: Vec<_> = (0..file.pages()?.len()).collect();
+        builder.append(file, &indices)?;
+    }
+    builder.finish()
+}
+
+/// A new document made of the pages of `source` at `indices`, in that order, such as a range
+/// to extract a part, a permutation to reorder pages, or an index listed twice to duplicate a
+/// page.
+///
+/// Only the objects the pages and the document-level parts kept use are copied, under new
+/// numbers. Outline items and link annotations leading to pages left out are removed, the
+/// children of removed items taking their place, as are fields whose widgets are all on pages
+/// left out.
+pub fn extract_pages(source: &PdfFile, indices: &[usize]) -> PdfResult<PdfFile<'static>> {
+    let mut builder = DocumentBuilder::new(PdfFile::new(*source.header()))?;
+    builder.append(source, indices)?;
+    builder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Destination, FieldValue, IndirectData, OutlineItem, PdfFile, PdfString, Resolve,
+        extract_pages, merge, pdf_file::test_file,
+    };
+
+    #[test]
+    fn merge_files() {
+        let first = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /Outlines 6 0 R /Names << /Dests 8 0 R >> /AcroForm << /Fields [9 0 R] /DR << /Font << /Helv 10 0 R >> >> >> >>",
+                "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 /Resources 5 0 R /MediaBox [0 0 100 100] >>",
+                "<< /Type /Page /Parent 2 0 R /Annots [9 0 R] >>",
+                "<< /Type /Page /Parent 2 0 R >>",
+                "<< /Font << /F1 10 0 R >> >>",
+                "<< /Type /Outlines /First 7 0 R /Last 7 0 R /Count 1 >>",
+                "<< /Title (First) /Parent 6 0 R /Dest [4 0 R /Fit] >>",
+                "<< /Names [(end) [4 0 R /Fit]] >>",
+                "<< /T (name) /FT /Tx /V (Ada) /Subtype /Widget /Rect [0 0 10 10] /P 3 0 R >>",
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let second = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /Names << /Dests << /Names [(end) [3 0 R /Fit]] >> >> /AcroForm << /Fields [4 0 R] >> >>",
+                "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+                "<< /Type /Page /Parent 2 0 R /Annots [4 0 R 5 0 R] >>",
+                "<< /T (name) /FT /Tx /V (Bob) /Subtype /Widget /Rect [0 0 10 10] /P 3 0 R >>",
+                "<< /Subtype /Link /Rect [0 0 5 5] /Dest (end) >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let files = [
+            PdfFile::load(&first).unwrap(),
+            PdfFile::load(&second).unwrap(),
+        ];
+        let merged = merge(&files).unwrap();
+        let written = merged.to_bytes().unwrap();
+        let file = PdfFile::load(&written).unwrap();
+
+        let pages = file.pages().unwrap();
+        assert_eq!(pages.len(), 3);
+        assert!(pages[0].dict().get(b"MediaBox").is_some());
+        // The resources shared by the first two pages and the form are copied once
+        let resources: Vec<_> = pages[..2]
+            .iter()
+            .map(|p| p.dict().get(b"Resources").unwrap().as_reference().cloned())
+            .collect();
+        assert_eq!(resources[0], resources[1]);
+        let resources = file
+            .deref(pages[0].dict().get_or_null(b"Resources"))
+            .unwrap()
+            .as_dict()
+            .unwrap();
+        let page_font = resources.get_dict(b"Font").unwrap().unwrap().get(b"F1");
+        let form = file
+            .deref(file.catalog().unwrap().get_or_null(b"AcroForm"))
+            .unwrap()
+            .as_dict()
+            .unwrap();
+        let form_resources = form.get_dict(b"DR").unwrap().unwrap();
+        let form_font = form_resources
+            .get_dict(b"Font")
+            .unwrap()
+            .unwrap()
+            .get(b"Helv");
+        assert_eq!(page_font, form_font);
+
+        assert_eq!(
+            file.outline(),
+            Ok(vec![OutlineItem::new("First", Some(Destination::new(1)))])
+        );
+        let names = file.named_destinations().unwrap();
+        assert_eq!(
+            names,
+            [
+                (PdfString::from_raw_bytes(b"end"), Destination::new(1)),
+                (PdfString::from_raw_bytes(b"end_2"), Destination::new(2)),
+            ]
+        );
+        let annots = pages[2].dict().get_array(b"Annots").unwrap().unwrap();
+        let link = file.deref(&annots[1]).unwrap().as_dict().unwrap();
+        assert_eq!(
+            link.get(b"Dest").unwrap().as_string_ref(),
+            Ok(&PdfString::from_raw_bytes(b"end_2"))
+        );
+
+        let fields: Vec<_> = file
+            .form_fields()
+            .unwrap()
+            .into_iter()
+            .map(|f| (f.name().to_string(), f.value().cloned()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                (
+                    "name".to_string(),
+                    Some(FieldValue::Text("Ada".to_string()))
+                ),
+                (
+                    "name_2".to_string(),
+                    Some(FieldValue::Text("Bob".to_string()))
+                ),
+            ]
+        );
+        let widget = file.form_fields().unwrap()[1].widgets()[0].clone();
+        let widget = file.resolve(&widget).unwrap().as_dict().unwrap();
+        assert_eq!(
+            widget.get(b"P").unwrap().as_reference(),
+            Some(pages[2].reference())
+        );
+        assert!(file.resolve(&IndirectData::new(1, 0)).is_ok());
+    }
+
+    #[test]
+    fn catalog_destinations() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /Dests << /end 6 0 R /gone [4 0 R /Fit] >> >>",
+                "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+                "<< /Type /Page /Parent 2 0 R /Annots [5 0 R 7 0 R] >>",
+                "<< /Type /Page /Parent 2 0 R >>",
+                "<< /Subtype /Link /Rect [0 0 5 5] /Dest /end >>",
+                "<< /D [3 0 R /Fit] >>",
+                "<< /Subtype /Link /Rect [0 0 5 5] /A << /S /GoTo /D /gone >> >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let source = PdfFile::load(&bytes).unwrap();
+        let part = extract_pages(&source, &[0]).unwrap().to_bytes().unwrap();
+        let extracted = PdfFile::load(&part).unwrap();
+        let pages = extracted.pages().unwrap();
+        let annots = pages[0].dict().get_array(b"Annots").unwrap().unwrap();
+        assert_eq!(annots.len(), 1);
+        let link = extracted.deref(&annots[0]).unwrap().as_dict().unwrap();
+        assert_eq!(
+            link.get(b"Dest").unwrap().as_string_ref(),
+            Ok(&PdfString::from_raw_bytes(b"end"))
+        );
+
+        let files = [extracted, PdfFile::load(&bytes).unwrap()];
+        let written = merge(&files).unwrap().to_bytes().unwrap();
+        let file = PdfFile::load(&written).unwrap();
+        assert!(file.catalog().unwrap().get(b"Dests").is_none());
+        assert_eq!(
+            file.named_destinations().unwrap(),
+            [
+                (PdfString::from_raw_bytes(b"end"), Destination::new(0)),
+                (PdfString::from_raw_bytes(b"end_2"), Destination::new(1)),
+                (PdfString::from_raw_bytes(b"gone"), Destination::new(2)),
+            ]
+        );
+        let pages = file.pages().unwrap();
+        let annots = pages[1].dict().get_array(b"Annots").unwrap().unwrap();
+        let targets: Vec<_> = annots
+            .iter()
+            .map(|a| {
+                let link = file.deref(a).unwrap().as_dict().unwrap();
+                file.target_destination(&link, &pages).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            targets,
+            [Some(Destination::new(1)), Some(Destination::new(2))]
+        );
+    }
+
+    #[test]
+    fn destinations_to_excluded_pages() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /Dests << /end [3 0 R /Fit] >> >>",
+                "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>",
+                "<< /Type /Page /Parent 2 0 R /Annots [5 0 R] /AA << /O 6 0 R /C 7 0 R >> /X [4 0 R /XYZ 0 0 0] >>",
+                "<< /Type /Page /Parent 2 0 R >>",
+                "<< /Subtype /Text /Rect [0 0 5 5] /Dest /end >>",
+                "<< /S /GoTo /D [4 0 R /Fit] >>",
+                "<< /S /GoTo /D /end >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let source = PdfFile::load(&bytes).unwrap();
+        let part = extract_pages(&source, &[0]).unwrap().to_bytes().unwrap();
+        let file = PdfFile::load(&part).unwrap();
+        let page = file.pages().unwrap()[0].dict().clone();
+        assert!(page.get(b"X").unwrap().as_null_ref().is_ok());
+        let actions = file
+            .deref(page.get(b"AA").unwrap())
+            .unwrap()
+            .as_dict()
+            .unwrap();
+        let action = |key: &[u8]| {
+            file.deref(actions.get(key).unwrap())
+                .unwrap()
+                .as_dict()
+                .unwrap()
+        };
+        assert!(action(b"O").get(b"D").is_none());
+        assert_eq!(
+            action(b"C").get(b"D").unwrap().as_string_ref(),
+            Ok(&PdfString::from_raw_bytes(b"end"))
+        );
+        // Only links, outline items and go-to actions are renamed
+        let annots = page.get_array(b"Annots").unwrap().unwrap();
+        let note = file.deref(&annots[0]).unwrap().as_dict().unwrap();
+        assert!(note.get(b"Dest").unwrap().as_name_ref().is_ok());
+    }
+
+    #[test]
+    fn extract() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /Outlines 7 0 R /AcroForm << /Fields [12 0 R 15 0 R] >> >>",
+                "<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R 6 0 R] /Count 4 >>",
+                "<< /Type /Page /Parent 2 0 R /Contents 17 0 R /Annots [18 0 R 19 0 R] >>",
+                "<< /Type /Page /Parent 2 0 R /Contents 16 0 R /Annots [13 0 R] >>",
+                "<< /Type /Page /Parent 2 0 R /Annots [15 0 R] >>",
+                "<< /Type /Page /Parent 2 0 R /Annots [14 0 R] >>",
+                "<< /Type /Outlines /First 8 0 R /Last 11 0 R /Count 3 >>",
+                "<< /Title (A) /Parent 7 0 R /Next 10 0 R /First 9 0 R /Last 9 0 R /Count 1 /Dest [3 0 R /Fit] >>",
+                "<< /Title (A.1) /Parent 8 0 R /Dest [5 0 R /Fit] >>",
+                "<< /Title (B) /Parent 7 0 R /Prev 8 0 R /Next 11 0 R /First 20 0 R /Last 20 0 R /Count 1 /Dest [4 0 R /Fit] >>",
+                "<< /Title (C) /Parent 7 0 R /Prev 10 0 R >>",
+                "<< /T (parent) /FT /Tx /Kids [13 0 R 14 0 R] >>",
+                "<< /Parent 12 0 R /Subtype /Widget /Rect [0 0 10 10] /P 4 0 R >>",
+                "<< /Parent 12 0 R /Subtype /Widget /Rect [0 0 10 10] /P 6 0 R >>",
+                "<< /T (solo) /FT /Tx /Subtype /Widget /Rect [0 0 10 10] /P 5 0 R >>",
+                "<< /Length 9 >> stream\n(dropped)\nendstream",
+                "<< /Length 6 >> stream\n(kept)\nendstream",
+                "<< /Subtype /Link /Rect [0 0 5 5] /P 3 0 R /Dest [6 0 R /Fit] >>",
+                "<< /Subtype /Link /Rect [0 0 5 5] /P 3 0 R /A << /S /GoTo /D [4 0 R /Fit] >> >>",
+                "<< /Title (B.1) /Parent 10 0 R /Dest [6 0 R /Fit] >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let source = PdfFile::load(&bytes).unwrap();
+        let extracted = extract_pages(&source, &[3, 0, 0]).unwrap();
+        assert!(extract_pages(&source, &[4]).is_err());
+        let written = extracted.to_bytes().unwrap();
+        let file = PdfFile::load(&written).unwrap();
+
+        let text = String::from_utf8_lossy(&written);
+        assert!(text.contains("(kept)") && !text.contains("(dropped)"));
+        let pages = file.pages().unwrap();
+        assert_eq!(pages.len(), 3);
+        assert_ne!(pages[1].reference(), pages[2].reference());
+        assert_eq!(
+            pages[1].dict().get(b"Contents"),
+            pages[2].dict().get(b"Contents")
+        );
+
+        let outline = file.outline().unwrap();
+        let titles: Vec<_> = outline
+            .iter()
+            .map(|i| {
+                (
+                    i.title.as_str(),
+                    i.destination.as_ref().map(|d| d.page),
+                    i.children.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            titles,
+            [("A", Some(1), 0), ("B.1", Some(0), 0), ("C", None, 0)]
+        );
+
+        // The link to a page left out is removed, and the one kept copied onto the duplicate
+        for page in &pages[1..] {
+            let annots = page.dict().get_array(b"Annots").unwrap().unwrap();
+            assert_eq!(annots.len(), 1);
+            let link = file.deref(&annots[0]).unwrap().as_dict().unwrap();
+            assert_eq!(
+                link.get(b"P").unwrap().as_reference(),
+                Some(page.reference())
+            );
+            let destination = file.target_destination(&link, &pages).unwrap();
+            assert_eq!(destination, Some(Destination::new(0)));
+        }
+
+        let fields = file.form_fields().unwrap();
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].name(), "parent");
+        assert_eq!(fields[0].widgets().len(), 1);
+    }
+}
@@ -0,0 +1,366 @@
+use crate::{
+    PdfDate, PdfDict, PdfFile, PdfName, PdfObject, PdfStream, PdfString, PdfVersion, Resolve,
+    pdf_error::PdfResult,
+};
+
+pub use xmp::{XmpMetadata, XmpValue};
+
+mod xml;
+mod xmp;
+
+/// The document information shared by the trailer's `/Info` dictionary (14.3.3) and the XMP
+/// metadata of the catalog.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentInfo {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    /// Application that created the original document.
+    pub creator: Option<String>,
+    /// Application that converted the document to PDF.
+    pub producer: Option<String>,
+    pub creation_date: Option<PdfDate>,
+    pub mod_date: Option<PdfDate>,
+}
+
+/// Separates the authors of an XMP `dc:creator` array when read as a single author.
+const AUTHOR_SEPARATOR: &str = ", ";
+
+impl DocumentInfo {
+    fn from_xmp(xmp: &XmpMetadata) -> Self {
+        let text = |namespace, name| Some(xmp.get(namespace, name)?.text()?.to_owned());
+        let date = |name| PdfDate::from_iso8601(xmp.get(XmpMetadata::XMP, name)?.text()?).ok();
+        let author = xmp
+            .get(XmpMetadata::DC, "creator")
+            .map(|value| match value {
+                XmpValue::Seq(authors) | XmpValue::Bag(authors) => authors.join(AUTHOR_SEPARATOR),
+                value => value.text().unwrap_or_default().to_owned(),
+            });
+        Self {
+            title: text(XmpMetadata::DC, "title"),
+            author,
+            subject: text(XmpMetadata::DC, "description"),
+            keywords: text(XmpMetadata::PDF, "Keywords"),
+            creator: text(XmpMetadata::XMP, "CreatorTool"),
+            producer: text(XmpMetadata::PDF, "Producer"),
+            creation_date: date("CreateDate"),
+            mod_date: date("ModifyDate"),
+        }
+    }
+
+    /// The fields of `self`, completed by those of `other`.
+    fn or(self, other: Self) -> Self {
+        Self {
+            title: self.title.or(other.title),
+            author: self.author.or(other.author),
+            subject: self.subject.or(other.subject),
+            keywords: self.keywords.or(other.keywords),
+            creator: self.creator.or(other.creator),
+            producer: self.producer.or(other.producer),
+            creation_date: self.creation_date.or(other.creation_date),
+            mod_date: self.mod_date.or(other.mod_date),
+        }
+    }
+
+    fn texts(&self) -> [(&'static [u8], &Option<String>); 6] {
+        [
+            (b"Title", &self.title),
+            (b"Author", &self.author),
+            (b"Subject", &self.subject),
+            (b"Keywords", &self.keywords),
+            (b"Creator", &self.creator),
+            (b"Producer", &self.producer),
+        ]
+    }
+
+    fn dates(&self) -> [(&'static [u8], &Option<PdfDate>); 2] {
+        [
+            (b"CreationDate", &self.creation_date),
+            (b"ModDate", &self.mod_date),
+        ]
+    }
+
+    /// Sets the entries of an `/Info` dictionary, removing those of the fields left empty.
+    fn write_to_dict(&self, dict: &mut PdfDict, version: PdfVersion) {
+        for (key, value) in self.texts() {
+            match value {
+                Some(text) => dict.insert(
+                    PdfName::from_raw_bytes(key),
+                    PdfString::from_text_in(text, version),
+                ),
+                None => dict.remove(key),
+            };
+        }
+        for (key, value) in self.dates() {
+            match value {
+                Some(date) => dict.insert(PdfName::from_raw_bytes(key), PdfString::from(*date)),
+                None => dict.remove(key),
+            };
+        }
+    }
+
+    /// Sets the matching XMP properties, removing those of the fields left empty.
+    fn write_to_xmp(&self, xmp: &mut XmpMetadata) -> PdfResult<()> {
+        let default = |text: &String| {
+            XmpValue::Alt(vec![(XmpValue::DEFAULT_LANGUAGE.to_owned(), text.clone())])
+        };
+        let properties = [
+            (XmpMetadata::DC, "title", self.title.as_ref().map(default)),
+            (
+                XmpMetadata::DC,
+                "creator",
+                self.author.clone().map(|a| XmpValue::Seq(vec![a])),
+            ),
+            (
+                XmpMetadata::DC,
+                "description",
+                self.subject.as_ref().map(default),
+            ),
+            (
+                XmpMetadata::PDF,
+                "Keywords",
+                self.keywords.clone().map(XmpValue::Text),
+            ),
+            (
+                XmpMetadata::XMP,
+                "CreatorTool",
+                self.creator.clone().map(XmpValue::Text),
+            ),
+            (
+                XmpMetadata::PDF,
+                "Producer",
+                self.producer.clone().map(XmpValue::Text),
+            ),
+            (XmpMetadata::XMP, "CreateDate", iso8601(self.creation_date)),
+            (XmpMetadata::XMP, "ModifyDate", iso8601(self.mod_date)),
+            (XmpMetadata::XMP, "MetadataDate", iso8601(self.mod_date)),
+        ];
+        for (namespace, name, value) in properties {
+            match value {
+                Some(value) => xmp.set(namespace, name, value)?,
+                None => {
+                    xmp.remove(namespace, name);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn iso8601(date: Option<PdfDate>) -> Option<XmpValue> {
+    date.map(|d| XmpValue::Text(d.to_iso8601()))
+}
+
+impl PdfFile<'_> {
+    /// The document information, read from both the `/Info` dictionary and the XMP metadata.
+    ///
+    /// When both give a field, the most recently modified source wins, `/Info` if that cannot be
+    /// told. Dates that cannot be parsed are left out.
+    pub fn info(&self) -> PdfResult<DocumentInfo> {
+        let from_dict = match self.info_dict()? {
+            Some(dict) => self.info_from_dict(&dict)?,
+            None => DocumentInfo::default(),
+        };
+        let Some(xmp) = self.xmp_metadata()? else {
+            return Ok(from_dict);
+        };
+        let from_xmp = DocumentInfo::from_xmp(&xmp);
+        let xmp_is_newer = matches!(
+            (from_xmp.mod_date, from_dict.mod_date),
+            (Some(xmp), Some(dict)) if xmp.timestamp() > dict.timestamp()
+        );
+        Ok(if xmp_is_newer {
+            from_xmp.or(from_dict)
+        } else {
+            from_dict.or(from_xmp)
+        })
+    }
+
+    /// Sets the document information in both the `/Info` dictionary and the XMP metadata, adding
+    /// them if missing. Fields left empty are removed.
+    pub fn set_info(&mut self, info: &DocumentInfo) -> PdfResult<()> {
+        let mut dict = self.info_dict()?.unwrap_or_default();
+        info.write_to_dict(&mut dict, self.header().version());
+        match self
+            .trailer()
+            .get(b"Info")
+            .and_then(PdfObject::as_reference)
+        {
+            Some(reference) => {
+                let reference = reference.clone();
+                self.set_object(&reference, dict);
+            }
+            None => {
+                let reference = self.add_object(dict);
+                self.trailer_mut()
+                    .insert(PdfName::from_raw_bytes(b"Info"), reference);
+            }
+        }
+
+        let mut xmp = self.xmp_metadata()?.unwrap_or_default();
+        info.write_to_xmp(&mut xmp)?;
+        self.set_xmp_metadata(&xmp)
+    }
+
+    /// The XMP metadata of the document, from the catalog's `/Metadata` stream.
+    pub fn xmp_metadata(&self) -> PdfResult<Option<XmpMetadata>> {
+        let catalog = self.catalog()?;
+        let Some(metadata) = catalog.get(b"Metadata") else {
+            return Ok(None);
+        };
+        let stream = self.deref(metadata)?.as_stream()?;
+        XmpMetadata::parse(&stream.decoded_data()?).map(Some)
+    }
+
+    /// Replaces the catalog's `/Metadata` stream, written without filters.
+    pub fn set_xmp_metadata(&mut self, xmp: &XmpMetadata) -> PdfResult<()> {
+        let mut info = PdfDict::empty();
+        info.insert(
+            PdfName::from_raw_bytes(b"Type"),
+            PdfName::from_raw_bytes(b"Metadata"),
+        );
+        info.insert(
+            PdfName::from_raw_bytes(b"Subtype"),
+            PdfName::from_raw_bytes(b"XML"),
+        );
+        let stream = PdfStream::new(info, xmp.to_bytes());
+
+        let mut catalog = self.catalog()?;
+        match catalog.get(b"Metadata").and_then(PdfObject::as_reference) {
+            Some(reference) => {
+                let reference = reference.clone();
+                self.set_object(&reference, stream);
+            }
+            None => {
+                let catalog_reference = self.catalog_reference()?;
+                let reference = self.add_object(stream);
+                catalog.insert(PdfName::from_raw_bytes(b"Metadata"), reference);
+                self.set_object(&catalog_reference, catalog);
+            }
+        }
+        Ok(())
+    }
+
+    fn info_dict(&self) -> PdfResult<Option<PdfDict>> {
+        self.trailer()
+            .get(b"Info")
+            .map(|info| self.deref(info)?.as_dict())
+            .transpose()
+    }
+
+    fn info_from_dict(&self, dict: &PdfDict) -> PdfResult<DocumentInfo> {
+        let text = |key: &[u8]| -> PdfResult<Option<String>> {
+            dict.get(key)
+                .map(|value| self.deref(value)?.as_string()?.to_text())
+                .transpose()
+        };
+        let date = |key: &[u8]| -> PdfResult<Option<PdfDate>> {
+            Ok(dict
+                .get(key)
+                .map(|value| self.deref(value)?.as_string())
+                .transpose()?
+                .and_then(|string| PdfDate::try_from(&string).ok()))
+        };
+        Ok(DocumentInfo {
+            title: text(b"Title")?,
+            author: text(b"Author")?,
+            subject: text(b"Subject")?,
+            keywords: text(b"Keywords")?,
+            creator: text(b"Creator")?,
+            producer: text(b"Producer")?,
+            creation_date: date(b"CreationDate")?,
+            mod_date: date(b"ModDate")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PdfDate, PdfFile, Resolve, pdf_file::test_file};
+
+    use super::*;
+
+    #[test]
+    fn info_dict() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog >>",
+                "<< /Title (Report) /Author <FEFF0041006E006E00E9> /ModDate (D:20240102030405Z) /CreationDate (garbage) >>",
+            ],
+            "/Root 1 0 R /Info 2 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        let info = file.info().unwrap();
+        assert_eq!(info.title.as_deref(), Some("Report"));
+        assert_eq!(info.author.as_deref(), Some("Anné"));
+        assert_eq!(info.creation_date, None);
+        assert_eq!(
+            info.mod_date,
+            Some(
+                PdfDate::new(2024, 1, 2, 3, 4, 5)
+                    .unwrap()
+                    .with_utc_offset(0)
+            )
+        );
+        assert_eq!(file.xmp_metadata(), Ok(None));
+    }
+
+    #[test]
+    fn set_info_syncs_xmp() {
+        let bytes = test_file::build(&["<< /Type /Catalog >>"], "/Root 1 0 R");
+        let mut file = PdfFile::load(&bytes).unwrap();
+        let date = PdfDate::new(2024, 5, 6, 7, 8, 9)
+            .unwrap()
+            .with_utc_offset(120);
+        let info = DocumentInfo {
+            title: Some("Café <menu>".into()),
+            author: Some("Ann".into()),
+            producer: Some("pdf_lib".into()),
+            mod_date: Some(date),
+            ..Default::default()
+        };
+        file.set_info(&info).unwrap();
+
+        let written = file.to_bytes().unwrap();
+        let file = PdfFile::load(&written).unwrap();
+        assert_eq!(file.info(), Ok(info.clone()));
+        let xmp = file.xmp_metadata().unwrap().unwrap();
+        assert_eq!(DocumentInfo::from_xmp(&xmp), info);
+        assert_eq!(
+            xmp.get(XmpMetadata::XMP, "ModifyDate"),
+            Some(XmpValue::Text("2024-05-06T07:08:09+02:00".into()))
+        );
+        let catalog = file.catalog().unwrap();
+        let metadata = file.deref(catalog.get_or_null(b"Metadata")).unwrap();
+        assert_eq!(
+            metadata.as_stream().unwrap().info().get_name(b"Subtype"),
+            Ok(Some(&PdfName::from_raw_bytes(b"XML")))
+        );
+    }
+
+    #[test]
+    fn newer_source_wins() {
+        let packet = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"><rdf:Description xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:ModifyDate="2025-01-01T00:00:00Z"><dc:title><rdf:Alt><rdf:li xml:lang="x-default">XMP title</rdf:li></rdf:Alt></dc:title><dc:subject><rdf:Bag><rdf:li>ignored</rdf:li></rdf:Bag></dc:subject><dc:description><rdf:Alt><rdf:li xml:lang="x-default">XMP subject</rdf:li></rdf:Alt></dc:description></rdf:Description></rdf:RDF></x:xmpmeta>"#;
+        let stream = format!("<< /Length {} >> stream\n{packet}\nendstream", packet.len());
+        let build = |mod_date: &str| {
+            test_file::build(
+                &[
+                    "<< /Type /Catalog /Metadata 3 0 R >>",
+                    &format!("<< /Title (Info title) /ModDate ({mod_date}) >>"),
+                    &stream,
+                ],
+                "/Root 1 0 R /Info 2 0 R",
+            )
+        };
+        let older = build("D:2020");
+        let info = PdfFile::load(&older).unwrap().info().unwrap();
+        assert_eq!(info.title.as_deref(), Some("XMP title"));
+        assert_eq!(info.subject.as_deref(), Some("XMP subject"));
+
+        let newer = build("D:2026");
+        let info = PdfFile::load(&newer).unwrap().info().unwrap();
+        assert_eq!(info.title.as_deref(), Some("Info title"));
+        assert_eq!(info.subject.as_deref(), Some("XMP subject"));
+    }
+}
@@ -0,0 +1,401 @@
+//! Minimal XML tree, enough to read and rewrite XMP packets.
+//!
+//! Namespaces are resolved while parsing. Document type declarations are kept but not
+//! interpreted, so only the predefined and numeric character references are decoded.
+
+use crate::pdf_error::*;
+
+pub(crate) const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum XmlNode {
+    Element(XmlElement),
+    Text(String),
+    /// Comment, processing instruction or declaration, kept as written.
+    Other(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct XmlElement {
+    /// Qualified name, as written.
+    pub name: String,
+    /// Namespace of the name, resolved from the declarations in scope.
+    pub namespace: Option<String>,
+    pub attributes: Vec<XmlAttribute>,
+    pub children: Vec<XmlNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct XmlAttribute {
+    pub name: String,
+    pub namespace: Option<String>,
+    pub value: String,
+}
+
+fn local_name(name: &str) -> &str {
+    name.split_once(':').map_or(name, |(_, local)| local)
+}
+
+impl XmlElement {
+    pub fn new(name: String, namespace: Option<String>) -> Self {
+        Self {
+            name,
+            namespace,
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn local_name(&self) -> &str {
+        local_name(&self.name)
+    }
+
+    pub fn is(&self, namespace: &str, local: &str) -> bool {
+        self.namespace.as_deref() == Some(namespace) && self.local_name() == local
+    }
+
+    pub fn attribute(&self, namespace: &str, local: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.is(namespace, local))
+            .map(|a| a.value.as_str())
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &XmlElement> {
+        self.children.iter().filter_map(|c| match c {
+            XmlNode::Element(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// Concatenated text of the element and its descendants.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for child in &self.children {
+            match child {
+                XmlNode::Text(t) => text.push_str(t),
+                XmlNode::Element(e) => text.push_str(&e.text()),
+                XmlNode::Other(_) => {}
+            }
+        }
+        text
+    }
+
+    /// The prefixes declared by the element, with their namespace, `""` for the default one.
+    pub fn declarations(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attributes.iter().filter_map(|a| {
+            let prefix = match a.name.split_once(':') {
+                Some(("xmlns", prefix)) => prefix,
+                None if a.name == "xmlns" => "",
+                _ => return None,
+            };
+            Some((prefix, a.value.as_str()))
+        })
+    }
+
+    fn write(&self, out: &mut String) {
+        out.push('<');
+        out.push_str(&self.name);
+        for attribute in &self.attributes {
+            out.push(' ');
+            out.push_str(&attribute.name);
+            out.push_str("=\"");
+            escape(&attribute.value, true, out);
+            out.push('"');
+        }
+        if self.children.is_empty() {
+            out.push_str("/>");
+            return;
+        }
+        out.push('>');
+        write_nodes(&self.children, out);
+        out.push_str("</");
+        out.push_str(&self.name);
+        out.push('>');
+    }
+}
+
+impl XmlAttribute {
+    pub fn new(name: String, namespace: Option<String>, value: String) -> Self {
+        Self {
+            name,
+            namespace,
+            value,
+        }
+    }
+
+    pub fn is(&self, namespace: &str, local: &str) -> bool {
+        self.namespace.as_deref() == Some(namespace) && local_name(&self.name) == local
+    }
+}
+
+fn escape(text: &str, attribute: bool, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+pub(crate) fn write_nodes(nodes: &[XmlNode], out: &mut String) {
+    for node in nodes {
+        match node {
+            XmlNode::Element(e) => e.write(out),
+            XmlNode::Text(t) => escape(t, false, out),
+            XmlNode::Other(o) => out.push_str(o),
+        }
+    }
+}
+
+fn malformed(expected: &'static str) -> PdfError {
+    PdfError::with_kind(PdfErrorKind::InvalidData).expecting(expected)
+}
+
+/// Parses the nodes of a document, which must have a single root element.
+pub(crate) fn parse(text: &str) -> PdfResult<Vec<XmlNode>> {
+    let mut reader = Reader {
+        rest: text.strip_prefix('\u{feff}').unwrap_or(text),
+        scopes: vec![vec![(String::from("xml"), String::from(XML_NAMESPACE))]],
+    };
+    let nodes = reader.nodes()?;
+    if !reader.rest.is_empty() {
+        return Err(malformed("a single XML root element"));
+    }
+    let roots = nodes
+        .iter()
+        .filter(|n| matches!(n, XmlNode::Element(_)))
+        .count();
+    if roots != 1 {
+        return Err(malformed("a single XML root element"));
+    }
+    Ok(nodes)
+}
+
+struct Reader<'s> {
+    rest: &'s str,
+    /// Namespace declarations of the open elements, innermost last.
+    scopes: Vec<Vec<(String, String)>>,
+}
+
+impl<'s> Reader<'s> {
+    /// Reads nodes up to the end of the input or a closing tag.
+    fn nodes(&mut self) -> PdfResult<Vec<XmlNode>> {
+        let mut nodes = Vec::new();
+        while !self.rest.is_empty() && !self.rest.starts_with("</") {
+            let node = if let Some(cdata) = self.rest.strip_prefix("<![CDATA[") {
+                let end = cdata.find("]]>").ok_or_else(|| malformed("`]]>`"))?;
+                self.rest = &cdata[end + 3..];
+                XmlNode::Text(cdata[..end].to_owned())
+            } else if self.rest.starts_with("<!--") {
+                XmlNode::Other(self.until("-->")?.to_owned())
+            } else if self.rest.starts_with("<?") {
+                XmlNode::Other(self.until("?>")?.to_owned())
+            } else if self.rest.starts_with("<!") {
+                XmlNode::Other(self.until(">")?.to_owned())
+            } else if self.rest.starts_with('<') {
+                XmlNode::Element(self.element()?)
+            } else {
+                let end = self.rest.find('<').unwrap_or(self.rest.len());
+                let text = decode_references(&self.rest[..end])?;
+                self.rest = &self.rest[end..];
+                XmlNode::Text(text)
+            };
+            nodes.push(node);
+        }
+        Ok(nodes)
+    }
+
+    /// Consumes and returns the input up to and including `end`.
+    fn until(&mut self, end: &'static str) -> PdfResult<&'s str> {
+        let len = self.rest.find(end).ok_or_else(|| malformed(end))? + end.len();
+        let (consumed, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Ok(consumed)
+    }
+
+    fn name(&mut self) -> PdfResult<&'s str> {
+        let end = self
+            .rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '=' | '<'))
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            return Err(malformed("an XML name"));
+        }
+        let (name, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Ok(name)
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn expect(&mut self, token: &'static str) -> PdfResult<()> {
+        self.rest = self
+            .rest
+            .strip_prefix(token)
+            .ok_or_else(|| malformed(token))?;
+        Ok(())
+    }
+
+    fn element(&mut self) -> PdfResult<XmlElement> {
+        self.expect("<")?;
+        let name = self.name()?;
+        let mut attributes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.rest.starts_with('/') || self.rest.starts_with('>') {
+                break;
+            }
+            let name = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = if self.rest.starts_with('\'') {
+                "'"
+            } else {
+                "\""
+            };
+            self.expect(quote)?;
+            let end = self
+                .rest
+                .find(quote)
+                .ok_or_else(|| malformed("a closing quote"))?;
+            let value = decode_references(&self.rest[..end])?;
+            self.rest = &self.rest[end + 1..];
+            attributes.push(XmlAttribute::new(name.to_owned(), None, value));
+        }
+
+        let mut element = XmlElement::new(name.to_owned(), None);
+        element.attributes = attributes;
+        let scope = element
+            .declarations()
+            .map(|(p, n)| (p.to_owned(), n.to_owned()))
+            .collect();
+        self.scopes.push(scope);
+        element.namespace = self.namespace_of(name, true);
+        for attribute in &mut element.attributes {
+            attribute.namespace = self.namespace_of(&attribute.name, false);
+        }
+
+        if self.rest.starts_with("/>") {
+            self.rest = &self.rest[2..];
+        } else {
+            self.expect(">")?;
+            element.children = self.nodes()?;
+            self.expect("</")?;
+            if self.name()? != name {
+                return Err(malformed("matching XML end tag"));
+            }
+            self.skip_whitespace();
+            self.expect(">")?;
+        }
+        self.scopes.pop();
+        Ok(element)
+    }
+
+    /// Namespace of a qualified name, unprefixed attributes being in no namespace.
+    fn namespace_of(&self, name: &str, is_element: bool) -> Option<String> {
+        let prefix = match name.split_once(':') {
+            Some(("xmlns", _)) => return None,
+            Some((prefix, _)) => prefix,
+            None if is_element => "",
+            None => return None,
+        };
+        self.scopes
+            .iter()
+            .rev()
+            .flatten()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, namespace)| namespace.clone())
+            .filter(|namespace| !namespace.is_empty())
+    }
+}
+
+/// Replaces the predefined entity and character references of `text`.
+fn decode_references(text: &str) -> PdfResult<String> {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| malformed("`;` ending a reference"))?;
+        let reference = &rest[start + 1..start + end];
+        let c = match reference {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => reference
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| reference.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32)
+                .ok_or_else(|| malformed("a known XML reference"))?,
+        };
+        decoded.push(c);
+        rest = &rest[start + end + 1..];
+    }
+    decoded.push_str(rest);
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(nodes: &[XmlNode]) -> &XmlElement {
+        nodes
+            .iter()
+            .find_map(|n| match n {
+                XmlNode::Element(e) => Some(e),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn namespaces() {
+        let text = r#"<?xml version="1.0"?><a:r xmlns:a="urn:a" xmlns="urn:d"><c b="1" a:b="2"/><a:c xmlns:a="urn:x"/></a:r>"#;
+        let nodes = parse(text).unwrap();
+        let r = root(&nodes);
+        assert!(r.is("urn:a", "r"));
+        let children: Vec<_> = r.elements().collect();
+        assert!(children[0].is("urn:d", "c"));
+        assert_eq!(children[0].attributes[0].namespace, None);
+        assert_eq!(children[0].attribute("urn:a", "b"), Some("2"));
+        assert!(children[1].is("urn:x", "c"));
+    }
+
+    #[test]
+    fn text_and_round_trip() {
+        let text = "<r a='x &amp; &#x3C;y&gt;'><!-- note --><![CDATA[<raw>]]> &#233;t&eacute;</r>";
+        assert!(parse(text).is_err());
+        let text = "<r a='x &amp; &#x3C;y&gt;'><!-- note --><![CDATA[<raw>]]> &#233;t&#xE9;</r>";
+        let nodes = parse(text).unwrap();
+        let r = root(&nodes);
+        assert_eq!(r.attributes[0].value, "x & <y>");
+        assert_eq!(r.text(), "<raw> été");
+        let mut written = String::new();
+        write_nodes(&nodes, &mut written);
+        assert_eq!(
+            written,
+            r#"<r a="x &amp; &lt;y&gt;"><!-- note -->&lt;raw&gt; été</r>"#
+        );
+        assert_eq!(root(&parse(&written).unwrap()).text(), r.text());
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        for text in ["", "<a>", "<a></b>", "<a/><b/>", "<a b=c/>", "text"] {
+            assert!(parse(text).is_err(), "{text}");
+        }
+    }
+}
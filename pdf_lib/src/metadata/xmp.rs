@@ -0,0 +1,439 @@
+use crate::pdf_error::*;
+
+use super::xml::{self, XML_NAMESPACE, XmlAttribute, XmlElement, XmlNode};
+
+/// Packet written for new metadata, with an empty description of the document.
+const EMPTY_PACKET: &str = concat!(
+    "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>",
+    "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">",
+    "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">",
+    "<rdf:Description rdf:about=\"\"/>",
+    "</rdf:RDF></x:xmpmeta>",
+    "<?xpacket end=\"w\"?>"
+);
+
+/// Path of child indices from the document to an element, with the namespace declarations in
+/// scope there as (prefix, namespace) pairs, innermost last.
+type Located = (Vec<usize>, Vec<(String, String)>);
+
+/// XMP metadata packet (ISO 16684-1), as found in `/Metadata` streams (14.3.2).
+///
+/// Properties are looked up by namespace and name in the top level `rdf:Description` elements,
+/// whatever the prefixes used. Everything else in the packet is kept as read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmpMetadata {
+    nodes: Vec<XmlNode>,
+}
+
+/// Value of an XMP property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmpValue {
+    Text(String),
+    /// Ordered array, `rdf:Seq`.
+    Seq(Vec<String>),
+    /// Unordered array, `rdf:Bag`.
+    Bag(Vec<String>),
+    /// Language alternatives, `rdf:Alt`, with their `xml:lang`.
+    Alt(Vec<(String, String)>),
+}
+
+impl XmpValue {
+    /// Alternative for `x-default`, the language used when none is given.
+    pub const DEFAULT_LANGUAGE: &str = "x-default";
+
+    /// The text of a simple value, the default language alternative, or the first array item.
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            XmpValue::Text(text) => Some(text),
+            XmpValue::Seq(items) | XmpValue::Bag(items) => items.first().map(String::as_str),
+            XmpValue::Alt(alternatives) => alternatives
+                .iter()
+                .find(|(lang, _)| lang == Self::DEFAULT_LANGUAGE)
+                .or(alternatives.first())
+                .map(|(_, text)| text.as_str()),
+        }
+    }
+}
+
+impl XmpMetadata {
+    pub const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+    pub const DC: &str = "http://purl.org/dc/elements/1.1/";
+    pub const XMP: &str = "http://ns.adobe.com/xap/1.0/";
+    pub const PDF: &str = "http://ns.adobe.com/pdf/1.3/";
+
+    /// A packet without properties.
+    pub fn new() -> Self {
+        Self::parse(EMPTY_PACKET.as_bytes()).expect("the empty packet is well-formed")
+    }
+
+    /// Parses a packet encoded in UTF-8.
+    pub fn parse(bytes: &[u8]) -> PdfResult<Self> {
+        let text = str::from_utf8(bytes)?;
+        Ok(Self {
+            nodes: xml::parse(text)?,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut text = String::new();
+        xml::write_nodes(&self.nodes, &mut text);
+        text.into_bytes()
+    }
+
+    /// The property `name` of `namespace`, from the first description giving it.
+    pub fn get(&self, namespace: &str, name: &str) -> Option<XmpValue> {
+        self.descriptions().into_iter().find_map(|(path, _)| {
+            let description = element_at(&self.nodes, &path);
+            if let Some(value) = description.attribute(namespace, name) {
+                return Some(XmpValue::Text(value.to_owned()));
+            }
+            description
+                .elements()
+                .find(|e| e.is(namespace, name))
+                .map(value_of)
+        })
+    }
+
+    /// Removes the property `name` of `namespace`, returning whether there was one.
+    pub fn remove(&mut self, namespace: &str, name: &str) -> bool {
+        let mut removed = false;
+        for (path, _) in self.descriptions() {
+            let description = element_at_mut(&mut self.nodes, &path);
+            let (attributes, children) = (description.attributes.len(), description.children.len());
+            description.attributes.retain(|a| !a.is(namespace, name));
+            description
+                .children
+                .retain(|c| !matches!(c, XmlNode::Element(e) if e.is(namespace, name)));
+            removed |= attributes != description.attributes.len()
+                || children != description.children.len();
+        }
+        removed
+    }
+
+    /// Sets the property `name` of `namespace`, replacing any previous value.
+    ///
+    /// The property is added to the first description, declaring a prefix for `namespace` if
+    /// there is none in scope. Fails if the packet has no `rdf:RDF` element.
+    pub fn set(&mut self, namespace: &str, name: &str, value: XmpValue) -> PdfResult<()> {
+        self.remove(namespace, name);
+        if self.descriptions().is_empty() {
+            self.add_description()?;
+        }
+        let (path, scope) = self.descriptions().swap_remove(0);
+        let rdf = prefix_in(&scope, Self::RDF).expect("descriptions are found by namespace");
+        let description = element_at_mut(&mut self.nodes, &path);
+        let prefix = match prefix_in(&scope, namespace) {
+            Some(prefix) => prefix,
+            None => {
+                let prefix = unused_prefix(&scope, namespace);
+                description.attributes.push(XmlAttribute::new(
+                    format!("xmlns:{prefix}"),
+                    None,
+                    namespace.to_owned(),
+                ));
+                prefix
+            }
+        };
+        let mut property = XmlElement::new(qualified(&prefix, name), Some(namespace.to_owned()));
+        let (container, items) = match value {
+            XmpValue::Text(text) => {
+                property.children.push(XmlNode::Text(text));
+                description.children.push(XmlNode::Element(property));
+                return Ok(());
+            }
+            XmpValue::Seq(items) => ("Seq", items.into_iter().map(|i| (None, i)).collect()),
+            XmpValue::Bag(items) => ("Bag", items.into_iter().map(|i| (None, i)).collect()),
+            XmpValue::Alt(alternatives) => (
+                "Alt",
+                alternatives
+                    .into_iter()
+                    .map(|(lang, text)| (Some(lang), text))
+                    .collect::<Vec<_>>(),
+            ),
+        };
+        let mut array = XmlElement::new(qualified(&rdf, container), Some(Self::RDF.to_owned()));
+        for (lang, text) in items {
+            let mut item = XmlElement::new(qualified(&rdf, "li"), Some(Self::RDF.to_owned()));
+            if let Some(lang) = lang {
+                item.attributes.push(XmlAttribute::new(
+                    String::from("xml:lang"),
+                    Some(XML_NAMESPACE.to_owned()),
+                    lang,
+                ));
+            }
+            item.children.push(XmlNode::Text(text));
+            array.children.push(XmlNode::Element(item));
+        }
+        property.children.push(XmlNode::Element(array));
+        description.children.push(XmlNode::Element(property));
+        Ok(())
+    }
+
+    /// Paths to the top level descriptions, with the namespace declarations in scope of each.
+    fn descriptions(&self) -> Vec<Located> {
+        let mut found = Vec::new();
+        for (rdf_path, scope) in find(&self.nodes, &|e| e.is(Self::RDF, "RDF")) {
+            let rdf = element_at(&self.nodes, &rdf_path);
+            for (i, child) in rdf.children.iter().enumerate() {
+                if let XmlNode::Element(e) = child
+                    && e.is(Self::RDF, "Description")
+                {
+                    let mut path = rdf_path.clone();
+                    path.push(i);
+                    let mut scope = scope.clone();
+                    scope.extend(declarations(e));
+                    found.push((path, scope));
+                }
+            }
+        }
+        found
+    }
+
+    fn add_description(&mut self) -> PdfResult<()> {
+        let (path, scope) = find(&self.nodes, &|e| e.is(Self::RDF, "RDF"))
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                PdfError::with_kind(PdfErrorKind::InvalidData).expecting("an rdf:RDF element")
+            })?;
+        let rdf = prefix_in(&scope, Self::RDF).expect("rdf:RDF is found by namespace");
+        let mut description =
+            XmlElement::new(qualified(&rdf, "Description"), Some(Self::RDF.to_owned()));
+        description.attributes.push(XmlAttribute::new(
+            qualified(&rdf, "about"),
+            Some(Self::RDF.to_owned()),
+            String::new(),
+        ));
+        element_at_mut(&mut self.nodes, &path)
+            .children
+            .push(XmlNode::Element(description));
+        Ok(())
+    }
+}
+
+impl Default for XmpMetadata {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn value_of(property: &XmlElement) -> XmpValue {
+    let array = property.elements().find(|e| {
+        e.namespace.as_deref() == Some(XmpMetadata::RDF)
+            && matches!(e.local_name(), "Seq" | "Bag" | "Alt")
+    });
+    let Some(array) = array else {
+        return match property.attribute(XmpMetadata::RDF, "resource") {
+            Some(resource) => XmpValue::Text(resource.to_owned()),
+            None => XmpValue::Text(property.text()),
+        };
+    };
+    let items = array.elements().filter(|e| e.is(XmpMetadata::RDF, "li"));
+    match array.local_name() {
+        "Seq" => XmpValue::Seq(items.map(XmlElement::text).collect()),
+        "Bag" => XmpValue::Bag(items.map(XmlElement::text).collect()),
+        _ => XmpValue::Alt(
+            items
+                .map(|item| {
+                    let lang = item
+                        .attribute(XML_NAMESPACE, "lang")
+                        .unwrap_or(XmpValue::DEFAULT_LANGUAGE);
+                    (lang.to_owned(), item.text())
+                })
+                .collect(),
+        ),
+    }
+}
+
+fn declarations(element: &XmlElement) -> impl Iterator<Item = (String, String)> {
+    element
+        .declarations()
+        .map(|(prefix, namespace)| (prefix.to_owned(), namespace.to_owned()))
+}
+
+/// Paths to the elements matching `predicate`, with the namespace declarations in scope of each,
+/// without looking inside matching elements.
+fn find(nodes: &[XmlNode], predicate: &dyn Fn(&XmlElement) -> bool) -> Vec<Located> {
+    fn walk(
+        nodes: &[XmlNode],
+        predicate: &dyn Fn(&XmlElement) -> bool,
+        path: &mut Vec<usize>,
+        scope: &mut Vec<(String, String)>,
+        found: &mut Vec<Located>,
+    ) {
+        for (i, node) in nodes.iter().enumerate() {
+            let XmlNode::Element(e) = node else { continue };
+            let scope_len = scope.len();
+            scope.extend(declarations(e));
+            path.push(i);
+            if predicate(e) {
+                found.push((path.clone(), scope.clone()));
+            } else {
+                walk(&e.children, predicate, path, scope, found);
+            }
+            path.pop();
+            scope.truncate(scope_len);
+        }
+    }
+    let mut found = Vec::new();
+    walk(
+        nodes,
+        predicate,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut found,
+    );
+    found
+}
+
+fn element_at<'n>(nodes: &'n [XmlNode], path: &[usize]) -> &'n XmlElement {
+    let (first, rest) = path.split_first().expect("paths are not empty");
+    let XmlNode::Element(e) = &nodes[*first] else {
+        unreachable!("paths lead through elements")
+    };
+    if rest.is_empty() {
+        e
+    } else {
+        element_at(&e.children, rest)
+    }
+}
+
+fn element_at_mut<'n>(nodes: &'n mut [XmlNode], path: &[usize]) -> &'n mut XmlElement {
+    let (first, rest) = path.split_first().expect("paths are not empty");
+    let XmlNode::Element(e) = &mut nodes[*first] else {
+        unreachable!("paths lead through elements")
+    };
+    if rest.is_empty() {
+        e
+    } else {
+        element_at_mut(&mut e.children, rest)
+    }
+}
+
+/// The prefix bound to `namespace` in `scope`, unless another binding of it shadows it.
+fn prefix_in(scope: &[(String, String)], namespace: &str) -> Option<String> {
+    scope
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, (_, ns))| ns == namespace)
+        .map(|(i, (prefix, _))| (i, prefix))
+        .find(|(i, prefix)| scope[i + 1..].iter().all(|(p, _)| p != *prefix))
+        .map(|(_, prefix)| prefix.clone())
+}
+
+/// The usual prefix of `namespace`, numbered if it is already bound.
+fn unused_prefix(scope: &[(String, String)], namespace: &str) -> String {
+    let base = match namespace {
+        XmpMetadata::DC => "dc",
+        XmpMetadata::XMP => "xmp",
+        XmpMetadata::PDF => "pdf",
+        _ => "ns",
+    };
+    let bound = |prefix: &str| scope.iter().any(|(p, _)| p == prefix);
+    if !bound(base) {
+        return base.to_owned();
+    }
+    (1..)
+        .map(|i| format!("{base}{i}"))
+        .find(|prefix| !bound(prefix))
+        .expect("some numbered prefix is free")
+}
+
+fn qualified(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{prefix}:{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKET: &str = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <r:RDF xmlns:r="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <r:Description r:about="" xmlns:pdf="http://ns.adobe.com/pdf/1.3/" pdf:Producer="Printer">
+      <pdf:Keywords>a, b</pdf:Keywords>
+    </r:Description>
+    <r:Description r:about="" xmlns:d="http://purl.org/dc/elements/1.1/">
+      <d:title><r:Alt><r:li xml:lang="fr">Titre</r:li><r:li xml:lang="x-default">Title</r:li></r:Alt></d:title>
+      <d:creator><r:Seq><r:li>Ann</r:li><r:li>Bob</r:li></r:Seq></d:creator>
+    </r:Description>
+  </r:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+    #[test]
+    fn get() {
+        let xmp = XmpMetadata::parse(PACKET.as_bytes()).unwrap();
+        let pdf = XmpMetadata::PDF;
+        assert_eq!(
+            xmp.get(pdf, "Producer"),
+            Some(XmpValue::Text("Printer".into()))
+        );
+        assert_eq!(
+            xmp.get(pdf, "Keywords"),
+            Some(XmpValue::Text("a, b".into()))
+        );
+        let title = xmp.get(XmpMetadata::DC, "title").unwrap();
+        assert_eq!(title.text(), Some("Title"));
+        assert_eq!(
+            xmp.get(XmpMetadata::DC, "creator"),
+            Some(XmpValue::Seq(vec!["Ann".into(), "Bob".into()]))
+        );
+        assert_eq!(xmp.get(XmpMetadata::XMP, "CreateDate"), None);
+    }
+
+    #[test]
+    fn set_and_remove() {
+        let mut xmp = XmpMetadata::parse(PACKET.as_bytes()).unwrap();
+        assert!(xmp.remove(XmpMetadata::PDF, "Producer"));
+        assert!(!xmp.remove(XmpMetadata::PDF, "Producer"));
+        let alt = XmpValue::Alt(vec![("x-default".into(), "New & improved".into())]);
+        xmp.set(XmpMetadata::DC, "title", alt.clone()).unwrap();
+        xmp.set(
+            XmpMetadata::XMP,
+            "CreatorTool",
+            XmpValue::Text("Tool".into()),
+        )
+        .unwrap();
+        xmp.set(XmpMetadata::PDF, "Keywords", XmpValue::Text("c".into()))
+            .unwrap();
+
+        let reparsed = XmpMetadata::parse(&xmp.to_bytes()).unwrap();
+        assert_eq!(reparsed.to_bytes(), xmp.to_bytes());
+        assert_eq!(reparsed.get(XmpMetadata::DC, "title"), Some(alt));
+        assert_eq!(
+            reparsed.get(XmpMetadata::XMP, "CreatorTool"),
+            Some(XmpValue::Text("Tool".into()))
+        );
+        assert_eq!(
+            reparsed.get(XmpMetadata::PDF, "Keywords"),
+            Some(XmpValue::Text("c".into()))
+        );
+        let text = String::from_utf8(xmp.to_bytes()).unwrap();
+        assert!(text.contains(r#"xmlns:xmp="http://ns.adobe.com/xap/1.0/""#));
+        assert!(text.contains("<r:Alt><r:li xml:lang=\"x-default\">New &amp; improved</r:li>"));
+        assert_eq!(text.matches("Keywords").count(), 2);
+    }
+
+    #[test]
+    fn new() {
+        let mut xmp = XmpMetadata::new();
+        assert_eq!(xmp.get(XmpMetadata::DC, "title"), None);
+        let authors = XmpValue::Seq(vec!["Ann".into()]);
+        xmp.set(XmpMetadata::DC, "creator", authors.clone())
+            .unwrap();
+        let reparsed = XmpMetadata::parse(&xmp.to_bytes()).unwrap();
+        assert_eq!(reparsed.get(XmpMetadata::DC, "creator"), Some(authors));
+
+        let mut bare = XmpMetadata::parse(b"<x:xmpmeta xmlns:x='adobe:ns:meta/'/>").unwrap();
+        assert!(
+            bare.set(XmpMetadata::DC, "title", XmpValue::Text("t".into()))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,399 @@
+use std::{collections::HashSet, marker::PhantomData};
+
+use crate::{
+    IndirectData, PdfArray, PdfDict, PdfFile, PdfName, PdfNumeric, PdfObject, PdfString, Resolve,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+};
+
+/// How deep a tree may be, to stop on malformed trees.
+const MAX_TREE_DEPTH: usize = 32;
+/// Most entries of a leaf, and kids of an intermediate node, in written trees.
+const MAX_NODE_LEN: usize = 32;
+
+/// Keys of a [`SearchTree`]: strings for name trees, integers for number trees.
+pub trait TreeKey: Ord + Clone {
+    /// Entry of the leaves holding the key and value pairs.
+    const ENTRIES: &'static [u8];
+
+    fn from_object(object: &PdfObject) -> PdfResult<Self>;
+    fn to_object(&self) -> PdfObject;
+}
+
+impl TreeKey for PdfString {
+    const ENTRIES: &'static [u8] = b"Names";
+
+    fn from_object(object: &PdfObject) -> PdfResult<Self> {
+        object.as_string_ref().cloned()
+    }
+    fn to_object(&self) -> PdfObject {
+        self.clone().into()
+    }
+}
+
+impl TreeKey for i64 {
+    const ENTRIES: &'static [u8] = b"Nums";
+
+    fn from_object(object: &PdfObject) -> PdfResult<Self> {
+        object.as_numeric_ref()?.to_i64()
+    }
+    fn to_object(&self) -> PdfObject {
+        PdfNumeric::PdfInt(*self).into()
+    }
+}
+
+/// A name tree (7.9.6) or number tree (7.9.7), its nodes resolved as needed.
+pub struct SearchTree<'f, 'a, K> {
+    file: &'f PdfFile<'a>,
+    root: PdfDict,
+    key: PhantomData<K>,
+}
+
+pub type NameTree<'f, 'a> = SearchTree<'f, 'a, PdfString>;
+pub type NumberTree<'f, 'a> = SearchTree<'f, 'a, i64>;
+
+fn malformed(expected: &'static str) -> PdfError {
+    PdfError::with_kind(PdfErrorKind::InvalidData).expecting(expected)
+}
+
+impl<'f, 'a, K: TreeKey> SearchTree<'f, 'a, K> {
+    pub fn new(file: &'f PdfFile<'a>, root: PdfDict) -> Self {
+        Self {
+            file,
+            root,
+            key: PhantomData,
+        }
+    }
+
+    /// The value of `key`, following only the kids whose `/Limits` include it.
+    pub fn get(&self, key: &K) -> PdfResult<Option<PdfObject>> {
+        let mut nodes = vec![(self.root.clone(), 0)];
+        let mut visited = HashSet::new();
+        while let Some((node, depth)) = nodes.pop() {
+            if let Some(entries) = node.get(K::ENTRIES) {
+                let entries = self.file.deref(entries)?.as_array()?;
+                for pair in entries.iter().collect::<Vec<_>>().chunks_exact(2) {
+                    if K::from_object(pair[0])? == *key {
+                        return self.file.deref(pair[1]).map(Some);
+                    }
+                }
+            }
+            let Some(kids) = node.get(b"Kids") else {
+                continue;
+            };
+            for kid in self.file.deref(kids)?.as_array()?.iter().rev() {
+                let kid = self.kid(kid, depth, &mut visited)?;
+                let limits = kid.get_array(b"Limits")?;
+                let within = match limits.map(|l| (l.get(0), l.get(1))) {
+                    Some((Some(low), Some(high))) => {
+                        K::from_object(low)? <= *key && *key <= K::from_object(high)?
+                    }
+                    _ => true,
+                };
+                if within {
+                    nodes.push((kid, depth + 1));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// The entries in tree order, which is key order in well-formed trees, values resolved.
+    pub fn iter(&self) -> SearchTreeIter<'_, 'f, 'a, K> {
+        SearchTreeIter {
+            tree: self,
+            nodes: vec![(self.root.clone(), 0)],
+            entries: Vec::new(),
+            visited: HashSet::new(),
+        }
+    }
+
+    fn kid(
+        &self,
+        kid: &PdfObject,
+        depth: usize,
+        visited: &mut HashSet<IndirectData>,
+    ) -> PdfResult<PdfDict> {
+        if depth >= MAX_TREE_DEPTH {
+            return Err(malformed("a tree nested less deeply"));
+        }
+        if let Some(reference) = kid.as_reference()
+            && !visited.insert(reference.clone())
+        {
+            return Err(malformed("a tree visiting each node once"));
+        }
+        self.file.deref(kid)?.as_dict()
+    }
+
+    /// Adds a balanced tree of `entries` to `file`, returning its root.
+    ///
+    /// Entries are sorted by key, the last one given for a key being kept.
+    pub fn write(
+        file: &mut PdfFile,
+        entries: impl IntoIterator<Item = (K, PdfObject)>,
+    ) -> IndirectData {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        // Stable, so that the last of equal keys stays last and is kept by the reverse dedup
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.reverse();
+        entries.dedup_by(|a, b| a.0 == b.0);
+        entries.reverse();
+
+        if entries.len() <= MAX_NODE_LEN {
+            return file.add_object(leaf::<K>(&entries, false));
+        }
+        let mut level: Vec<_> = balanced_chunks(&entries)
+            .map(|chunk| {
+                let limits = (chunk[0].0.clone(), chunk[chunk.len() - 1].0.clone());
+                (limits, file.add_object(leaf::<K>(chunk, true)))
+            })
+            .collect();
+        while level.len() > MAX_NODE_LEN {
+            level = balanced_chunks(&level)
+                .map(|chunk| {
+                    let limits = (chunk[0].0.0.clone(), chunk[chunk.len() - 1].0.1.clone());
+                    let mut node = intermediate(chunk);
+                    node.insert(PdfName::from_raw_bytes(b"Limits"), limits_array(&limits));
+                    (limits, file.add_object(node))
+                })
+                .collect();
+        }
+        file.add_object(intermediate(&level))
+    }
+}
+
+/// Splits `items` into the fewest chunks of at most [`MAX_NODE_LEN`] items, of even lengths.
+fn balanced_chunks<T>(items: &[T]) -> impl Iterator<Item = &[T]> {
+    let count = items.len().div_ceil(MAX_NODE_LEN);
+    let (len, longer) = (items.len() / count, items.len() % count);
+    (0..count).map(move |i| {
+        let start = i * len + i.min(longer);
+        let end = start + len + usize::from(i < longer);
+        &items[start..end]
+    })
+}
+
+fn limits_array<K: TreeKey>(limits: &(K, K)) -> PdfArray {
+    [limits.0.to_object(), limits.1.to_object()]
+        .into_iter()
+        .collect()
+}
+
+fn leaf<K: TreeKey>(entries: &[(K, PdfObject)], with_limits: bool) -> PdfDict {
+    let mut node = PdfDict::empty();
+    if with_limits && let (Some(first), Some(last)) = (entries.first(), entries.last()) {
+        node.insert(
+            PdfName::from_raw_bytes(b"Limits"),
+            limits_array(&(first.0.clone(), last.0.clone())),
+        );
+    }
+    let array: PdfArray = entries
+        .iter()
+        .flat_map(|(key, value)| [key.to_object(), value.clone()])
+        .collect();
+    node.insert(PdfName::from_raw_bytes(K::ENTRIES), array);
+    node
+}
+
+fn intermediate<K: TreeKey>(kids: &[((K, K), IndirectData)]) -> PdfDict {
+    let mut node = PdfDict::empty();
+    let kids: PdfArray = kids.iter().map(|(_, kid)| kid.clone()).collect();
+    node.insert(PdfName::from_raw_bytes(b"Kids"), kids);
+    node
+}
+
+/// Iterator over the entries of a [`SearchTree`], stopping after the first error.
+pub struct SearchTreeIter<'t, 'f, 'a, K> {
+    tree: &'t SearchTree<'f, 'a, K>,
+    /// Nodes left to visit, the next one last.
+    nodes: Vec<(PdfDict, usize)>,
+    /// Entries of the current leaf, the next one last.
+    entries: Vec<(PdfObject, PdfObject)>,
+    visited: HashSet<IndirectData>,
+}
+
+impl<K: TreeKey> SearchTreeIter<'_, '_, '_, K> {
+    fn next_entry(&mut self) -> PdfResult<Option<(K, PdfObject)>> {
+        loop {
+            if let Some((key, value)) = self.entries.pop() {
+                return Ok(Some((K::from_object(&key)?, self.tree.file.deref(&value)?)));
+            }
+            let Some((node, depth)) = self.nodes.pop() else {
+                return Ok(None);
+            };
+            let file = self.tree.file;
+            if let Some(entries) = node.get(K::ENTRIES) {
+                let entries: Vec<_> = file.deref(entries)?.as_array()?.into_iter().collect();
+                let mut pairs = entries.chunks_exact(2);
+                self.entries = pairs
+                    .by_ref()
+                    .rev()
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect();
+                if !pairs.remainder().is_empty() {
+                    return Err(malformed("key and value pairs"));
+                }
+            }
+            if let Some(kids) = node.get(b"Kids") {
+                for kid in file.deref(kids)?.as_array()?.iter().rev() {
+                    let kid = self.tree.kid(kid, depth, &mut self.visited)?;
+                    self.nodes.push((kid, depth + 1));
+                }
+            }
+        }
+    }
+}
+
+impl<K: TreeKey> Iterator for SearchTreeIter<'_, '_, '_, K> {
+    type Item = PdfResult<(K, PdfObject)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.next_entry();
+        if next.is_err() {
+            self.nodes.clear();
+            self.entries.clear();
+        }
+        next.transpose()
+    }
+}
+
+impl<'a> PdfFile<'a> {
+    /// The name tree `name` of the catalog's `/Names` dictionary, such as `Dests` or
+    /// `EmbeddedFiles`.
+    pub fn name_tree(&self, name: &[u8]) -> PdfResult<Option<NameTree<'_, 'a>>> {
+        let catalog = self.catalog()?;
+        let Some(names) = catalog.get(b"Names") else {
+            return Ok(None);
+        };
+        let names = self.deref(names)?.as_dict()?;
+        names
+            .get(name)
+            .map(|root| Ok(NameTree::new(self, self.deref(root)?.as_dict()?)))
+            .transpose()
+    }
+
+    /// Sets the name tree `name` of the catalog's `/Names` dictionary, removing it if `root` is
+    /// `None`.
+    pub fn set_name_tree(&mut self, name: &[u8], root: Option<IndirectData>) -> PdfResult<()> {
+        let catalog_reference = self.catalog_reference()?;
+        let mut catalog = self.catalog()?;
+        let names_reference = catalog
+            .get(b"Names")
+            .and_then(PdfObject::as_reference)
+            .cloned();
+        let mut names = match catalog.get(b"Names") {
+            Some(names) => self.deref(names)?.as_dict()?,
+            None => PdfDict::empty(),
+        };
+        match root {
+            Some(root) => names.insert(PdfName::from_raw_bytes(name), root),
+            None => names.remove(name),
+        };
+        match names_reference {
+            Some(reference) => self.set_object(&reference, names),
+            None => {
+                catalog.insert(PdfName::from_raw_bytes(b"Names"), names);
+                self.set_object(&catalog_reference, catalog);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        NameTree, NumberTree, PdfFile, PdfNumeric, PdfObject, PdfString, Resolve,
+        pdf_file::test_file,
+    };
+
+    fn string(s: &str) -> PdfString {
+        PdfString::from_raw_bytes(s.as_bytes())
+    }
+
+    fn number(n: i64) -> PdfObject {
+        PdfNumeric::PdfInt(n).into()
+    }
+
+    #[test]
+    fn read() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Names << /Dests 2 0 R >> >>",
+                "<< /Kids [3 0 R 4 0 R] >>",
+                "<< /Limits [(a) (c)] /Names [(a) 1 (c) 5 0 R] >>",
+                "<< /Limits [(d) (f)] /Kids [<< /Limits [(d) (f)] /Names [(d) 4 (f) 6] >>] >>",
+                "3",
+                "<< /Nums [0 (zero) 10 (ten)] >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        let tree = file.name_tree(b"Dests").unwrap().unwrap();
+        let c = tree.get(&string("c")).unwrap().unwrap();
+        assert_eq!(c.as_numeric_ref().unwrap().to_i64(), Ok(3));
+        assert_eq!(tree.get(&string("f")), Ok(Some(number(6))));
+        assert_eq!(tree.get(&string("b")), Ok(None));
+        let keys: Vec<_> = tree.iter().map(|e| e.unwrap().0).collect();
+        assert_eq!(keys, ["a", "c", "d", "f"].map(string));
+        assert!(file.name_tree(b"JavaScript").unwrap().is_none());
+
+        let root = file.resolve(&crate::IndirectData::new(6, 0)).unwrap();
+        let numbers = NumberTree::new(&file, root.as_dict().unwrap());
+        assert_eq!(numbers.get(&10), Ok(Some(string("ten").into())));
+        assert_eq!(numbers.iter().count(), 2);
+    }
+
+    #[test]
+    fn cycle() {
+        let bytes = test_file::build(
+            &["<< /Type /Catalog >>", "<< /Kids [2 0 R] >>"],
+            "/Root 1 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        let root = file.resolve(&crate::IndirectData::new(2, 0)).unwrap();
+        let tree = NameTree::new(&file, root.as_dict().unwrap());
+        assert!(tree.iter().any(|e| e.is_err()));
+        assert_eq!(tree.iter().count(), 1);
+        assert!(tree.get(&string("a")).is_err());
+    }
+
+    #[test]
+    fn write_balanced() {
+        let bytes = test_file::build(&["<< /Type /Catalog >>"], "/Root 1 0 R");
+        let mut file = PdfFile::load(&bytes).unwrap();
+        let entries: Vec<_> = (0..2000).rev().map(|i| (i * 2, number(i))).collect();
+        let root = NumberTree::write(&mut file, entries);
+        let small = NameTree::write(
+            &mut file,
+            [
+                (string("b"), number(1)),
+                (string("a"), number(2)),
+                (string("b"), number(3)),
+            ],
+        );
+        file.set_name_tree(b"Dests", Some(small)).unwrap();
+
+        let written = file.to_bytes().unwrap();
+        let file = PdfFile::load(&written).unwrap();
+        let tree = NumberTree::new(&file, file.resolve(&root).unwrap().as_dict().unwrap());
+        let keys: Vec<_> = tree.iter().map(|e| e.unwrap().0).collect();
+        assert_eq!(keys, (0..2000).map(|i| i * 2).collect::<Vec<_>>());
+        assert_eq!(tree.get(&1998), Ok(Some(number(999))));
+        assert_eq!(tree.get(&1999), Ok(None));
+
+        // 2000 entries make 63 leaves of 31 or 32 entries, under 2 intermediate nodes
+        let root = file.resolve(&root).unwrap().as_dict().unwrap();
+        let kids = root.get_array(b"Kids").unwrap().unwrap();
+        assert_eq!(kids.len(), 2);
+        assert!(root.get(b"Limits").is_none());
+        let kid = file.deref(&kids[0]).unwrap().as_dict().unwrap();
+        assert_eq!(kid.get_array(b"Kids").unwrap().unwrap().len(), 32);
+
+        let names = file.name_tree(b"Dests").unwrap().unwrap();
+        let entries: Vec<_> = names.iter().map(Result::unwrap).collect();
+        assert_eq!(
+            entries,
+            [(string("a"), number(2)), (string("b"), number(3))]
+        );
+    }
+}
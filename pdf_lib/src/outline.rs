@@ -0,0 +1,354 @@
+use std::collections::HashSet;
+
+use crate::{
+    Destination, IndirectData, PdfDict, PdfFile, PdfName, PdfNull, PdfNumeric, PdfObject, Resolve,
+    pages::Page,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+};
+
+/// How deep outline items may be nested, to stop on malformed outlines.
+const MAX_OUTLINE_DEPTH: usize = 64;
+
+/// An item of the document outline, or bookmark (12.3.3).
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineItem {
+    pub title: String,
+    /// Where the item leads, `None` if it has no destination or one outside the document.
+    pub destination: Option<Destination>,
+    /// Whether the children are shown.
+    pub open: bool,
+    pub children: Vec<OutlineItem>,
+}
+
+impl OutlineItem {
+    /// A closed item without children.
+    pub fn new(title: impl Into<String>, destination: Option<Destination>) -> Self {
+        Self {
+            title: title.into(),
+            destination,
+            open: false,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_children(mut self, children: Vec<OutlineItem>, open: bool) -> Self {
+        self.children = children;
+        self.open = open;
+        self
+    }
+
+    /// Number of descendants shown when the item is open, the magnitude of its `/Count`.
+    fn visible_descendants(&self) -> usize {
+        visible_items(&self.children)
+    }
+}
+
+/// Number of items shown among `items` and their descendants.
+fn visible_items(items: &[OutlineItem]) -> usize {
+    items
+        .iter()
+        .map(|i| 1 + if i.open { i.visible_descendants() } else { 0 })
+        .sum()
+}
+
+/// The references of `/First` and the following `/Next` items of an outline node, checked to be
+/// visited only once.
+fn references(
+    file: &PdfFile,
+    node: &PdfDict,
+    visited: &mut HashSet<IndirectData>,
+) -> PdfResult<Vec<(IndirectData, PdfDict)>> {
+    let mut items = Vec::new();
+    let mut next = node
+        .get(b"First")
+        .and_then(PdfObject::as_reference)
+        .cloned();
+    while let Some(reference) = next {
+        if !visited.insert(reference.clone()) {
+            return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                .expecting("an outline visiting each item once"));
+        }
+        let dict = file.resolve(&reference)?.as_dict()?;
+        next = dict.get(b"Next").and_then(PdfObject::as_reference).cloned();
+        items.push((reference, dict));
+    }
+    Ok(items)
+}
+
+impl PdfFile<'_> {
+    /// The top level items of the document outline, empty if there is none.
+    pub fn outline(&self) -> PdfResult<Vec<OutlineItem>> {
+        let Some(root) = self.outline_root()? else {
+            return Ok(Vec::new());
+        };
+        let pages = self.pages()?;
+        self.read_outline_items(&root, &pages, &mut HashSet::new(), 0)
+    }
+
+    /// Replaces the document outline by `items`, removing it if there are none.
+    pub fn set_outline(&mut self, items: &[OutlineItem]) -> PdfResult<()> {
+        // The previous items are removed, their numbers not being reused
+        if let Some(root) = self.outline_root()? {
+            let mut visited = HashSet::new();
+            let mut nodes = vec![root];
+            while let Some(node) = nodes.pop() {
+                for (reference, dict) in references(self, &node, &mut visited)? {
+                    self.delete_object(&reference);
+                    nodes.push(dict);
+                }
+            }
+        }
+        let mut catalog = self.catalog()?;
+        if let Some(reference) = catalog.get(b"Outlines").and_then(PdfObject::as_reference) {
+            self.delete_object(&reference.clone());
+        }
+        let catalog_reference = self.catalog_reference()?;
+        if items.is_empty() {
+            catalog.remove(b"Outlines");
+            self.set_object(&catalog_reference, catalog);
+            return Ok(());
+        }
+
+        let pages = self.pages()?;
+        let root = self.add_object(PdfNull {});
+        let mut dict = PdfDict::empty();
+        dict.insert(
+            PdfName::from_raw_bytes(b"Type"),
+            PdfName::from_raw_bytes(b"Outlines"),
+        );
+        let (first, last) = self.write_outline_items(items, &root, &pages)?;
+        dict.insert(PdfName::from_raw_bytes(b"First"), first);
+        dict.insert(PdfName::from_raw_bytes(b"Last"), last);
+        dict.insert(
+            PdfName::from_raw_bytes(b"Count"),
+            PdfNumeric::PdfInt(visible_items(items) as i64),
+        );
+        self.set_object(&root, dict);
+        catalog.insert(PdfName::from_raw_bytes(b"Outlines"), root);
+        self.set_object(&catalog_reference, catalog);
+        Ok(())
+    }
+
+    fn outline_root(&self) -> PdfResult<Option<PdfDict>> {
+        self.catalog()?
+            .get(b"Outlines")
+            .map(|o| self.deref(o)?.as_dict())
+            .transpose()
+    }
+
+    fn read_outline_items(
+        &self,
+        node: &PdfDict,
+        pages: &[Page],
+        visited: &mut HashSet<IndirectData>,
+        depth: usize,
+    ) -> PdfResult<Vec<OutlineItem>> {
+        if depth > MAX_OUTLINE_DEPTH {
+            return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                .expecting("outline items nested less deeply"));
+        }
+        let mut items = Vec::new();
+        for (_, dict) in references(self, node, visited)? {
+            // An undecodable title is no reason to lose the outline
+            let title = match dict.get(b"Title") {
+                Some(title) => self
+                    .deref(title)?
+                    .as_string()
+                    .map(|t| t.to_text_lossy())
+                    .unwrap_or_default(),
+                None => String::new(),
+            };
+            let destination = self.target_destination(&dict, pages)?;
+            let open = dict.get_int(b"Count")?.is_some_and(|c| c > 0);
+            let children = self.read_outline_items(&dict, pages, visited, depth + 1)?;
+            items.push(OutlineItem {
+                title,
+                destination,
+                open,
+                children,
+            });
+        }
+        Ok(items)
+    }
+
+    /// Adds `items` as children of `parent`, returning the first and last of them.
+    fn write_outline_items(
+        &mut self,
+        items: &[OutlineItem],
+        parent: &IndirectData,
+        pages: &[Page],
+    ) -> PdfResult<(IndirectData, IndirectData)> {
+        let references: Vec<_> = items.iter().map(|_| self.add_object(PdfNull {})).collect();
+        for (i, item) in items.iter().enumerate() {
+            let mut dict = PdfDict::empty();
+            dict.insert(
+                PdfName::from_raw_bytes(b"Title"),
+                self.text_string(&item.title),
+            );
+            dict.insert(PdfName::from_raw_bytes(b"Parent"), parent.clone());
+            if i > 0 {
+                dict.insert(PdfName::from_raw_bytes(b"Prev"), references[i - 1].clone());
+            }
+            if let Some(next) = references.get(i + 1) {
+                dict.insert(PdfName::from_raw_bytes(b"Next"), next.clone());
+            }
+            if !item.children.is_empty() {
+                let (first, last) =
+                    self.write_outline_items(&item.children, &references[i], pages)?;
+                dict.insert(PdfName::from_raw_bytes(b"First"), first);
+                dict.insert(PdfName::from_raw_bytes(b"Last"), last);
+                let count = item.visible_descendants() as i64;
+                dict.insert(
+                    PdfName::from_raw_bytes(b"Count"),
+                    PdfNumeric::PdfInt(if item.open { count } else { -count }),
+                );
+            }
+            if let Some(destination) = &item.destination {
+                dict.insert(
+                    PdfName::from_raw_bytes(b"Dest"),
+                    destination.to_array(pages)?,
+                );
+            }
+            self.set_object(&references[i], dict);
+        }
+        let first = references.first().expect("items are not empty").clone();
+        let last = references.last().expect("items are not empty").clone();
+        Ok((first, last))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Destination, DestinationView, IndirectData, OutlineItem, PdfFile, PdfRect, Resolve,
+        pdf_file::test_file,
+    };
+
+    fn pages(count: usize) -> Vec<String> {
+        let kids: Vec<_> = (0..count).map(|i| format!("{} 0 R", i + 3)).collect();
+        let mut objects = vec![
+            String::from("<< /Type /Catalog /Pages 2 0 R >>"),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {count} >>",
+                kids.join(" ")
+            ),
+        ];
+        objects.extend((0..count).map(|_| String::from("<< /Type /Page /Parent 2 0 R >>")));
+        objects
+    }
+
+    #[test]
+    fn read() {
+        let mut objects = pages(3);
+        objects[0] = String::from(
+            "<< /Type /Catalog /Pages 2 0 R /Outlines 6 0 R /Dests << /intro [3 0 R /Fit] >> \
+             /Names << /Dests << /Kids [12 0 R] >> >> >>",
+        );
+        objects.extend(
+            [
+                "<< /Type /Outlines /First 7 0 R /Last 8 0 R /Count 4 >>",
+                "<< /Title (Intro) /Parent 6 0 R /Next 8 0 R /Dest /intro >>",
+                "<< /Title <FEFF00C9> /Parent 6 0 R /Prev 7 0 R /First 9 0 R /Last 10 0 R /Count 2 \
+                 /A << /S /GoTo /D [5 0 R /XYZ 10 20 null] >> >>",
+                "<< /Title (Named) /Parent 8 0 R /Next 10 0 R /Dest (chapter) >>",
+                "<< /Title (Closed) /Parent 8 0 R /Prev 9 0 R /First 11 0 R /Last 11 0 R /Count -1 >>",
+                "<< /Title (Missing) /Parent 10 0 R /Dest (nowhere) >>",
+                "<< /Limits [(a) (z)] /Names [(chapter) << /D [4 0 R /FitR 0 0 10 20] >>] >>",
+            ]
+            .map(String::from),
+        );
+        let objects: Vec<_> = objects.iter().map(String::as_str).collect();
+        let bytes = test_file::build(&objects, "/Root 1 0 R");
+        let file = PdfFile::load(&bytes).unwrap();
+
+        let missing = OutlineItem::new("Missing", None);
+        let closed = OutlineItem::new("Closed", None).with_children(vec![missing], false);
+        let named = OutlineItem::new(
+            "Named",
+            Some(Destination {
+                page: 1,
+                view: DestinationView::FitR(PdfRect::new(0.0, 0.0, 10.0, 20.0)),
+            }),
+        );
+        let chapter = OutlineItem::new(
+            "É",
+            Some(Destination {
+                page: 2,
+                view: DestinationView::Xyz {
+                    left: Some(10.0),
+                    top: Some(20.0),
+                    zoom: None,
+                },
+            }),
+        )
+        .with_children(vec![named, closed], true);
+        let intro = OutlineItem::new("Intro", Some(Destination::new(0)));
+        assert_eq!(file.outline(), Ok(vec![intro, chapter]));
+    }
+
+    #[test]
+    fn undecodable_titles() {
+        let mut objects = pages(1);
+        objects[0] = String::from("<< /Type /Catalog /Pages 2 0 R /Outlines 4 0 R >>");
+        objects.extend(
+            [
+                "<< /Type /Outlines /First 5 0 R /Last 6 0 R /Count 2 >>",
+                "<< /Title <FEFF0041D83D> /Parent 4 0 R /Next 6 0 R >>",
+                "<< /Title 7 /Parent 4 0 R /Prev 5 0 R >>",
+            ]
+            .map(String::from),
+        );
+        let objects: Vec<_> = objects.iter().map(String::as_str).collect();
+        let bytes = test_file::build(&objects, "/Root 1 0 R");
+        let file = PdfFile::load(&bytes).unwrap();
+        assert_eq!(
+            file.outline(),
+            Ok(vec![
+                OutlineItem::new("A\u{fffd}", None),
+                OutlineItem::new("", None)
+            ])
+        );
+    }
+
+    #[test]
+    fn write() {
+        let objects = pages(2);
+        let objects: Vec<_> = objects.iter().map(String::as_str).collect();
+        let bytes = test_file::build(&objects, "/Root 1 0 R");
+        let mut file = PdfFile::load(&bytes).unwrap();
+
+        let leaf = |title: &str, page| OutlineItem::new(title, Some(Destination::new(page)));
+        let outline = vec![
+            leaf("A", 0).with_children(
+                vec![
+                    leaf("A.1", 1),
+                    leaf("A.2", 1).with_children(vec![leaf("A.2.a", 0)], false),
+                ],
+                true,
+            ),
+            leaf("B", 1).with_children(vec![leaf("B.1", 0), leaf("B.2", 0)], false),
+        ];
+        file.set_outline(&outline).unwrap();
+        let written = file.to_bytes().unwrap();
+        let mut file = PdfFile::load(&written).unwrap();
+        assert_eq!(file.outline(), Ok(outline));
+
+        // Visible items: A, A.1, A.2 and B; closed items count their hidden descendants
+        let root = file
+            .deref(file.catalog().unwrap().get_or_null(b"Outlines"))
+            .unwrap();
+        let root = root.as_dict().unwrap();
+        assert_eq!(root.get_int(b"Count"), Ok(Some(4)));
+        let counts: Vec<_> = [root.get_or_null(b"First"), root.get_or_null(b"Last")]
+            .iter()
+            .map(|i| file.deref(i).unwrap().as_dict().unwrap().get_int(b"Count"))
+            .collect();
+        assert_eq!(counts, [Ok(Some(2)), Ok(Some(-2))]);
+
+        let old_item = root.get_or_null(b"First").as_reference().unwrap().clone();
+        file.set_outline(&[]).unwrap();
+        assert_eq!(file.outline(), Ok(vec![]));
+        assert!(file.resolve(&old_item).is_err());
+        assert!(file.resolve(&IndirectData::new(3, 0)).is_ok());
+    }
+}
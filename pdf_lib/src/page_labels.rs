@@ -0,0 +1,317 @@
+use crate::{
+    NumberTree, PdfDict, PdfFile, PdfName, PdfNumeric, PdfObject, PdfString, PdfVersion, Resolve,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+};
+
+/// Numbering style of a page label range (12.4.2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageLabelStyle {
+    /// Decimal arabic numerals, `D`.
+    Decimal,
+    /// Uppercase roman numerals, `R`.
+    UpperRoman,
+    /// Lowercase roman numerals, `r`.
+    LowerRoman,
+    /// Uppercase letters, `A`: A to Z, then AA to ZZ and so on.
+    UpperLetters,
+    /// Lowercase letters, `a`.
+    LowerLetters,
+}
+
+impl PageLabelStyle {
+    fn from_name(name: &[u8]) -> PdfResult<Self> {
+        Ok(match name {
+            b"D" => Self::Decimal,
+            b"R" => Self::UpperRoman,
+            b"r" => Self::LowerRoman,
+            b"A" => Self::UpperLetters,
+            b"a" => Self::LowerLetters,
+            _ => {
+                return Err(
+                    PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a page label style")
+                );
+            }
+        })
+    }
+
+    fn name(self) -> &'static [u8] {
+        match self {
+            Self::Decimal => b"D",
+            Self::UpperRoman => b"R",
+            Self::LowerRoman => b"r",
+            Self::UpperLetters => b"A",
+            Self::LowerLetters => b"a",
+        }
+    }
+
+    /// The numeric part of a label for `number`, which is at least 1.
+    ///
+    /// Numbers above 100000 are written in decimal whatever the style, their roman numerals
+    /// and letters growing with the number.
+    pub fn format(self, number: u64) -> String {
+        match self {
+            _ if number > MAX_LETTERED_NUMBER => number.to_string(),
+            Self::Decimal => number.to_string(),
+            Self::UpperRoman => roman(number),
+            Self::LowerRoman => roman(number).to_ascii_lowercase(),
+            Self::UpperLetters => letters(number),
+            Self::LowerLetters => letters(number).to_ascii_lowercase(),
+        }
+    }
+}
+
+/// Largest number given in roman numerals or letters.
+const MAX_LETTERED_NUMBER: u64 = 100_000;
+
+/// Roman numerals of `number`, thousands repeating `M` past 3999.
+fn roman(number: u64) -> String {
+    const NUMERALS: [(u64, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut rest = number;
+    let mut result = String::new();
+    for (value, numeral) in NUMERALS {
+        while rest >= value {
+            result.push_str(numeral);
+            rest -= value;
+        }
+    }
+    result
+}
+
+/// Letters of `number`, as A to Z, AA to ZZ, AAA to ZZZ and so on.
+fn letters(number: u64) -> String {
+    let Some(index) = number.checked_sub(1) else {
+        return String::new();
+    };
+    let letter = char::from(b'A' + (index % 26) as u8);
+    std::iter::repeat_n(letter, (index / 26 + 1) as usize).collect()
+}
+
+/// Labels of the pages from `start` to the start of the next range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageLabelRange {
+    /// Index of the first page of the range.
+    pub start: usize,
+    /// Numbering style, `None` for labels made of the prefix only.
+    pub style: Option<PageLabelStyle>,
+    pub prefix: Option<String>,
+    /// Number of the first page of the range, at least 1.
+    pub first: u64,
+}
+
+impl PageLabelRange {
+    /// A range numbered from 1 in `style`, without prefix.
+    pub fn new(start: usize, style: Option<PageLabelStyle>) -> Self {
+        Self {
+            start,
+            style,
+            prefix: None,
+            first: 1,
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn with_first(mut self, first: u64) -> Self {
+        self.first = first;
+        self
+    }
+
+    /// Label of the page at `index`, which is in the range.
+    pub fn label(&self, index: usize) -> String {
+        let mut label = self.prefix.clone().unwrap_or_default();
+        if let Some(style) = self.style {
+            label.push_str(&style.format(self.first + (index - self.start) as u64));
+        }
+        label
+    }
+
+    fn from_dict(start: usize, dict: &PdfDict) -> PdfResult<Self> {
+        let style = dict
+            .get_name(b"S")?
+            .map(|s| PageLabelStyle::from_name(s.bytes()))
+            .transpose()?;
+        let prefix = match dict.get(b"P") {
+            Some(prefix) => Some(prefix.as_string_ref()?.to_text()?),
+            None => None,
+        };
+        let first = match dict.get_int(b"St")? {
+            Some(first) => u64::try_from(first)
+                .ok()
+                .filter(|f| *f >= 1)
+                .ok_or_else(|| {
+                    PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a positive /St")
+                })?,
+            None => 1,
+        };
+        Ok(Self {
+            start,
+            style,
+            prefix,
+            first,
+        })
+    }
+
+    fn to_dict(&self, version: PdfVersion) -> PdfDict {
+        let mut dict = PdfDict::empty();
+        if let Some(style) = self.style {
+            dict.insert(
+                PdfName::from_raw_bytes(b"S"),
+                PdfName::from_raw_bytes(style.name()),
+            );
+        }
+        if let Some(prefix) = &self.prefix {
+            dict.insert(
+                PdfName::from_raw_bytes(b"P"),
+                PdfString::from_text_in(prefix, version),
+            );
+        }
+        if self.first != 1 {
+            dict.insert(
+                PdfName::from_raw_bytes(b"St"),
+                PdfNumeric::from(self.first as i64),
+            );
+        }
+        dict
+    }
+}
+
+impl PdfFile<'_> {
+    /// The page label ranges of the catalog's `/PageLabels` number tree, by first page.
+    pub fn page_labels(&self) -> PdfResult<Vec<PageLabelRange>> {
+        let catalog = self.catalog()?;
+        let Some(root) = catalog.get(b"PageLabels") else {
+            return Ok(Vec::new());
+        };
+        let tree = NumberTree::new(self, self.deref(root)?.as_dict()?);
+        tree.iter()
+            .map(|entry| {
+                let (start, dict) = entry?;
+                let start = usize::try_from(start).map_err(|_| {
+                    PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a page index")
+                })?;
+                PageLabelRange::from_dict(start, dict.as_dict_ref()?)
+            })
+            .collect()
+    }
+
+    /// Label of the page at `index`, `None` if the document has no page labels.
+    ///
+    /// Pages before the first range have empty labels.
+    pub fn page_label(&self, index: usize) -> PdfResult<Option<String>> {
+        let ranges = self.page_labels()?;
+        if ranges.is_empty() {
+            return Ok(None);
+        }
+        let range = ranges.iter().rev().find(|r| r.start <= index);
+        Ok(Some(range.map(|r| r.label(index)).unwrap_or_default()))
+    }
+
+    /// Replaces the page labels with `ranges`, removing `/PageLabels` if there are none.
+    ///
+    /// The first range should start at page 0, which the number tree requires.
+    pub fn set_page_labels(&mut self, ranges: &[PageLabelRange]) -> PdfResult<()> {
+        let catalog_reference = self.catalog_reference()?;
+        let mut catalog = self.catalog()?;
+        if ranges.is_empty() {
+            catalog.remove(b"PageLabels");
+        } else {
+            let version = self.header().version();
+            let entries = ranges
+                .iter()
+                .map(|r| (r.start as i64, PdfObject::from(r.to_dict(version))));
+            let root = NumberTree::write(self, entries);
+            catalog.insert(PdfName::from_raw_bytes(b"PageLabels"), root);
+        }
+        self.set_object(&catalog_reference, catalog);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PageLabelRange, PageLabelStyle, PdfFile, pdf_file::test_file};
+
+    #[test]
+    fn format() {
+        let roman: Vec<_> = [1, 4, 9, 14, 40, 90, 400, 1994]
+            .map(|n| PageLabelStyle::LowerRoman.format(n))
+            .into();
+        assert_eq!(roman, ["i", "iv", "ix", "xiv", "xl", "xc", "cd", "mcmxciv"]);
+        let letters = [1, 26, 27, 52, 53].map(|n| PageLabelStyle::UpperLetters.format(n));
+        assert_eq!(letters, ["A", "Z", "AA", "ZZ", "AAA"]);
+        assert_eq!(PageLabelStyle::UpperLetters.format(100_000).len(), 3847);
+        assert_eq!(PageLabelStyle::LowerRoman.format(100_001), "100001");
+    }
+
+    #[test]
+    fn huge_start() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /PageLabels 4 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+                "<< /Type /Page /Parent 2 0 R >>",
+                "<< /Nums [0 << /S /R /St 9000000000000000000 >>] >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        assert_eq!(
+            file.page_label(0),
+            Ok(Some("9000000000000000000".to_string()))
+        );
+    }
+
+    #[test]
+    fn read_and_write() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /PageLabels 2 0 R >>",
+                "<< /Nums [0 << /S /r >> 4 << /S /D >> 7 << /S /D /P (A-) /St 8 >> 9 << /P (Cover) >>] >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        let labels: Vec<_> = (0..11)
+            .map(|i| file.page_label(i).unwrap().unwrap())
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                "i", "ii", "iii", "iv", "1", "2", "3", "A-8", "A-9", "Cover", "Cover"
+            ]
+        );
+
+        let ranges = [
+            PageLabelRange::new(0, Some(PageLabelStyle::UpperLetters)),
+            PageLabelRange::new(2, Some(PageLabelStyle::Decimal))
+                .with_prefix("p. ")
+                .with_first(10),
+        ];
+        file.set_page_labels(&ranges).unwrap();
+        let written = file.to_bytes().unwrap();
+        let mut file = PdfFile::load(&written).unwrap();
+        assert_eq!(file.page_labels(), Ok(ranges.to_vec()));
+        assert_eq!(file.page_label(1), Ok(Some("B".to_string())));
+        assert_eq!(file.page_label(3), Ok(Some("p. 11".to_string())));
+
+        file.set_page_labels(&[]).unwrap();
+        assert_eq!(file.page_label(0), Ok(None));
+    }
+}
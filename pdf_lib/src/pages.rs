@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+
+use crate::{
+    IndirectData, PdfDict, PdfFile, PdfName, PdfObject, Resolve,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+};
+
+/// Page attributes that page tree nodes pass down to their descendants (7.7.3.4).
+pub(crate) const INHERITABLE: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// How deep the page tree may be, to stop on malformed trees.
+const MAX_PAGE_TREE_DEPTH: usize = 64;
+
+/// A page object, with the attributes it inherits from the page tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    reference: IndirectData,
+    dict: PdfDict,
+    inherited: PdfDict,
+}
+
+impl Page {
+    pub fn reference(&self) -> &IndirectData {
+        &self.reference
+    }
+    /// The page dictionary itself, without inherited attributes.
+    pub fn dict(&self) -> &PdfDict {
+        &self.dict
+    }
+    pub fn dict_mut(&mut self) -> &mut PdfDict {
+        &mut self.dict
+    }
+    /// The attribute `key` of the page, or the one it inherits.
+    pub fn attribute(&self, key: impl AsRef<[u8]>) -> Option<&PdfObject> {
+        let key = key.as_ref();
+        self.dict.get(key).or_else(|| self.inherited.get(key))
+    }
+}
+
+impl PdfFile<'_> {
+    /// The pages in order, from the page tree of the catalog's `/Pages`.
+    pub fn pages(&self) -> PdfResult<Vec<Page>> {
+        let catalog = self.catalog()?;
+        let root = catalog
+            .get_or_null(b"Pages")
+            .as_reference()
+            .ok_or_else(|| {
+                PdfError::with_kind(PdfErrorKind::WrongType)
+                    .expecting("a reference to the page tree")
+            })?;
+        let mut pages = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![(root.clone(), PdfDict::empty(), 0)];
+        while let Some((reference, mut inherited, depth)) = stack.pop() {
+            if !visited.insert(reference.clone()) || depth > MAX_PAGE_TREE_DEPTH {
+                return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                    .expecting("a page tree visiting each node once"));
+            }
+            let dict = self.resolve(&reference)?.as_dict()?;
+            let Some(kids) = dict.get(b"Kids") else {
+                pages.push(Page {
+                    reference,
+                    dict,
+                    inherited,
+                });
+                continue;
+            };
+            for key in INHERITABLE {
+                if let Some(value) = dict.get(key) {
+                    inherited.insert(PdfName::from_raw_bytes(key), value.clone());
+                }
+            }
+            let kids = self.deref(kids)?.as_array()?;
+            // Pushed last to first so that the first kid is visited next
+            for kid in kids.iter().rev() {
+                let kid = kid.as_reference().ok_or_else(|| {
+                    PdfError::with_kind(PdfErrorKind::WrongType).expecting("a reference to a page")
+                })?;
+                stack.push((kid.clone(), inherited.clone(), depth + 1));
+            }
+        }
+        Ok(pages)
+    }
+
+    /// The page at `index`, counting from 0.
+    pub fn page(&self, index: usize) -> PdfResult<Page> {
+        self.pages()?.into_iter().nth(index).ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a page index in range")
+        })
+    }
+
+    /// Index of the page object `reference`, `None` if it is not a page of the document.
+    pub fn page_index(&self, reference: &IndirectData) -> PdfResult<Option<usize>> {
+        Ok(self
+            .pages()?
+            .iter()
+            .position(|p| p.reference() == reference))
+    }
+
+    /// Writes back a page changed through [`Page::dict_mut`].
+    pub fn set_page(&mut self, page: &Page) {
+        self.set_object(&page.reference, page.dict.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IndirectData, PdfErrorKind, PdfFile, pdf_file::test_file};
+
+    #[test]
+    fn page_tree() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 3 /Rotate 90 /MediaBox [0 0 10 10] >>",
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 5 5] >>",
+                "<< /Type /Pages /Parent 2 0 R /Kids [5 0 R 6 0 R] /Count 2 /Rotate 180 >>",
+                "<< /Type /Page /Parent 4 0 R >>",
+                "<< /Type /Page /Parent 4 0 R /Rotate 0 >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        let pages = file.pages().unwrap();
+        let objects: Vec<_> = pages.iter().map(|p| p.reference().object()).collect();
+        assert_eq!(objects, [3, 5, 6]);
+        let rotate: Vec<_> = pages
+            .iter()
+            .map(|p| {
+                p.attribute(b"Rotate")
+                    .unwrap()
+                    .as_numeric_ref()
+                    .unwrap()
+                    .to_i64()
+            })
+            .collect();
+        assert_eq!(rotate, [Ok(90), Ok(180), Ok(0)]);
+        assert!(pages[1].dict().get(b"MediaBox").is_none());
+        assert!(pages[1].attribute(b"MediaBox").is_some());
+        assert_eq!(file.page_index(&IndirectData::new(6, 0)), Ok(Some(2)));
+        assert_eq!(file.page_index(&IndirectData::new(4, 0)), Ok(None));
+        assert!(file.page(3).is_err());
+    }
+
+    #[test]
+    fn cycle() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [2 0 R] /Count 1 >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        assert_eq!(file.pages().unwrap_err().kind(), &PdfErrorKind::InvalidData);
+    }
+}
@@ -1,7 +1,10 @@
-use std::{error::Error, fmt::Display, num::ParseIntError, str::Utf8Error};
+use std::{error::Error, fmt::Display, io, num::ParseIntError, str::Utf8Error};
 
 use crate::filter::FilterError;
 
+/// Number of input bytes kept in an error to show where parsing stopped.
+const SNIPPET_LEN: usize = 24;
+
 #[allow(dead_code)]
 pub type PdfResult<T> = std::result::Result<T, PdfError>;
 #[derive(PartialEq, Debug, Clone)]
@@ -12,21 +15,185 @@ pub enum PdfErrorKind {
     External(String),
     MissingStreamLength,
     Filter(FilterError),
+    /// Reference to an object that could not be found.
+    UnresolvedReference(usize, usize),
 }
-#[allow(dead_code)]
-#[derive(PartialEq, Debug, Clone)]
+
+/// Where an error happened and what the parser was looking for.
+#[derive(Debug, Clone, Default)]
+struct ErrorContext {
+    offset: Option<usize>,
+    object: Option<(usize, usize)>,
+    expected: Option<&'static str>,
+    snippet: Vec<u8>,
+    // Length of the input left when the error was raised, turned into `offset` by `locate`
+    remaining: Option<usize>,
+    // The input ended before the value did, wherever the error was raised
+    input_ended: bool,
+}
+
+#[derive(Debug, Clone)]
 pub struct PdfError {
     pub(crate) kind: PdfErrorKind,
+    context: Option<Box<ErrorContext>>,
 }
+
 impl PdfError {
     pub(crate) fn with_kind(kind: PdfErrorKind) -> Self {
-        Self { kind }
+        Self {
+            kind,
+            context: None,
+        }
+    }
+
+    /// Parse error raised while looking at `bytes`, which must be a suffix of the parsed input.
+    pub(crate) fn parse_at(bytes: &[u8], expected: &'static str) -> Self {
+        Self::with_kind(PdfErrorKind::Parse)
+            .at(bytes)
+            .expecting(expected)
+    }
+
+    /// Records the position of `bytes` if the error has none yet.
+    pub(crate) fn at(mut self, bytes: &[u8]) -> Self {
+        let context = self.context_mut();
+        if context.remaining.is_none() && context.offset.is_none() {
+            context.remaining = Some(bytes.len());
+            context.snippet = bytes[..bytes.len().min(SNIPPET_LEN)].to_vec();
+        }
+        self
+    }
+
+    /// Records an absolute offset if the error has no position yet.
+    pub(crate) fn at_offset(mut self, offset: usize, snippet: &[u8]) -> Self {
+        let context = self.context_mut();
+        if context.remaining.is_none() && context.offset.is_none() {
+            context.offset = Some(offset);
+            context.snippet = snippet[..snippet.len().min(SNIPPET_LEN)].to_vec();
+        }
+        self
+    }
+
+    pub(crate) fn expecting(mut self, expected: &'static str) -> Self {
+        self.context_mut().expected.get_or_insert(expected);
+        self
+    }
+
+    /// Marks the error as caused by the input ending before the value, such as stream data
+    /// shorter than its `/Length`.
+    pub(crate) fn input_ended(mut self) -> Self {
+        self.context_mut().input_ended = true;
+        self
+    }
+
+    /// Whether a longer input might have avoided the error: it was marked so, or raised in the
+    /// last bytes of the input.
+    pub(crate) fn is_end_of_input(&self) -> bool {
+        self.context.as_deref().is_some_and(|c| {
+            let positioned = c.offset.is_some() || c.remaining.is_some();
+            c.input_ended || (positioned && c.snippet.len() < SNIPPET_LEN)
+        })
+    }
+
+    /// Marks the error as raised inside indirect object `object generation`.
+    pub(crate) fn in_object(mut self, object: usize, generation: usize) -> Self {
+        self.context_mut()
+            .object
+            .get_or_insert((object, generation));
+        self
+    }
+
+    /// Turns a relative position into an absolute offset, `input` starting `offset` bytes into the file.
+    pub(crate) fn locate(mut self, input: &[u8], offset: usize) -> Self {
+        if let Some(context) = self.context.as_deref_mut()
+            && let Some(remaining) = context.remaining.take()
+        {
+            context.offset = input
+                .len()
+                .checked_sub(remaining)
+                .map(|consumed| offset + consumed);
+        }
+        self
+    }
+
+    /// Moves an already located error by `delta` bytes.
+    pub(crate) fn shifted(mut self, delta: usize) -> Self {
+        if let Some(offset) = self.context.as_deref_mut().and_then(|c| c.offset.as_mut()) {
+            *offset += delta;
+        }
+        self
+    }
+
+    pub fn kind(&self) -> &PdfErrorKind {
+        &self.kind
+    }
+    /// Absolute byte offset of the error, when known.
+    pub fn offset(&self) -> Option<usize> {
+        self.context.as_ref().and_then(|c| c.offset)
+    }
+    /// Object and generation numbers of the indirect object being parsed, if any.
+    pub fn object(&self) -> Option<(usize, usize)> {
+        self.context.as_ref().and_then(|c| c.object)
+    }
+    pub fn expected(&self) -> Option<&'static str> {
+        self.context.as_ref().and_then(|c| c.expected)
+    }
+    /// Bytes found at the error position.
+    pub fn snippet(&self) -> &[u8] {
+        self.context.as_ref().map_or(&[], |c| &c.snippet)
+    }
+
+    fn context_mut(&mut self) -> &mut ErrorContext {
+        self.context.get_or_insert_default()
+    }
+}
+
+// The context only helps diagnosing, two errors are the same if their kinds are.
+impl PartialEq for PdfError {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl Display for PdfErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PdfErrorKind::Parse => write!(f, "parse error"),
+            PdfErrorKind::WrongType => write!(f, "wrong object type"),
+            PdfErrorKind::InvalidData => write!(f, "invalid data"),
+            PdfErrorKind::External(e) => write!(f, "{e}"),
+            PdfErrorKind::MissingStreamLength => write!(f, "missing stream /Length"),
+            PdfErrorKind::Filter(e) => write!(f, "filter error: {e}"),
+            PdfErrorKind::UnresolvedReference(object, generation) => {
+                write!(f, "unresolved reference {object} {generation} R")
+            }
+        }
     }
 }
 
 impl Display for PdfError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        write!(f, "{}", self.kind)?;
+        let Some(context) = &self.context else {
+            return Ok(());
+        };
+        if let Some(offset) = context.offset {
+            write!(f, " at byte {offset}")?;
+        }
+        if let Some((object, generation)) = context.object {
+            write!(f, " in object {object} {generation}")?;
+        }
+        if let Some(expected) = context.expected {
+            write!(f, ": expected {expected}")?;
+        }
+        if context.offset.is_some() || context.remaining.is_some() {
+            let found = context.snippet.escape_ascii();
+            if context.snippet.is_empty() {
+                write!(f, ", found end of input")?;
+            } else {
+                write!(f, ", found \"{found}\"")?;
+            }
+        }
+        Ok(())
     }
 }
 impl Error for PdfError {}
@@ -36,13 +203,42 @@ macro_rules! impl_from {
         $(
             impl From<$E> for PdfError {
                 fn from(e: $E) -> Self {
-                    Self {
-                        kind: PdfErrorKind::External(e.to_string()),
-                    }
+                    Self::with_kind(PdfErrorKind::External(e.to_string()))
                 }
             }
         )+
     };
 }
 
-impl_from!(ParseIntError, Utf8Error);
+impl_from!(ParseIntError, Utf8Error, io::Error);
+
+#[cfg(test)]
+mod tests {
+    use crate::{PdfArray, PdfDict, parse};
+
+    use super::*;
+
+    #[test]
+    fn offset_and_object() {
+        let input = b"[1 2 3 0 obj << /Length 3 >> stream\nabc\nendstraem endobj]";
+        let err = parse::<PdfArray>(input).unwrap_err();
+        assert_eq!(err.kind(), &PdfErrorKind::Parse);
+        assert_eq!(err.offset(), Some(40));
+        assert_eq!(err.object(), Some((3, 0)));
+        assert_eq!(err.expected(), Some("`endstream`"));
+        assert!(err.snippet().starts_with(b"endstraem"));
+    }
+
+    #[test]
+    fn display() {
+        let err = parse::<PdfDict>(b"<< /Key (abc >>").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parse error at byte 8: expected `)`, found \"(abc >>\""
+        );
+        assert_eq!(
+            PdfError::with_kind(PdfErrorKind::WrongType).to_string(),
+            "wrong object type"
+        );
+    }
+}
@@ -0,0 +1,1263 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    io::Write,
+};
+
+use crate::{
+    IndirectData, Parser, PdfDict, PdfName, PdfNumeric, PdfObject, PdfStream, Resolve, Writable,
+    cleanup::{collect_references, is_page_tree_node, rewrite},
+    next_eol,
+    pages::{INHERITABLE, Page},
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+    strip_whitespace,
+};
+
+use super::{
+    PdfFile,
+    pdf_cross_ref_table::{PdfCrossRefTableEntry, PdfCrossRefTableSection},
+    writer::FREE_LIST_HEAD_GENERATION,
+};
+
+/// Width the values of the linearization dictionary and of the first page trailer's `/Prev`
+/// are padded to, for the bytes before the objects to have the same length whatever the
+/// offsets they give.
+const PADDED_WIDTH: usize = 10;
+
+/// How far from the start of the file the linearization dictionary must be (F.3.3).
+const LINEARIZATION_DICT_SEARCH_LEN: usize = 1024;
+
+/// A way in which a document does not follow the layout of a linearized file (Annex F).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinearizationIssue {
+    /// The first object of the file is not a linearization parameter dictionary.
+    NotLinearized,
+    /// `/L` is not the length of the file, which was changed after being linearized.
+    FileLength { declared: usize, actual: usize },
+    /// `/N` is not the number of pages.
+    PageCount { declared: usize, actual: usize },
+    /// `/O` is not the object number of the first page.
+    FirstPage { declared: usize, actual: usize },
+    /// An object used by the first page is not before `/E`.
+    FirstPageEnd { object: usize },
+    /// The page object of `page` comes before the one of the previous page.
+    PageOrder { page: usize },
+    /// `/T` is not the offset of the first entry of the main cross-reference table.
+    MainXref,
+    /// `/H` is not the offset of a hint stream, or its tables could not be read.
+    HintStream,
+    /// The page offset hint table places the page object of `page` elsewhere.
+    PageOffset {
+        page: usize,
+        hinted: usize,
+        actual: usize,
+    },
+}
+
+impl Display for LinearizationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinearizationIssue::NotLinearized => {
+                write!(f, "no linearization parameter dictionary")
+            }
+            LinearizationIssue::FileLength { declared, actual } => {
+                write!(f, "/L is {declared} but the file is {actual} bytes")
+            }
+            LinearizationIssue::PageCount { declared, actual } => {
+                write!(f, "/N is {declared} but there are {actual} pages")
+            }
+            LinearizationIssue::FirstPage { declared, actual } => {
+                write!(f, "/O is {declared} but the first page is object {actual}")
+            }
+            LinearizationIssue::FirstPageEnd { object } => {
+                write!(f, "object {object} of the first page is after /E")
+            }
+            LinearizationIssue::PageOrder { page } => {
+                write!(f, "page {page} comes before the previous one")
+            }
+            LinearizationIssue::MainXref => {
+                write!(f, "/T is not the start of the main cross-reference table")
+            }
+            LinearizationIssue::HintStream => write!(f, "unreadable hint stream"),
+            LinearizationIssue::PageOffset {
+                page,
+                hinted,
+                actual,
+            } => write!(f, "page {page} hinted at {hinted} but found at {actual}"),
+        }
+    }
+}
+
+/// Number of bits needed to write `value`.
+fn bit_width(value: u64) -> u32 {
+    u64::BITS - value.leading_zeros()
+}
+
+/// Writes the items of the hint tables, packed most significant bit first (F.4).
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// Bits used in the last byte, 0 if it is full.
+    used: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u64, bits: u32) {
+        for i in (0..bits).rev() {
+            if self.used == 0 {
+                self.bytes.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            *self.bytes.last_mut().expect("a byte was pushed") |= bit << (7 - self.used);
+            self.used = (self.used + 1) % 8;
+        }
+    }
+
+    /// Starts the next item on a new byte, as each item of the tables does.
+    fn align(&mut self) {
+        self.used = 0;
+    }
+}
+
+/// Reads what [`BitWriter`] writes.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// Position in bits.
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, bits: u32) -> PdfResult<u64> {
+        if bits > u64::BITS {
+            return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                .expecting("hint table items of at most 64 bits"));
+        }
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = self.bytes.get(self.position / 8).ok_or_else(|| {
+                PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a complete hint table")
+            })?;
+            let bit = (byte >> (7 - self.position % 8)) & 1;
+            value = value << 1 | u64::from(bit);
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn read_usize(&mut self, bits: u32) -> PdfResult<usize> {
+        self.read(bits).map(|v| v as usize)
+    }
+
+    fn read_bits(&mut self) -> PdfResult<u32> {
+        self.read(16).map(|v| v as u32)
+    }
+
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+/// The entries of a page in the page offset hint table (table F.4).
+#[derive(Debug, Clone, PartialEq)]
+struct PageHint {
+    /// Number of objects of the page.
+    objects: usize,
+    /// Length of the page, in bytes.
+    len: usize,
+    /// Identifiers of the shared object groups the page uses.
+    shared: Vec<usize>,
+}
+
+/// The page offset hint table (tables F.3 and F.4), without the content stream and fractional
+/// position items, which readers do not use. Offsets are given as if the hint stream was not
+/// in the file.
+#[derive(Debug, Clone, PartialEq)]
+struct PageOffsetHints {
+    /// Offset of the page object of the first page.
+    first_page_offset: usize,
+    pages: Vec<PageHint>,
+}
+
+impl PageOffsetHints {
+    /// Reads the table of `pages` pages, whose shared object identifiers are below `groups`.
+    fn read(r: &mut BitReader, pages: usize, groups: usize) -> PdfResult<Self> {
+        let least_objects = r.read_usize(32)?;
+        let first_page_offset = r.read_usize(32)?;
+        let objects_bits = r.read_bits()?;
+        let least_len = r.read_usize(32)?;
+        let len_bits = r.read_bits()?;
+        // Content stream offsets and lengths
+        r.read(32)?;
+        r.read_bits()?;
+        r.read(32)?;
+        r.read_bits()?;
+        let shared_count_bits = r.read_bits()?;
+        let identifier_bits = r.read_bits()?;
+        let numerator_bits = r.read_bits()?;
+        r.read(16)?;
+
+        let mut hints = Vec::new();
+        for _ in 0..pages {
+            hints.push(PageHint {
+                objects: least_objects + r.read_usize(objects_bits)?,
+                len: 0,
+                shared: Vec::new(),
+            });
+        }
+        r.align();
+        for hint in &mut hints {
+            hint.len = least_len + r.read_usize(len_bits)?;
+        }
+        r.align();
+        let mut counts = Vec::new();
+        for _ in 0..pages {
+            let count = r.read_usize(shared_count_bits)?;
+            if count > groups {
+                return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                    .expecting("at most one reference to each shared object group"));
+            }
+            counts.push(count);
+        }
+        r.align();
+        for (hint, count) in hints.iter_mut().zip(counts) {
+            for _ in 0..count {
+                hint.shared.push(r.read_usize(identifier_bits)?);
+            }
+        }
+        r.align();
+        for _ in hints.iter().flat_map(|h| &h.shared) {
+            r.read(numerator_bits)?;
+        }
+        r.align();
+        Ok(Self {
+            first_page_offset,
+            pages: hints,
+        })
+    }
+}
+
+/// The shared object hint table (tables F.5 and F.6), without the MD5 signatures. Offsets
+/// are given as if the hint stream was not in the file.
+#[derive(Debug, Clone, PartialEq)]
+struct SharedObjectHints {
+    /// Object number of the first object of the shared objects section, 0 if there is none.
+    first_shared: usize,
+    /// Offset of that object.
+    first_shared_offset: usize,
+    /// Number of groups in the first page section, the first ones.
+    first_page_groups: usize,
+    /// Length of each group, those of the first page section then of the shared objects
+    /// section.
+    group_lens: Vec<usize>,
+}
+
+impl SharedObjectHints {
+    fn read(r: &mut BitReader) -> PdfResult<Self> {
+        let first_shared = r.read_usize(32)?;
+        let first_shared_offset = r.read_usize(32)?;
+        let first_page_groups = r.read_usize(32)?;
+        let groups = r.read_usize(32)?;
+        let group_objects_bits = r.read_bits()?;
+        let least_len = r.read_usize(32)?;
+        let len_bits = r.read_bits()?;
+        // Each group takes at least the bit of its MD5 flag
+        if groups > r.bytes.len() * 8 || first_page_groups > groups {
+            return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                .expecting("a shared object hint table as long as its groups"));
+        }
+
+        let mut group_lens = Vec::new();
+        for _ in 0..groups {
+            group_lens.push(least_len + r.read_usize(len_bits)?);
+        }
+        r.align();
+        let mut signed = Vec::new();
+        for _ in 0..groups {
+            signed.push(r.read(1)? == 1);
+        }
+        r.align();
+        for _ in signed.iter().filter(|s| **s) {
+            r.read(64)?;
+            r.read(64)?;
+        }
+        for _ in 0..groups {
+            r.read(group_objects_bits)?;
+        }
+        Ok(Self {
+            first_shared,
+            first_shared_offset,
+            first_page_groups,
+            group_lens,
+        })
+    }
+}
+
+/// The objects of the document in the order of a linearized file (F.3).
+struct Layout {
+    catalog: IndirectData,
+    /// The first page object, then all the objects the first page uses.
+    first_page: Vec<IndirectData>,
+    /// For each other page, its page object then the objects no other page uses.
+    pages: Vec<Vec<IndirectData>>,
+    /// The objects used by several pages, but not by the first one.
+    shared: Vec<IndirectData>,
+    /// For each other page, the objects it shares with other pages.
+    shared_by_page: Vec<Vec<IndirectData>>,
+    /// The objects used by no page, such as the page tree or the outline.
+    other: Vec<IndirectData>,
+}
+
+/// Indirect objects written one after the other, with their offsets from the first.
+#[derive(Default)]
+struct Section {
+    bytes: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl Section {
+    fn push(&mut self, reference: &IndirectData, object: &PdfObject) -> PdfResult<()> {
+        self.offsets.push(self.bytes.len());
+        writeln!(
+            self.bytes,
+            "{} {} obj",
+            reference.object(),
+            reference.generation()
+        )?;
+        object.write_to(&mut self.bytes)?;
+        self.bytes.extend_from_slice(b"\nendobj\n");
+        Ok(())
+    }
+
+    /// Length of the `index`-th object.
+    fn object_len(&self, index: usize) -> usize {
+        let end = self
+            .offsets
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.bytes.len());
+        end - self.offsets[index]
+    }
+}
+
+/// The values of the linearization parameter dictionary (table F.1).
+#[derive(Debug, Default)]
+struct Parameters {
+    /// `/L`, length of the file.
+    file_len: usize,
+    /// `/H`, offset and length of the primary hint stream.
+    hint_stream: [usize; 2],
+    /// `/O`, object number of the first page.
+    first_page: usize,
+    /// `/E`, offset of the end of the first page section.
+    first_page_end: usize,
+    /// `/N`, number of pages.
+    pages: usize,
+    /// `/T`, offset of the white-space before the first entry of the main cross-reference table.
+    main_xref: usize,
+}
+
+impl Parameters {
+    /// The dictionary as the indirect object `number`, its numbers padded to a fixed width.
+    fn object(&self, number: usize) -> Vec<u8> {
+        let w = PADDED_WIDTH;
+        format!(
+            "{number} 0 obj\n<< /Linearized 1 /L {:<w$} /H [{:<w$} {:<w$}] /O {:<w$} /E {:<w$} /N {:<w$} /T {:<w$} >>\nendobj\n",
+            self.file_len,
+            self.hint_stream[0],
+            self.hint_stream[1],
+            self.first_page,
+            self.first_page_end,
+            self.pages,
+            self.main_xref,
+        )
+        .into_bytes()
+    }
+
+    fn from_dict(dict: &PdfDict) -> Option<Self> {
+        let number = |key: &[u8]| {
+            dict.get_int(key)
+                .ok()
+                .flatten()
+                .and_then(|n| usize::try_from(n).ok())
+        };
+        let hint_stream = dict.get_array(b"H").ok().flatten()?;
+        let hint = |i: usize| {
+            hint_stream
+                .get(i)
+                .and_then(|n| n.as_numeric_ref().ok())
+                .and_then(|n| n.to_usize().ok())
+        };
+        Some(Self {
+            file_len: number(b"L")?,
+            hint_stream: [hint(0)?, hint(1)?],
+            first_page: number(b"O")?,
+            first_page_end: number(b"E")?,
+            pages: number(b"N")?,
+            main_xref: number(b"T")?,
+        })
+    }
+}
+
+/// The page offset hint table (table F.3 and F.4), for pages laid out as `starts` and `ends`,
+/// with `objects` objects each and the identifiers of the shared objects they use.
+///
+/// The content stream items are not used by readers, and written as the whole pages.
+fn page_offset_table(
+    w: &mut BitWriter,
+    starts: &[usize],
+    ends: &[usize],
+    objects: &[usize],
+    shared: &[Vec<usize>],
+) {
+    let lengths: Vec<_> = starts.iter().zip(ends).map(|(s, e)| e - s).collect();
+    let least_objects = objects.iter().min().copied().unwrap_or(0);
+    let objects_bits = bit_width((objects.iter().max().unwrap_or(&0) - least_objects) as u64);
+    let least_len = lengths.iter().min().copied().unwrap_or(0);
+    let len_bits = bit_width((lengths.iter().max().unwrap_or(&0) - least_len) as u64);
+    let shared_count_bits = bit_width(shared.iter().map(Vec::len).max().unwrap_or(0) as u64);
+    let identifier_bits = bit_width(shared.iter().flatten().max().copied().unwrap_or(0) as u64);
+
+    w.write(least_objects as u64, 32);
+    w.write(starts[0] as u64, 32);
+    w.write(objects_bits.into(), 16);
+    w.write(least_len as u64, 32);
+    w.write(len_bits.into(), 16);
+    // Content stream offsets, then lengths
+    w.write(0, 32);
+    w.write(0, 16);
+    w.write(least_len as u64, 32);
+    w.write(len_bits.into(), 16);
+    w.write(shared_count_bits.into(), 16);
+    w.write(identifier_bits.into(), 16);
+    // Fractional positions of the shared objects in the pages, not given
+    w.write(0, 16);
+    w.write(1, 16);
+
+    for count in objects {
+        w.write((count - least_objects) as u64, objects_bits);
+    }
+    w.align();
+    for len in &lengths {
+        w.write((len - least_len) as u64, len_bits);
+    }
+    w.align();
+    for identifiers in shared {
+        w.write(identifiers.len() as u64, shared_count_bits);
+    }
+    w.align();
+    for identifier in shared.iter().flatten() {
+        w.write(*identifier as u64, identifier_bits);
+    }
+    w.align();
+    for len in &lengths {
+        w.write((len - least_len) as u64, len_bits);
+    }
+    w.align();
+}
+
+/// The shared object hint table (table F.5 and F.6) of objects of `lengths`, the first
+/// `first_page` ones being in the first page section, and the others from the object
+/// `first_shared` at `first_shared_offset`. Each object is its own group.
+fn shared_object_table(
+    w: &mut BitWriter,
+    lengths: &[usize],
+    first_page: usize,
+    first_shared: usize,
+    first_shared_offset: usize,
+) {
+    let least_len = lengths.iter().min().copied().unwrap_or(0);
+    let len_bits = bit_width((lengths.iter().max().unwrap_or(&0) - least_len) as u64);
+
+    w.write(first_shared as u64, 32);
+    w.write(first_shared_offset as u64, 32);
+    w.write(first_page as u64, 32);
+    w.write(lengths.len() as u64, 32);
+    w.write(0, 16);
+    w.write(least_len as u64, 32);
+    w.write(len_bits.into(), 16);
+
+    for len in lengths {
+        w.write((len - least_len) as u64, len_bits);
+    }
+    w.align();
+    // No MD5 signatures
+    for _ in lengths {
+        w.write(0, 1);
+    }
+    w.align();
+}
+
+impl PdfFile<'_> {
+    /// The objects `page` uses, itself first: those reached from its dictionary and inherited
+    /// attributes, without going up the page tree, to other pages or to the catalog.
+    fn page_objects(&self, page: &Page, catalog: &IndirectData) -> PdfResult<Vec<IndirectData>> {
+        let mut references = Vec::new();
+        for (key, value) in page.dict().iter() {
+            if key.bytes() != b"Parent" {
+                collect_references(value, &mut references);
+            }
+        }
+        for key in INHERITABLE {
+            if !page.dict().contains_key(key)
+                && let Some(value) = page.attribute(key)
+            {
+                collect_references(value, &mut references);
+            }
+        }
+        let mut objects = vec![page.reference().clone()];
+        let mut seen: HashSet<_> = objects.iter().cloned().collect();
+        seen.insert(catalog.clone());
+        let mut stack: Vec<_> = references.into_iter().rev().collect();
+        while let Some(reference) = stack.pop() {
+            if !seen.insert(reference.clone()) {
+                continue;
+            }
+            let object = match self.resolve(&reference) {
+                Ok(object) => object,
+                Err(e) if matches!(e.kind(), PdfErrorKind::UnresolvedReference(..)) => continue,
+                Err(e) => return Err(e),
+            };
+            if is_page_tree_node(&object) {
+                continue;
+            }
+            objects.push(reference);
+            let mut references = Vec::new();
+            collect_references(&object, &mut references);
+            stack.extend(references.into_iter().rev());
+        }
+        Ok(objects)
+    }
+
+    fn layout(&self, pages: &[Page]) -> PdfResult<Layout> {
+        let catalog = self.catalog_reference()?;
+        let used = pages
+            .iter()
+            .map(|page| self.page_objects(page, &catalog))
+            .collect::<PdfResult<Vec<_>>>()?;
+        let mut users: HashMap<&IndirectData, usize> = HashMap::new();
+        for objects in &used {
+            for reference in objects {
+                *users.entry(reference).or_default() += 1;
+            }
+        }
+        let first_page = used[0].clone();
+        let mut placed: HashSet<_> = first_page.iter().cloned().collect();
+        placed.insert(catalog.clone());
+
+        let mut page_groups = Vec::new();
+        let mut shared_by_page = Vec::new();
+        for objects in &used[1..] {
+            let mut group = Vec::new();
+            for (i, reference) in objects.iter().enumerate() {
+                // The page object leads its group even if it is used twice
+                if (i == 0 || users[reference] == 1) && placed.insert(reference.clone()) {
+                    group.push(reference.clone());
+                }
+            }
+            page_groups.push(group);
+            shared_by_page.push(
+                objects[1..]
+                    .iter()
+                    .filter(|r| users[r] > 1)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            );
+        }
+        let mut shared = Vec::new();
+        for reference in shared_by_page.iter().flatten() {
+            if placed.insert(reference.clone()) {
+                shared.push(reference.clone());
+            }
+        }
+        let other = self
+            .objects()
+            .into_iter()
+            .filter(|r| !placed.contains(r))
+            .collect();
+        Ok(Layout {
+            catalog,
+            first_page,
+            pages: page_groups,
+            shared,
+            shared_by_page,
+            other,
+        })
+    }
+
+    /// Writes the document as a linearized file (Annex F), which viewers can show the first
+    /// page of before the rest is loaded, and any page of fetching only its part of the file.
+    ///
+    /// Objects are renumbered: the first page section, from the linearization dictionary to the
+    /// first page's objects, takes the last numbers and the first cross-reference table, and
+    /// the other pages, shared objects and the rest of the document the main one. The hint
+    /// stream has a page offset and a shared object hint table. The document must have a page,
+    /// and not be encrypted, the keys of its strings depending on the object numbers.
+    pub fn write_linearized(&self, mut w: impl Write) -> PdfResult<()> {
+        if self.trailer.dict.contains_key(b"Encrypt") {
+            return Err(
+                PdfError::with_kind(PdfErrorKind::InvalidData).expecting("an unencrypted document")
+            );
+        }
+        let pages = self.pages()?;
+        if pages.is_empty() {
+            return Err(PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a page"));
+        }
+        let layout = self.layout(&pages)?;
+
+        // The main section is numbered from 1 in the order of the file, the first page section
+        // after it
+        let main: Vec<_> = layout
+            .pages
+            .iter()
+            .flatten()
+            .chain(&layout.shared)
+            .chain(&layout.other)
+            .collect();
+        let linearization = main.len() + 1;
+        let catalog = IndirectData::new(linearization + 1, 0);
+        let hint = IndirectData::new(linearization + 2, 0);
+        let first_number = linearization + 3;
+        let size = first_number + layout.first_page.len();
+        let mut moved: HashMap<_, _> = main
+            .iter()
+            .enumerate()
+            .map(|(i, r)| ((*r).clone(), IndirectData::new(i + 1, 0)))
+            .collect();
+        moved.insert(layout.catalog.clone(), catalog.clone());
+        for (i, reference) in layout.first_page.iter().enumerate() {
+            moved.insert(reference.clone(), IndirectData::new(first_number + i, 0));
+        }
+        let write_section = |references: &mut dyn Iterator<Item = &IndirectData>| {
+            let mut section = Section::default();
+            for reference in references {
+                let object = rewrite(&self.resolve(reference)?, &moved, false);
+                section.push(&moved[reference], &object)?;
+            }
+            PdfResult::Ok(section)
+        };
+        let catalog_section = write_section(&mut [&layout.catalog].into_iter())?;
+        let first_section = write_section(&mut layout.first_page.iter())?;
+        let main_section = write_section(&mut main.into_iter())?;
+
+        let mut trailer = rewrite(&self.trailer.dict.clone().into(), &moved, false).as_dict()?;
+        trailer.remove(b"Prev");
+        trailer.remove(b"XRefStm");
+        trailer.insert(
+            PdfName::from_raw_bytes(b"Size"),
+            PdfNumeric::PdfInt(size as i64),
+        );
+        let first_trailer = |prev: usize| {
+            let mut bytes = b"trailer\n".to_vec();
+            let dict = trailer.to_bytes();
+            bytes.extend_from_slice(dict.strip_suffix(b">>").unwrap_or(&dict));
+            let w = PADDED_WIDTH;
+            bytes.extend(format!("/Prev {prev:<w$}>>\nstartxref\n0\n%%EOF\n").into_bytes());
+            bytes
+        };
+
+        // Everything before the catalog has the same length whatever the offsets
+        let header = self.header.to_string().into_bytes();
+        let first_xref_offset = header.len() + Parameters::default().object(linearization).len();
+        let first_xref_len = PdfCrossRefTableSection::with_entries(
+            linearization,
+            vec![PdfCrossRefTableEntry::in_use(0, 0); size - linearization],
+        )
+        .to_string()
+        .len();
+        let catalog_offset = first_xref_offset + first_xref_len + first_trailer(0).len();
+        let hint_offset = catalog_offset + catalog_section.bytes.len();
+
+        // Offsets in the hint tables are given as if the hint stream was not there
+        let first_offset = hint_offset;
+        let main_offset = first_offset + first_section.bytes.len();
+        let mut starts = vec![first_offset];
+        let mut ends = vec![main_offset];
+        let mut objects = vec![layout.first_page.len()];
+        let mut index = 0;
+        for group in &layout.pages {
+            starts.push(main_offset + main_section.offsets[index]);
+            index += group.len();
+            ends.push(
+                main_offset
+                    + main_section
+                        .offsets
+                        .get(index)
+                        .copied()
+                        .unwrap_or(main_section.bytes.len()),
+            );
+            objects.push(group.len());
+        }
+        let identifiers: HashMap<_, _> = layout
+            .first_page
+            .iter()
+            .chain(&layout.shared)
+            .enumerate()
+            .map(|(i, r)| (r, i))
+            .collect();
+        let mut shared: Vec<Vec<usize>> = vec![Vec::new()];
+        shared.extend(
+            layout
+                .shared_by_page
+                .iter()
+                .map(|used| used.iter().map(|r| identifiers[r]).collect()),
+        );
+        let mut tables = BitWriter::default();
+        page_offset_table(&mut tables, &starts, &ends, &objects, &shared);
+        let shared_table_offset = tables.bytes.len();
+        let mut lengths: Vec<_> = (0..layout.first_page.len())
+            .map(|i| first_section.object_len(i))
+            .collect();
+        let first_shared = index;
+        lengths.extend((0..layout.shared.len()).map(|i| main_section.object_len(first_shared + i)));
+        let (shared_number, shared_offset) = match main_section.offsets.get(first_shared) {
+            Some(offset) if !layout.shared.is_empty() => (first_shared + 1, main_offset + offset),
+            _ => (0, 0),
+        };
+        shared_object_table(
+            &mut tables,
+            &lengths,
+            layout.first_page.len(),
+            shared_number,
+            shared_offset,
+        );
+        let mut info = PdfDict::empty();
+        info.insert(
+            PdfName::from_raw_bytes(b"S"),
+            PdfNumeric::PdfInt(shared_table_offset as i64),
+        );
+        let mut hint_section = Section::default();
+        hint_section.push(&hint, &PdfStream::new(info, tables.bytes).into())?;
+        let hint_len = hint_section.bytes.len();
+
+        let main_xref_offset = main_offset + hint_len + main_section.bytes.len();
+        let mut entries = vec![PdfCrossRefTableEntry::free(0, FREE_LIST_HEAD_GENERATION)];
+        entries.extend(
+            main_section
+                .offsets
+                .iter()
+                .map(|o| PdfCrossRefTableEntry::in_use((main_offset + hint_len + o) as u64, 0)),
+        );
+        let main_xref = PdfCrossRefTableSection::with_entries(0, entries).to_string();
+        let mut main_trailer = PdfDict::empty();
+        main_trailer.insert(
+            PdfName::from_raw_bytes(b"Size"),
+            PdfNumeric::PdfInt(linearization as i64),
+        );
+        let main_trailer = format!(
+            "trailer\n{}\nstartxref\n{first_xref_offset}\n%%EOF\n",
+            String::from_utf8_lossy(&main_trailer.to_bytes())
+        );
+        let parameters = Parameters {
+            file_len: main_xref_offset + main_xref.len() + main_trailer.len(),
+            hint_stream: [hint_offset, hint_len],
+            first_page: first_number,
+            first_page_end: main_offset + hint_len,
+            pages: pages.len(),
+            main_xref: main_xref_offset + format!("xref\n0 {linearization}\n").len() - 1,
+        };
+
+        let mut entries = vec![
+            PdfCrossRefTableEntry::in_use(header.len() as u64, 0),
+            PdfCrossRefTableEntry::in_use(catalog_offset as u64, 0),
+            PdfCrossRefTableEntry::in_use(hint_offset as u64, 0),
+        ];
+        entries.extend(
+            first_section
+                .offsets
+                .iter()
+                .map(|o| PdfCrossRefTableEntry::in_use((first_offset + hint_len + o) as u64, 0)),
+        );
+        let first_xref = PdfCrossRefTableSection::with_entries(linearization, entries);
+
+        w.write_all(&header)?;
+        w.write_all(&parameters.object(linearization))?;
+        write!(w, "{first_xref}")?;
+        w.write_all(&first_trailer(main_xref_offset))?;
+        w.write_all(&catalog_section.bytes)?;
+        w.write_all(&hint_section.bytes)?;
+        w.write_all(&first_section.bytes)?;
+        w.write_all(&main_section.bytes)?;
+        write!(w, "{main_xref}{main_trailer}")?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// The document written by [`PdfFile::write_linearized`].
+    pub fn to_linearized_bytes(&self) -> PdfResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.write_linearized(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// The ways in which the file as loaded is not correctly linearized, none if it is.
+    ///
+    /// The linearization dictionary is checked against the file and its pages, then the
+    /// objects of the first page against `/E`, the order of the pages and the offsets the page
+    /// offset hint table gives them. Changes made since loading are not considered.
+    pub fn linearization_issues(&self) -> PdfResult<Vec<LinearizationIssue>> {
+        let Some(parameters) = self.linearization_parameters()? else {
+            return Ok(vec![LinearizationIssue::NotLinearized]);
+        };
+        let mut issues = Vec::new();
+        let source = &self.body.source;
+        if parameters.file_len != source.len() {
+            issues.push(LinearizationIssue::FileLength {
+                declared: parameters.file_len,
+                actual: source.len(),
+            });
+        }
+        let pages = self.pages()?;
+        if parameters.pages != pages.len() {
+            issues.push(LinearizationIssue::PageCount {
+                declared: parameters.pages,
+                actual: pages.len(),
+            });
+        }
+        let Some(first) = pages.first() else {
+            return Ok(issues);
+        };
+        if parameters.first_page != first.reference().object() {
+            issues.push(LinearizationIssue::FirstPage {
+                declared: parameters.first_page,
+                actual: first.reference().object(),
+            });
+        }
+        let offset_of = |reference: &IndirectData| {
+            self.ref_table
+                .offset_of(reference.object(), reference.generation())
+                .map(|o| o as usize)
+        };
+        for reference in self.page_objects(first, &self.catalog_reference()?)? {
+            if offset_of(&reference).is_some_and(|o| o >= parameters.first_page_end) {
+                issues.push(LinearizationIssue::FirstPageEnd {
+                    object: reference.object(),
+                });
+            }
+        }
+        let offsets: Vec<_> = pages
+            .iter()
+            .map(|p| offset_of(p.reference()).unwrap_or(0))
+            .collect();
+        for page in 1..offsets.len() {
+            if offsets[page] < offsets[page - 1] {
+                issues.push(LinearizationIssue::PageOrder { page });
+            }
+        }
+        let entry = source.read(parameters.main_xref, 19)?;
+        if !entry.first().is_some_and(|b| b.is_ascii_whitespace())
+            || !entry.ends_with(b"0000000000 65535 f")
+        {
+            issues.push(LinearizationIssue::MainXref);
+        }
+        match self.hinted_page_offsets(&parameters, pages.len()) {
+            Ok(hinted) => {
+                for (page, (hinted, actual)) in hinted.into_iter().zip(offsets).enumerate() {
+                    if hinted != actual {
+                        issues.push(LinearizationIssue::PageOffset {
+                            page,
+                            hinted,
+                            actual,
+                        });
+                    }
+                }
+            }
+            Err(_) => issues.push(LinearizationIssue::HintStream),
+        }
+        Ok(issues)
+    }
+
+    /// Whether the file as loaded is correctly linearized, see
+    /// [`PdfFile::linearization_issues`].
+    pub fn is_linearized(&self) -> PdfResult<bool> {
+        Ok(self.linearization_issues()?.is_empty())
+    }
+
+    /// The linearization parameters, if the first object after the header is their dictionary.
+    fn linearization_parameters(&self) -> PdfResult<Option<Parameters>> {
+        let source = &self.body.source;
+        let start = source.read(0, LINEARIZATION_DICT_SEARCH_LEN)?;
+        let mut parser = Parser::new(self.body.options);
+        let Ok((_, mut rest)) = parser.parse::<super::PdfHeader>(&start, 0) else {
+            return Ok(None);
+        };
+        loop {
+            rest = strip_whitespace(rest);
+            match rest.first() {
+                Some(b'%') => rest = next_eol(rest),
+                _ => break,
+            }
+        }
+        let offset = start.len() - rest.len();
+        let Ok((object, _)) = parser.parse::<PdfObject>(rest, offset) else {
+            return Ok(None);
+        };
+        Ok(object
+            .as_dict_ref()
+            .ok()
+            .filter(|d| d.contains_key(b"Linearized"))
+            .and_then(Parameters::from_dict))
+    }
+
+    /// The page offset and shared object hint tables of the hint stream, for `pages` pages.
+    fn hint_tables(
+        &self,
+        parameters: &Parameters,
+        pages: usize,
+    ) -> PdfResult<(PageOffsetHints, SharedObjectHints)> {
+        let hint_offset = parameters.hint_stream[0];
+        let mut parser = Parser::new(self.body.options).with_resolver(self);
+        let object = self
+            .body
+            .source
+            .parse_at(hint_offset, &mut parser, |bytes, parser| {
+                let (object, rest) = parser.parse::<PdfObject>(bytes, hint_offset)?;
+                Ok((object, rest.len()))
+            })?;
+        let stream = object.as_stream()?;
+        let shared_table_offset = stream.info().get_int(b"S")?.ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a hint stream /S")
+        })?;
+        let data = stream.decoded_data()?;
+        let shared_data = usize::try_from(shared_table_offset)
+            .ok()
+            .and_then(|offset| data.get(offset..))
+            .ok_or_else(|| {
+                PdfError::with_kind(PdfErrorKind::InvalidData)
+                    .expecting("a shared object hint table in the hint stream")
+            })?;
+        let shared = SharedObjectHints::read(&mut BitReader {
+            bytes: shared_data,
+            position: 0,
+        })?;
+        let page_offsets = PageOffsetHints::read(
+            &mut BitReader {
+                bytes: &data,
+                position: 0,
+            },
+            pages,
+            shared.group_lens.len(),
+        )?;
+        Ok((page_offsets, shared))
+    }
+
+    /// The offsets of the page objects given by the page offset hint table.
+    fn hinted_page_offsets(&self, parameters: &Parameters, pages: usize) -> PdfResult<Vec<usize>> {
+        let [hint_offset, hint_len] = parameters.hint_stream;
+        let (page_offsets, _) = self.hint_tables(parameters, pages)?;
+        // Offsets after the hint stream are given without it
+        let adjust = |offset: usize| {
+            if offset >= hint_offset {
+                offset + hint_len
+            } else {
+                offset
+            }
+        };
+        let mut offsets = Vec::with_capacity(pages);
+        let mut start = page_offsets.first_page_offset;
+        for page in &page_offsets.pages {
+            offsets.push(adjust(start));
+            start += page.len;
+        }
+        Ok(offsets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{IndirectData, LinearizationIssue, PdfFile, Resolve, pdf_file::test_file};
+
+    use super::PADDED_WIDTH;
+
+    /// A document of four pages: the first two share a content stream, the last two another,
+    /// and all of them the font of the page tree.
+    fn shared_objects() -> Vec<u8> {
+        test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /Outlines 12 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R 6 0 R] /Count 4 /Resources << /Font << /F1 7 0 R >> >> >>",
+                "<< /Type /Page /Parent 2 0 R /Contents [8 0 R 9 0 R] >>",
+                "<< /Type /Page /Parent 2 0 R /Contents 9 0 R /Annots [13 0 R] >>",
+                "<< /Type /Page /Parent 2 0 R /Contents [10 0 R 11 0 R] >>",
+                "<< /Type /Page /Parent 2 0 R /Contents 10 0 R >>",
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+                "<< /Length 5 >> stream\nfirst\nendstream",
+                "<< /Length 4 >> stream\nboth\nendstream",
+                "<< /Length 6 >> stream\nshared\nendstream",
+                "<< /Length 5 >> stream\nthird\nendstream",
+                "<< /Count 0 >>",
+                "<< /Subtype /Link /Rect [0 0 5 5] /Dest [3 0 R /Fit] >>",
+            ],
+            "/Root 1 0 R",
+        )
+    }
+
+    /// The offsets of the objects of `file` with their references, in the order of the file.
+    fn object_offsets(file: &PdfFile) -> Vec<(usize, IndirectData)> {
+        let mut offsets: Vec<_> = file
+            .objects()
+            .into_iter()
+            .map(|r| {
+                let offset = file.ref_table.offset_of(r.object(), r.generation());
+                (offset.unwrap() as usize, r)
+            })
+            .collect();
+        offsets.sort_by_key(|(offset, _)| *offset);
+        offsets
+    }
+
+    /// `bytes` with the value after `key` in the linearization dictionary set to `value`.
+    fn patch(bytes: &[u8], key: &str, value: usize) -> Vec<u8> {
+        let at = bytes
+            .windows(key.len())
+            .position(|w| w == key.as_bytes())
+            .unwrap()
+            + key.len();
+        let mut patched = bytes.to_vec();
+        let value = format!("{value:<w$}", w = PADDED_WIDTH);
+        patched[at..at + PADDED_WIDTH].copy_from_slice(value.as_bytes());
+        patched
+    }
+
+    #[test]
+    fn hint_tables() {
+        let bytes = shared_objects();
+        let file = PdfFile::load(&bytes).unwrap();
+        let written = file.to_linearized_bytes().unwrap();
+        let linearized = PdfFile::load(&written).unwrap();
+        assert_eq!(linearized.linearization_issues(), Ok(Vec::new()));
+        let parameters = linearized.linearization_parameters().unwrap().unwrap();
+        let pages = linearized.pages().unwrap();
+        let (page_offsets, shared) = linearized.hint_tables(&parameters, pages.len()).unwrap();
+
+        let offsets = object_offsets(&linearized);
+        let main_xref = written.windows(6).rposition(|w| w == b"\nxref\n").unwrap() + 1;
+        // Where the object at `offset` ends: at the next object or the main cross-references
+        let end_of = |offset: usize| {
+            offsets
+                .iter()
+                .map(|(o, _)| *o)
+                .find(|o| *o > offset)
+                .unwrap_or(main_xref)
+        };
+        // The hint tables give offsets as if the hint stream was not there
+        let [hint_offset, hint_len] = parameters.hint_stream;
+        let actual = |hinted: usize| {
+            if hinted >= hint_offset {
+                hinted + hint_len
+            } else {
+                hinted
+            }
+        };
+        let within = |start: usize, end: usize| -> Vec<IndirectData> {
+            offsets
+                .iter()
+                .filter(|(o, _)| (start..end).contains(o))
+                .map(|(_, r)| r.clone())
+                .collect()
+        };
+
+        // Each page is its objects, from its page object to where the next part starts
+        let mut start = page_offsets.first_page_offset;
+        let mut own = Vec::new();
+        for (page, hint) in pages.iter().zip(&page_offsets.pages) {
+            let objects = within(actual(start), actual(start + hint.len));
+            assert_eq!(objects.len(), hint.objects);
+            assert_eq!(&objects[0], page.reference());
+            let end = actual(start + hint.len);
+            assert!(end == main_xref || offsets.iter().any(|(o, _)| *o == end));
+            own.push(objects);
+            start += hint.len;
+        }
+        assert_eq!(
+            actual(page_offsets.first_page_offset + page_offsets.pages[0].len),
+            parameters.first_page_end
+        );
+
+        // The groups are the objects of the first page, then those of the shared section
+        let first_section = within(
+            actual(page_offsets.first_page_offset),
+            parameters.first_page_end,
+        );
+        assert_eq!(shared.first_page_groups, first_section.len());
+        let shared_start = offsets
+            .iter()
+            .position(|(o, _)| *o == actual(shared.first_shared_offset))
+            .unwrap();
+        assert_eq!(offsets[shared_start].1.object(), shared.first_shared);
+        let shared_count = shared.group_lens.len() - shared.first_page_groups;
+        assert!(shared_count > 0);
+        let groups: Vec<_> = offsets
+            .iter()
+            .filter(|(_, r)| first_section.contains(r))
+            .chain(&offsets[shared_start..shared_start + shared_count])
+            .collect();
+        for ((offset, _), len) in groups.iter().zip(&shared.group_lens) {
+            assert_eq!(end_of(*offset) - offset, *len);
+        }
+
+        // The other pages refer to the groups of the objects they use outside their own part
+        assert!(page_offsets.pages[0].shared.is_empty());
+        let catalog = linearized.catalog_reference().unwrap();
+        for (i, page) in pages.iter().enumerate().skip(1) {
+            let used = linearized.page_objects(page, &catalog).unwrap();
+            let expected: HashSet<_> = used.into_iter().filter(|r| !own[i].contains(r)).collect();
+            assert_eq!(expected.len(), 2);
+            let hinted: HashSet<_> = page_offsets.pages[i]
+                .shared
+                .iter()
+                .map(|id| groups[*id].1.clone())
+                .collect();
+            assert_eq!(hinted, expected);
+        }
+    }
+
+    #[test]
+    fn tampered() {
+        let bytes = shared_objects();
+        let file = PdfFile::load(&bytes).unwrap();
+        let written = file.to_linearized_bytes().unwrap();
+        let linearized = PdfFile::load(&written).unwrap();
+        let parameters = linearized.linearization_parameters().unwrap().unwrap();
+        let pages = linearized.pages().unwrap();
+        let catalog = linearized.catalog_reference().unwrap();
+        let issues = |bytes: &[u8]| {
+            PdfFile::load(bytes)
+                .unwrap()
+                .linearization_issues()
+                .unwrap()
+        };
+
+        // The first page section ends right after the page object
+        let page_offset = linearized
+            .ref_table
+            .offset_of(pages[0].reference().object(), 0)
+            .unwrap() as usize;
+        let first_page = linearized.page_objects(&pages[0], &catalog).unwrap();
+        let expected: Vec<_> = first_page[1..]
+            .iter()
+            .map(|r| LinearizationIssue::FirstPageEnd { object: r.object() })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(issues(&patch(&written, "/E ", page_offset + 1)), expected);
+
+        assert_eq!(
+            issues(&patch(&written, "/T ", parameters.main_xref + 1)),
+            [LinearizationIssue::MainXref]
+        );
+
+        // The hint stream said to be where the catalog is
+        let catalog_offset = linearized
+            .ref_table
+            .offset_of(catalog.object(), catalog.generation())
+            .unwrap() as usize;
+        assert_eq!(
+            issues(&patch(&written, "/H [", catalog_offset)),
+            [LinearizationIssue::HintStream]
+        );
+
+        // The last two pages swapped in the page tree, which the hints do not follow
+        let kids: Vec<_> = pages
+            .iter()
+            .map(|p| format!("{} 0 R", p.reference().object()))
+            .collect();
+        assert_eq!(kids[2].len(), kids[3].len());
+        let (from, to) = (
+            format!("/Kids [{}]", kids.join(" ")),
+            format!("/Kids [{} {} {} {}]", kids[0], kids[1], kids[3], kids[2]),
+        );
+        let at = written
+            .windows(from.len())
+            .position(|w| w == from.as_bytes())
+            .unwrap();
+        let mut swapped = written.clone();
+        swapped[at..at + to.len()].copy_from_slice(to.as_bytes());
+        let offset = |page: &crate::Page| {
+            linearized
+                .ref_table
+                .offset_of(page.reference().object(), 0)
+                .unwrap() as usize
+        };
+        let (third, fourth) = (offset(&pages[2]), offset(&pages[3]));
+        assert_eq!(
+            issues(&swapped),
+            [
+                LinearizationIssue::PageOrder { page: 3 },
+                LinearizationIssue::PageOffset {
+                    page: 2,
+                    hinted: third,
+                    actual: fourth,
+                },
+                LinearizationIssue::PageOffset {
+                    page: 3,
+                    hinted: fourth,
+                    actual: third,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn linearize() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R /Outlines 9 0 R >>",
+                "<< /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 /Resources << /Font << /F1 6 0 R >> >> >>",
+                "<< /Type /Page /Parent 2 0 R /Contents 7 0 R >>",
+                "<< /Type /Page /Parent 2 0 R /Contents 8 0 R /Annots [10 0 R] >>",
+                "<< /Type /Page /Parent 2 0 R /Contents 8 0 R >>",
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+                "<< /Length 5 >> stream\nfirst\nendstream",
+                "<< /Length 6 >> stream\nshared\nendstream",
+                "<< /Count 0 >>",
+                "<< /Subtype /Link /Rect [0 0 5 5] /Dest [3 0 R /Fit] >>",
+            ],
+            "/Root 1 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        assert_eq!(
+            file.linearization_issues(),
+            Ok(vec![LinearizationIssue::NotLinearized])
+        );
+        let written = file.to_linearized_bytes().unwrap();
+        let linearized = PdfFile::load(&written).unwrap();
+        assert_eq!(linearized.linearization_issues(), Ok(Vec::new()));
+        assert!(linearized.is_linearized().unwrap());
+
+        // Other pages and the objects used by none come first in the numbering
+        let pages = linearized.pages().unwrap();
+        let numbers: Vec<_> = pages.iter().map(|p| p.reference().object()).collect();
+        assert_eq!(numbers, [10, 1, 3]);
+        assert_eq!(linearized.objects().len(), 12);
+        for (page, original) in pages.iter().zip(file.pages().unwrap()) {
+            let contents = |file: &PdfFile, page: &crate::Page| {
+                let contents = page.dict().get(b"Contents").unwrap();
+                file.deref(contents)
+                    .unwrap()
+                    .as_stream()
+                    .unwrap()
+                    .data()
+                    .to_vec()
+            };
+            assert_eq!(contents(&linearized, page), contents(&file, &original));
+        }
+        let annots = pages[1].dict().get_array(b"Annots").unwrap().unwrap();
+        let link = linearized.deref(&annots[0]).unwrap().as_dict().unwrap();
+        assert_eq!(
+            link.get_array(b"Dest").unwrap().unwrap()[0].as_reference(),
+            Some(pages[0].reference())
+        );
+
+        let mut changed = written.clone();
+        changed.extend_from_slice(b"% appended\n");
+        let changed = PdfFile::load(&changed).unwrap();
+        assert_eq!(
+            changed.linearization_issues(),
+            Ok(vec![LinearizationIssue::FileLength {
+                declared: written.len(),
+                actual: written.len() + 11,
+            }])
+        );
+    }
+}
@@ -1,23 +1,625 @@
-use pdf_cross_ref_table::PdfCrossRefTable;
-use pdf_header::PdfHeader;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashSet},
+    io::{Read, Seek},
+    str,
+};
 
-use crate::pdf_error::{PdfError, PdfErrorKind};
+pub use linearized::LinearizationIssue;
+use object_cache::ObjectCache;
+pub use pdf_cross_ref_table::{ObjectLocation, PdfCrossRefTable, XrefResolver};
+use pdf_cross_ref_table::{
+    PdfCrossRefTableSection, object_stream, parse_compressed_object, parse_object_at,
+};
+pub use pdf_header::{PdfHeader, PdfVersion};
+use source::Source;
+pub use writer::WriteOptions;
 
+use crate::{
+    IndirectData, ParseOptions, ParseWarning, Parser, PdfArray, PdfDict, PdfName, PdfNull,
+    PdfNumeric, PdfObject, PdfString, Resolve,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+    strip_whitespace,
+};
+
+mod linearized;
+mod object_cache;
 mod pdf_cross_ref_table;
 mod pdf_header;
+mod source;
+mod writer;
 
 mod constants {
     pub const CROSS_REF_SECTION_KEYWORD: &str = "xref";
+    pub const TRAILER_KEYWORD: &[u8] = b"trailer";
+    pub const STARTXREF_KEYWORD: &[u8] = b"startxref";
+    /// How far from the end of the file `startxref` is looked for.
+    pub const STARTXREF_SEARCH_LEN: usize = 1024;
+    pub const DEFAULT_CACHE_CAPACITY: usize = 1024;
 }
-pub struct PdfFile {
+
+/// A PDF document whose objects are parsed when first resolved.
+pub struct PdfFile<'a> {
     header: PdfHeader,
-    body: PdfBody,
+    body: PdfBody<'a>,
     ref_table: PdfCrossRefTable,
     trailer: PdfTrailer,
+    /// Objects added, replaced or deleted since loading, by object number, `None` if deleted.
+    updates: BTreeMap<usize, Option<PdfObject>>,
 }
 
-pub struct PdfBody {
+pub struct PdfBody<'a> {
     // "Sequence of indirect objects" 7.5.3
     // If Version >= 1.5, also contains object streams
+    source: Source<'a>,
+    options: ParseOptions,
+    cache: RefCell<ObjectCache>,
+    warnings: RefCell<Vec<ParseWarning>>,
+    depth: Cell<usize>,
+}
+
+pub struct PdfTrailer {
+    dict: PdfDict,
+    startxref: usize,
+}
+
+impl PdfFile<'static> {
+    /// A new document with an empty page tree, to be written with `header`.
+    pub fn new(header: PdfHeader) -> Self {
+        let mut file = Self {
+            header,
+            body: PdfBody {
+                source: Source::Bytes(&[]),
+                options: ParseOptions::default(),
+                cache: RefCell::new(ObjectCache::with_capacity(0)),
+                warnings: RefCell::new(Vec::new()),
+                depth: Cell::new(0),
+            },
+            ref_table: PdfCrossRefTable::from_sections(Vec::new()),
+            trailer: PdfTrailer {
+                dict: PdfDict::empty(),
+                startxref: 0,
+            },
+            updates: BTreeMap::new(),
+        };
+        let catalog = file.add_object(PdfNull {});
+        let mut pages = PdfDict::empty();
+        pages.insert(
+            PdfName::from_raw_bytes(b"Type"),
+            PdfName::from_raw_bytes(b"Pages"),
+        );
+        pages.insert(PdfName::from_raw_bytes(b"Kids"), PdfArray::new());
+        pages.insert(PdfName::from_raw_bytes(b"Count"), PdfNumeric::PdfInt(0));
+        let pages = file.add_object(pages);
+        let mut dict = PdfDict::empty();
+        dict.insert(
+            PdfName::from_raw_bytes(b"Type"),
+            PdfName::from_raw_bytes(b"Catalog"),
+        );
+        dict.insert(PdfName::from_raw_bytes(b"Pages"), pages);
+        file.set_object(&catalog, dict);
+        file.trailer
+            .dict
+            .insert(PdfName::from_raw_bytes(b"Root"), catalog);
+        file
+    }
+}
+
+impl<'a> PdfFile<'a> {
+    /// Opens the document in `bytes`, reading only its header, cross-reference table and trailer.
+    pub fn load(bytes: &'a [u8]) -> PdfResult<Self> {
+        Self::load_with(bytes, ParseOptions::default())
+    }
+
+    pub fn load_with(bytes: &'a [u8], options: ParseOptions) -> PdfResult<Self> {
+        Self::open(Source::Bytes(bytes), options)
+    }
+
+    /// Opens the document read by `reader`, seeking to the parts needed instead of reading it all.
+    pub fn from_reader(reader: impl Read + Seek + 'a) -> PdfResult<Self> {
+        Self::from_reader_with(reader, ParseOptions::default())
+    }
+
+    pub fn from_reader_with(
+        reader: impl Read + Seek + 'a,
+        options: ParseOptions,
+    ) -> PdfResult<Self> {
+        Self::open(Source::from_reader(reader)?, options)
+    }
+
+    fn open(source: Source<'a>, options: ParseOptions) -> PdfResult<Self> {
+        let mut parser = Parser::new(options);
+        let header = source.parse_at(0, &mut parser, |bytes, parser| {
+            let (header, rest) = parser.parse::<PdfHeader>(bytes, 0)?;
+            Ok((header, rest.len()))
+        })?;
+        let startxref = find_startxref(&source)?;
+        let (ref_table, dict) = load_cross_references(&source, startxref, &mut parser)?;
+
+        Ok(Self {
+            header,
+            body: PdfBody {
+                source,
+                options,
+                cache: RefCell::new(ObjectCache::with_capacity(
+                    constants::DEFAULT_CACHE_CAPACITY,
+                )),
+                warnings: RefCell::new(parser.take_warnings()),
+                depth: Cell::new(0),
+            },
+            ref_table,
+            trailer: PdfTrailer { dict, startxref },
+            updates: BTreeMap::new(),
+        })
+    }
+
+    pub fn header(&self) -> &PdfHeader {
+        &self.header
+    }
+    /// `text` as a text string, in UTF-8 only if the version of the document allows it.
+    pub(crate) fn text_string(&self, text: &str) -> PdfString {
+        PdfString::from_text_in(text, self.header.version())
+    }
+    pub fn trailer(&self) -> &PdfDict {
+        &self.trailer.dict
+    }
+    pub fn trailer_mut(&mut self) -> &mut PdfDict {
+        &mut self.trailer.dict
+    }
+    /// Offset of the last cross-reference section, as given by `startxref`.
+    pub fn startxref(&self) -> usize {
+        self.trailer.startxref
+    }
+    pub fn cross_ref_table(&self) -> &PdfCrossRefTable {
+        &self.ref_table
+    }
+
+    /// The document catalog, from the trailer's `/Root`.
+    pub fn catalog(&self) -> PdfResult<PdfDict> {
+        self.deref(self.trailer.dict.get_or_null(b"Root"))?
+            .as_dict()
+    }
+
+    /// Reference to the document catalog, an error if `/Root` is not a reference.
+    pub(crate) fn catalog_reference(&self) -> PdfResult<IndirectData> {
+        self.trailer
+            .dict
+            .get_or_null(b"Root")
+            .as_reference()
+            .cloned()
+            .ok_or_else(|| {
+                PdfError::with_kind(PdfErrorKind::WrongType).expecting("a reference to the catalog")
+            })
+    }
+
+    /// The indirect objects of the document, with the changes made since loading, by increasing
+    /// object number.
+    pub fn objects(&self) -> Vec<IndirectData> {
+        let mut objects: BTreeMap<_, _> = self
+            .ref_table
+            .in_use_objects()
+            .into_iter()
+            .map(|r| (r.object(), r))
+            .collect();
+        for (object, update) in &self.updates {
+            match update.as_ref().and_then(PdfObject::indirect) {
+                Some(reference) => objects.insert(*object, reference.clone()),
+                None => objects.remove(object),
+            };
+        }
+        objects.into_values().collect()
+    }
+
+    /// The first object number not used by the document, nor listed by `/Size`.
+    pub fn next_object_number(&self) -> usize {
+        let size = self
+            .trailer
+            .dict
+            .get_int(b"Size")
+            .ok()
+            .flatten()
+            .and_then(|s| usize::try_from(s).ok())
+            .unwrap_or(0);
+        let last = self.objects().last().map_or(0, |r| r.object() + 1);
+        let updated = self.updates.last_key_value().map_or(0, |(o, _)| o + 1);
+        size.max(last).max(updated).max(1)
+    }
+
+    /// Adds `object` as a new indirect object, returning its reference.
+    pub fn add_object(&mut self, object: impl Into<PdfObject>) -> IndirectData {
+        let reference = IndirectData::new(self.next_object_number(), 0);
+        self.set_object(&reference, object);
+        reference
+    }
+
+    /// Replaces the indirect object `reference`, or adds it if there is none.
+    pub fn set_object(&mut self, reference: &IndirectData, object: impl Into<PdfObject>) {
+        let object = object.into().into_indirect(reference.clone());
+        self.updates.insert(reference.object(), Some(object));
+    }
+
+    /// Removes the indirect object `reference`, references to it then resolving to nothing.
+    pub fn delete_object(&mut self, reference: &IndirectData) {
+        self.updates.insert(reference.object(), None);
+    }
+
+    /// Sets how many parsed objects are kept in memory.
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.body.cache.get_mut().set_capacity(capacity);
+    }
+    /// Number of parsed objects currently kept in memory.
+    pub fn cached_objects(&self) -> usize {
+        self.body.cache.borrow().len()
+    }
+
+    /// Warnings raised so far, while loading and resolving objects.
+    pub fn warnings(&self) -> Vec<ParseWarning> {
+        self.body.warnings.borrow().clone()
+    }
+}
+
+impl Resolve for PdfFile<'_> {
+    fn resolve(&self, reference: &IndirectData) -> PdfResult<PdfObject> {
+        if let Some(update) = self.updates.get(&reference.object()) {
+            return update
+                .as_ref()
+                .filter(|o| o.indirect() == Some(reference))
+                .cloned()
+                .ok_or_else(|| {
+                    PdfError::with_kind(PdfErrorKind::UnresolvedReference(
+                        reference.object(),
+                        reference.generation(),
+                    ))
+                });
+        }
+        if let Some(object) = self.body.cache.borrow_mut().get(reference) {
+            return Ok(object.clone());
+        }
+        let source = &self.body.source;
+        let location = self
+            .ref_table
+            .checked_location_of(reference, source.len())?;
+        let mut parser = Parser::new(self.body.options).with_resolver(self);
+        let object = match location {
+            ObjectLocation::Offset(offset) => {
+                let offset = offset as usize;
+                source.parse_at(offset, &mut parser, |bytes, parser| {
+                    let (object, rest) =
+                        parse_object_at(bytes, offset, reference, parser, &self.body.depth)?;
+                    Ok((object, rest.len()))
+                })
+            }
+            ObjectLocation::Compressed { stream, index } => {
+                // Object streams are never compressed themselves, which also ends cycles
+                match self.ref_table.location_of(stream, 0) {
+                    Some(ObjectLocation::Offset(_)) => {
+                        object_stream(self, stream).and_then(|stream| {
+                            parse_compressed_object(&stream, index, reference, &mut parser)
+                        })
+                    }
+                    _ => Err(PdfError::with_kind(PdfErrorKind::UnresolvedReference(
+                        reference.object(),
+                        reference.generation(),
+                    ))),
+                }
+            }
+        };
+        self.body
+            .warnings
+            .borrow_mut()
+            .extend(parser.take_warnings());
+
+        let object = object?;
+        self.body
+            .cache
+            .borrow_mut()
+            .insert(reference, object.clone());
+        Ok(object)
+    }
+}
+
+/// Reads the offset following the last `startxref` keyword.
+fn find_startxref(source: &Source) -> PdfResult<usize> {
+    let tail_start = source.len().saturating_sub(constants::STARTXREF_SEARCH_LEN);
+    let tail = source.read(tail_start, constants::STARTXREF_SEARCH_LEN)?;
+    let keyword = constants::STARTXREF_KEYWORD;
+    let position = tail
+        .windows(keyword.len())
+        .rposition(|w| w == keyword)
+        .ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::Parse)
+                .at_offset(tail_start, &tail)
+                .expecting("`startxref`")
+        })?;
+    let number = strip_whitespace(&tail[position + keyword.len()..]);
+    let end = number
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .unwrap_or(number.len());
+    let number_offset = tail_start + tail.len() - number.len();
+    str::from_utf8(&number[..end])
+        .map_err(PdfError::from)
+        .and_then(|n| n.parse().map_err(PdfError::from))
+        .map_err(|e| {
+            e.at_offset(number_offset, number)
+                .expecting("cross-reference offset")
+        })
+}
+
+/// Keys of a cross-reference stream's dictionary that describe the stream rather than the
+/// document, left out of the trailer.
+const XREF_STREAM_KEYS: [&[u8]; 9] = [
+    b"Type",
+    b"W",
+    b"Index",
+    b"Length",
+    b"Filter",
+    b"DecodeParms",
+    b"F",
+    b"FFilter",
+    b"FDecodeParms",
+];
+
+/// Reads the cross-reference section at `startxref` and the ones it links to with `/Prev`.
+///
+/// Sections are tables or cross-reference streams (7.5.8). The stream of a hybrid file's
+/// `/XRefStm` is read with the table that gives it, and takes precedence over it: the table
+/// lists the objects of the object streams as free (7.5.8.4).
+///
+/// Returns the table, oldest section first, and the most recent trailer dictionary.
+fn load_cross_references(
+    source: &Source,
+    startxref: usize,
+    parser: &mut Parser,
+) -> PdfResult<(PdfCrossRefTable, PdfDict)> {
+    let mut sections = Vec::new();
+    let mut trailer = None;
+    let mut next = Some(startxref);
+    let mut visited = HashSet::new();
+    while let Some(offset) = next.take() {
+        if !visited.insert(offset) {
+            break;
+        }
+        let (section, mut dict) = load_section_at(source, offset, parser)?;
+        let number = |key: &[u8]| -> PdfResult<Option<usize>> {
+            match dict.get(key) {
+                Some(value) => value
+                    .as_numeric_ref()
+                    .and_then(PdfNumeric::to_usize)
+                    .map(Some)
+                    .map_err(|e| e.expecting("a non-negative offset")),
+                None => Ok(None),
+            }
+        };
+        next = number(b"Prev")?;
+        if let Some(stream_offset) = number(b"XRefStm")? {
+            let (stream_section, _) = load_section_at(source, stream_offset, parser)?;
+            sections.push(stream_section);
+        }
+        sections.push(section);
+        for key in XREF_STREAM_KEYS {
+            dict.remove(key);
+        }
+        trailer.get_or_insert(dict);
+    }
+    sections.reverse();
+    let trailer = trailer.expect("at least one section was loaded");
+    Ok((PdfCrossRefTable::from_sections(sections), trailer))
+}
+
+/// Reads the cross-reference table or stream at `offset`, with its trailer or stream dictionary.
+fn load_section_at(
+    source: &Source,
+    offset: usize,
+    parser: &mut Parser,
+) -> PdfResult<(PdfCrossRefTableSection, PdfDict)> {
+    if offset > source.len() {
+        return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+            .at_offset(source.len(), &[])
+            .expecting("a cross-reference section"));
+    }
+    source.parse_at(offset, parser, |bytes, parser| {
+        let keyword = constants::CROSS_REF_SECTION_KEYWORD.as_bytes();
+        if strip_whitespace(bytes).starts_with(keyword) {
+            let (section, dict, rest) = load_cross_reference_section(bytes, offset, parser)?;
+            return Ok(((section, dict), rest.len()));
+        }
+        let (object, rest) = parser.parse::<PdfObject>(bytes, offset)?;
+        let stream = object
+            .as_stream()
+            .ok()
+            .filter(|s| {
+                s.info()
+                    .get_name(b"Type")
+                    .ok()
+                    .flatten()
+                    .map(PdfName::bytes)
+                    == Some(b"XRef")
+            })
+            .ok_or_else(|| {
+                PdfError::with_kind(PdfErrorKind::Parse)
+                    .at_offset(offset, bytes)
+                    .expecting("`xref` or a cross-reference stream")
+            })?;
+        let section = PdfCrossRefTableSection::from_stream(&stream)
+            .map_err(|e| e.at_offset(offset, bytes))?;
+        Ok(((section, stream.info().clone()), rest.len()))
+    })
+}
+
+/// Parses the section and trailer in `section_bytes`, which start `offset` bytes into the file.
+fn load_cross_reference_section<'b>(
+    section_bytes: &'b [u8],
+    offset: usize,
+    parser: &mut Parser,
+) -> PdfResult<(PdfCrossRefTableSection, PdfDict, &'b [u8])> {
+    let keyword = constants::TRAILER_KEYWORD;
+    let trailer = section_bytes
+        .windows(keyword.len())
+        .position(|w| w == keyword)
+        .ok_or_else(|| {
+            PdfError::with_kind(PdfErrorKind::Parse)
+                .at_offset(offset, section_bytes)
+                .expecting("`trailer`")
+        })?;
+    let section = str::from_utf8(&section_bytes[..trailer])
+        .map_err(|e| PdfError::from(e).at_offset(offset, section_bytes))?
+        .trim_end_matches(['\r', '\n']);
+    let section = PdfCrossRefTableSection::parse_with(section, offset, parser)
+        .map_err(|e| e.shifted(offset))?;
+    let dict_bytes = strip_whitespace(&section_bytes[trailer + keyword.len()..]);
+    let dict_offset = offset + section_bytes.len() - dict_bytes.len();
+    let (dict, rest) = parser.parse::<PdfDict>(dict_bytes, dict_offset)?;
+    Ok((section, dict, rest))
+}
+
+/// Builds small files for the tests, objects being numbered from 1.
+#[cfg(test)]
+pub(crate) mod test_file {
+    pub fn build(objects: &[&str], trailer: &str) -> Vec<u8> {
+        let mut file = String::from("%PDF-1.7\n");
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for (i, object) in objects.iter().enumerate() {
+            xref.push_str(&format!("{:010} 00000 n \n", file.len()));
+            file.push_str(&format!("{} 0 obj\n{object}\nendobj\n", i + 1));
+        }
+        let startxref = file.len();
+        file.push_str(&xref);
+        file.push_str(&format!(
+            "trailer\n<< /Size {} {trailer} >>\nstartxref\n{startxref}\n%%EOF\n",
+            objects.len() + 1
+        ));
+        file.into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IndirectData, ParseOptions, ParseWarningKind, PdfErrorKind, PdfFile, Resolve};
+
+    use super::test_file;
+
+    #[test]
+    fn lazy_loading() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [] /Count 0 >>",
+                "<< /Length 4 0 R >> stream\nabc\nendstream",
+                "3",
+                "(broken",
+            ],
+            "/Root 1 0 R",
+        );
+        let mut file = PdfFile::load(&bytes).unwrap();
+        assert_eq!(file.cached_objects(), 0);
+
+        let catalog = file.catalog().unwrap();
+        let pages = file.deref(catalog.get_or_null(b"Pages")).unwrap();
+        assert!(pages.as_dict().is_ok());
+        assert_eq!(file.cached_objects(), 2);
+
+        let stream = file.resolve(&IndirectData::new(3, 0)).unwrap();
+        assert_eq!(stream.as_stream().unwrap().len(), 3);
+        assert_eq!(file.cached_objects(), 4);
+
+        let err = file.resolve(&IndirectData::new(5, 0)).unwrap_err();
+        assert_eq!(err.object(), Some((5, 0)));
+        assert_eq!(
+            file.resolve(&IndirectData::new(6, 0)).unwrap_err().kind(),
+            &PdfErrorKind::UnresolvedReference(6, 0)
+        );
+
+        file.set_cache_capacity(1);
+        assert_eq!(file.cached_objects(), 1);
+        file.resolve(&IndirectData::new(1, 0)).unwrap();
+        file.resolve(&IndirectData::new(2, 0)).unwrap();
+        assert_eq!(file.cached_objects(), 1);
+    }
+
+    #[test]
+    fn incremental_update() {
+        let mut bytes = test_file::build(&["<< /Version 1 >>", "(old)"], "/Root 1 0 R");
+        let startxref = PdfFile::load(&bytes).unwrap().startxref();
+        let update_offset = bytes.len();
+        bytes.extend_from_slice(b"2 0 obj\n(new)\nendobj\n");
+        let xref_offset = bytes.len();
+        bytes.extend_from_slice(
+            format!(
+                "xref\n2 1\n{update_offset:010} 00000 n \ntrailer\n<< /Size 3 /Root 1 0 R /Prev {startxref} >>\nstartxref\n{xref_offset}\n%%EOF\n"
+            )
+            .as_bytes(),
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        let string = file.resolve(&IndirectData::new(2, 0)).unwrap();
+        assert_eq!(
+            string.as_string().unwrap(),
+            crate::PdfString::from_raw_bytes(b"new")
+        );
+        assert!(file.resolve(&IndirectData::new(1, 0)).is_ok());
+        assert_eq!(file.startxref(), xref_offset);
+    }
+
+    #[test]
+    fn hybrid_cross_references() {
+        // Object 4 is only in the object stream 3, which the table lists as free
+        let mut bytes = b"%PDF-1.5\n".to_vec();
+        let mut offsets = Vec::new();
+        for object in [
+            "<< /Type /Catalog /Packed 4 0 R >>",
+            "(plain)",
+            "<< /Type /ObjStm /N 1 /First 4 /Length 12 >> stream\n4 0 (packed)\nendstream",
+            "<< /Type /XRef /Size 6 /Index [4 1] /W [1 1 1] /Filter /ASCIIHexDecode /Length 7 >> stream\n020300>\nendstream",
+        ] {
+            offsets.push(bytes.len());
+            let number = offsets.len() + usize::from(offsets.len() == 4);
+            bytes.extend(format!("{number} 0 obj\n{object}\nendobj\n").into_bytes());
+        }
+        let startxref = bytes.len();
+        let mut table = String::from("xref\n0 5\n0000000000 65535 f \n");
+        for offset in &offsets[..3] {
+            table.push_str(&format!("{offset:010} 00000 n \n"));
+        }
+        table.push_str("0000000000 65535 f \n");
+        bytes.extend(
+            format!(
+                "{table}trailer\n<< /Size 6 /Root 1 0 R /XRefStm {} >>\nstartxref\n{startxref}\n%%EOF\n",
+                offsets[3]
+            )
+            .into_bytes(),
+        );
+
+        let file = PdfFile::load(&bytes).unwrap();
+        let packed = file.resolve(&IndirectData::new(4, 0)).unwrap();
+        assert_eq!(
+            packed.as_string_ref().unwrap(),
+            &crate::PdfString::from_raw_bytes(b"packed")
+        );
+        assert_eq!(packed.indirect(), Some(&IndirectData::new(4, 0)));
+        assert!(file.resolve(&IndirectData::new(2, 0)).is_ok());
+        let numbers: Vec<_> = file.objects().iter().map(IndirectData::object).collect();
+        assert_eq!(numbers, [1, 2, 3, 4]);
+        assert_eq!(
+            file.resolve(&IndirectData::new(4, 1)).unwrap_err().kind(),
+            &PdfErrorKind::UnresolvedReference(4, 1)
+        );
+    }
+
+    #[test]
+    fn lenient_warnings() {
+        let bytes = test_file::build(&["<< /Length 10 >> stream\nabc\nendstream"], "");
+        let strict = PdfFile::load(&bytes).unwrap();
+        assert!(strict.resolve(&IndirectData::new(1, 0)).is_err());
+
+        let file = PdfFile::load_with(&bytes, ParseOptions::lenient()).unwrap();
+        assert!(file.resolve(&IndirectData::new(1, 0)).is_ok());
+        let warnings = file.warnings();
+        assert_eq!(
+            warnings[0].kind,
+            ParseWarningKind::WrongStreamLength {
+                declared: 10,
+                actual: 3
+            }
+        );
+    }
 }
-pub struct PdfTrailer {}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use crate::{IndirectData, PdfObject};
+
+/// Bounded cache of parsed objects, evicting the least recently used one when full.
+#[derive(Debug, Clone)]
+pub(crate) struct ObjectCache {
+    capacity: usize,
+    objects: HashMap<(usize, usize), (PdfObject, u64)>,
+    // Incremented on every access, the entry with the smallest stamp is the least recently used
+    clock: u64,
+}
+
+impl ObjectCache {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            objects: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    pub fn get(&mut self, reference: &IndirectData) -> Option<&PdfObject> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.objects
+            .get_mut(&Self::key(reference))
+            .map(|(object, stamp)| {
+                *stamp = clock;
+                &*object
+            })
+    }
+
+    pub fn insert(&mut self, reference: &IndirectData, object: PdfObject) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = Self::key(reference);
+        if !self.objects.contains_key(&key) && self.objects.len() >= self.capacity {
+            self.evict();
+        }
+        self.clock += 1;
+        self.objects.insert(key, (object, self.clock));
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.objects.len() > capacity {
+            self.evict();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    fn evict(&mut self) {
+        let oldest = self
+            .objects
+            .iter()
+            .min_by_key(|(_, (_, stamp))| *stamp)
+            .map(|(key, _)| *key);
+        if let Some(key) = oldest {
+            self.objects.remove(&key);
+        }
+    }
+
+    fn key(reference: &IndirectData) -> (usize, usize) {
+        (reference.object(), reference.generation())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IndirectData, PdfNumeric};
+
+    use super::ObjectCache;
+
+    #[test]
+    fn least_recently_used() {
+        let mut cache = ObjectCache::with_capacity(2);
+        let refs = [1, 2, 3].map(|o| IndirectData::new(o, 0));
+        cache.insert(&refs[0], PdfNumeric::PdfInt(1).into());
+        cache.insert(&refs[1], PdfNumeric::PdfInt(2).into());
+        assert!(cache.get(&refs[0]).is_some());
+        cache.insert(&refs[2], PdfNumeric::PdfInt(3).into());
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&refs[1]).is_none());
+        assert!(cache.get(&refs[0]).is_some());
+
+        cache.set_capacity(1);
+        assert!(cache.get(&refs[2]).is_none());
+        cache.set_capacity(0);
+        cache.insert(&refs[2], PdfNumeric::PdfInt(3).into());
+        assert_eq!(cache.len(), 0);
+    }
+}
@@ -1,4 +1,9 @@
-use std::str::FromStr;
+use std::{cell::Cell, collections::BTreeMap, fmt::Display, str, str::FromStr};
+
+use crate::{
+    IndirectData, ParseOptions, ParseWarningKind, Parser, PdfNumeric, PdfObject, PdfStream,
+    Resolve, pdf_error::PdfResult,
+};
 
 use super::{PdfError, PdfErrorKind};
 
@@ -9,6 +14,9 @@ pub struct PdfCrossRefTable {
 #[derive(PartialEq, Debug, Clone)]
 pub struct PdfCrossRefTableSection {
     subsections: Vec<PdfCrossRefTableSubsection>,
+    /// Objects stored in object streams, from a cross-reference stream: the number of the
+    /// stream and the index of the object in it, by object number.
+    compressed: BTreeMap<usize, (usize, usize)>,
 }
 #[derive(PartialEq, Debug, Clone)]
 pub struct PdfCrossRefTableSubsection {
@@ -20,6 +28,15 @@ pub struct PdfCrossRefTableSubsectionHeader {
     first_object: usize,
     len: usize,
 }
+/// Where the cross-reference table places an object in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectLocation {
+    /// At a byte offset from the beginning of the file.
+    Offset(u64),
+    /// The `index`-th object of the object stream `stream` (7.5.7).
+    Compressed { stream: usize, index: usize },
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct PdfCrossRefTableEntry {
     // Byte offset from beginning of file
@@ -32,11 +49,86 @@ impl FromStr for PdfCrossRefTable {
     type Err = PdfError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let sections = s
-            .split(super::constants::CROSS_REF_SECTION_KEYWORD)
-            .skip(1)
-            .map(|s| format!("{}{s}", super::constants::CROSS_REF_SECTION_KEYWORD))
-            .map(|s| s.parse::<PdfCrossRefTableSection>())
+        Self::parse_with(s, 0, &mut Parser::strict())
+    }
+}
+
+impl PdfCrossRefTable {
+    /// Location of the in-use object `object generation`, later sections taking precedence.
+    pub fn location_of(&self, object: usize, generation: usize) -> Option<ObjectLocation> {
+        self.sections
+            .iter()
+            .rev()
+            .find_map(|s| s.location_of(object))
+            .flatten()
+            .filter(|(_, g)| *g == generation)
+            .map(|(location, _)| location)
+    }
+
+    /// Byte offset of the in-use object `object generation`, `None` if it is in an object
+    /// stream.
+    pub fn offset_of(&self, object: usize, generation: usize) -> Option<u64> {
+        match self.location_of(object, generation)? {
+            ObjectLocation::Offset(offset) => Some(offset),
+            ObjectLocation::Compressed { .. } => None,
+        }
+    }
+
+    /// Location of `reference`, an offset being checked to be within a file of `file_len`
+    /// bytes.
+    pub(crate) fn checked_location_of(
+        &self,
+        reference: &IndirectData,
+        file_len: usize,
+    ) -> PdfResult<ObjectLocation> {
+        let (object, generation) = (reference.object(), reference.generation());
+        self.location_of(object, generation)
+            .filter(|l| match l {
+                ObjectLocation::Offset(offset) => *offset < file_len as u64,
+                ObjectLocation::Compressed { .. } => true,
+            })
+            .ok_or_else(|| {
+                PdfError::with_kind(PdfErrorKind::UnresolvedReference(object, generation))
+            })
+    }
+
+    pub(crate) fn from_sections(sections: Vec<PdfCrossRefTableSection>) -> Self {
+        Self { sections }
+    }
+
+    /// The objects in use after the last section, by increasing object number.
+    pub fn in_use_objects(&self) -> Vec<IndirectData> {
+        let mut latest = BTreeMap::new();
+        for section in &self.sections {
+            for ss in &section.subsections {
+                for (i, entry) in ss.entries.iter().enumerate() {
+                    let generation = (!entry.free).then_some(usize::from(entry.gen_number));
+                    latest.insert(ss.header.first_object + i, generation);
+                }
+            }
+            for object in section.compressed.keys() {
+                latest.insert(*object, Some(0));
+            }
+        }
+        latest
+            .into_iter()
+            .filter_map(|(object, generation)| Some(IndirectData::new(object, generation?)))
+            .collect()
+    }
+
+    /// Parses the cross-reference sections in `s`, which starts `offset` bytes into the file.
+    pub fn parse_with(s: &str, offset: usize, parser: &mut Parser) -> Result<Self, PdfError> {
+        let starts: Vec<_> = s
+            .match_indices(super::constants::CROSS_REF_SECTION_KEYWORD)
+            .map(|(i, _)| i)
+            .collect();
+        let sections = starts
+            .iter()
+            .zip(starts.iter().skip(1).chain([&s.len()]))
+            .map(|(&start, &end)| {
+                PdfCrossRefTableSection::parse_with(&s[start..end], offset + start, parser)
+                    .map_err(|e| e.shifted(start))
+            })
             .collect::<Result<_, _>>()?;
         Ok(Self { sections })
     }
@@ -46,18 +138,157 @@ impl FromStr for PdfCrossRefTableSection {
     type Err = PdfError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, 0, &mut Parser::strict())
+    }
+}
+
+impl PdfCrossRefTableSection {
+    /// Section of a single subsection listing the objects from `first_object` on.
+    pub(crate) fn with_entries(first_object: usize, entries: Vec<PdfCrossRefTableEntry>) -> Self {
+        Self {
+            subsections: vec![PdfCrossRefTableSubsection {
+                header: PdfCrossRefTableSubsectionHeader {
+                    first_object,
+                    len: entries.len(),
+                },
+                entries,
+            }],
+            compressed: BTreeMap::new(),
+        }
+    }
+
+    /// Location and generation of `object`, `Some(None)` if it is free and `None` if the
+    /// section does not list it.
+    fn location_of(&self, object: usize) -> Option<Option<(ObjectLocation, usize)>> {
+        if let Some((stream, index)) = self.compressed.get(&object) {
+            let location = ObjectLocation::Compressed {
+                stream: *stream,
+                index: *index,
+            };
+            return Some(Some((location, 0)));
+        }
+        let entry = self.subsections.iter().find_map(|ss| {
+            let index = object.checked_sub(ss.header.first_object)?;
+            ss.entries.get(index)
+        })?;
+        Some((!entry.free).then_some((
+            ObjectLocation::Offset(entry.offset),
+            entry.gen_number.into(),
+        )))
+    }
+
+    /// The section of the cross-reference stream `stream` (7.5.8).
+    ///
+    /// Rows of unknown type, which stand for the null object, are skipped.
+    pub(crate) fn from_stream(stream: &PdfStream) -> PdfResult<Self> {
+        let invalid = |what| PdfError::with_kind(PdfErrorKind::InvalidData).expecting(what);
+        let info = stream.info();
+        let numbers = |key: &[u8]| -> PdfResult<Option<Vec<usize>>> {
+            info.get_array(key)?
+                .map(|a| {
+                    a.iter()
+                        .map(|n| n.as_numeric_ref().and_then(PdfNumeric::to_usize))
+                        .collect()
+                })
+                .transpose()
+        };
+        let widths = numbers(b"W")?
+            .filter(|w| w.len() == 3 && w.iter().all(|w| *w <= 8))
+            .ok_or_else(|| invalid("/W of three widths of at most 8 bytes"))?;
+        let row_len: usize = widths.iter().sum();
+        let index = match numbers(b"Index")? {
+            Some(index) => index,
+            None => {
+                let size = info
+                    .get_int(b"Size")?
+                    .and_then(|s| usize::try_from(s).ok())
+                    .ok_or_else(|| invalid("a /Size"))?;
+                vec![0, size]
+            }
+        };
+        if row_len == 0 || index.len() % 2 != 0 {
+            return Err(invalid("a cross-reference stream"));
+        }
+        let data = stream.decoded_data()?;
+        let mut rows = data.chunks_exact(row_len);
+
+        let mut section = Self {
+            subsections: Vec::new(),
+            compressed: BTreeMap::new(),
+        };
+        let mut subsection: Option<PdfCrossRefTableSubsection> = None;
+        for range in index.chunks(2) {
+            let (first, count) = (range[0], range[1]);
+            for object in (first..).take(count) {
+                let row = rows
+                    .next()
+                    .ok_or_else(|| invalid("a row for each object"))?;
+                let mut fields = [0u64; 3];
+                let mut rest = row;
+                for (field, width) in fields.iter_mut().zip(&widths) {
+                    let (bytes, tail) = rest.split_at(*width);
+                    *field = bytes.iter().fold(0, |v, b| v << 8 | u64::from(*b));
+                    rest = tail;
+                }
+                // The type defaults to 1 when its field is absent
+                let kind = if widths[0] == 0 { 1 } else { fields[0] };
+                let entry = match kind {
+                    0 | 1 => PdfCrossRefTableEntry {
+                        offset: fields[1],
+                        gen_number: u16::try_from(fields[2]).unwrap_or(u16::MAX),
+                        free: kind == 0,
+                    },
+                    2 => {
+                        section
+                            .compressed
+                            .insert(object, (fields[1] as usize, fields[2] as usize));
+                        section.subsections.extend(subsection.take());
+                        continue;
+                    }
+                    _ => {
+                        section.subsections.extend(subsection.take());
+                        continue;
+                    }
+                };
+                match &mut subsection {
+                    Some(ss) if ss.header.first_object + ss.header.len == object => {
+                        ss.header.len += 1;
+                        ss.entries.push(entry);
+                    }
+                    _ => {
+                        section.subsections.extend(subsection.take());
+                        subsection = Some(PdfCrossRefTableSubsection {
+                            header: PdfCrossRefTableSubsectionHeader {
+                                first_object: object,
+                                len: 1,
+                            },
+                            entries: vec![entry],
+                        });
+                    }
+                }
+            }
+            section.subsections.extend(subsection.take());
+        }
+        Ok(section)
+    }
+
+    /// Parses one section, `s` starting `offset` bytes into the file.
+    ///
+    /// Error offsets are relative to `s`.
+    pub fn parse_with(s: &str, offset: usize, parser: &mut Parser) -> Result<Self, PdfError> {
         let mut subsections = Vec::new();
         let mut subsection = None;
-        let mut lines = s.lines();
+        let mut lines = xref_lines(s, offset, parser).into_iter();
         if lines
             .next()
-            .is_none_or(|k| k != super::constants::CROSS_REF_SECTION_KEYWORD)
+            .is_none_or(|(_, k)| k != super::constants::CROSS_REF_SECTION_KEYWORD)
         {
-            return Err(PdfError {
-                kind: PdfErrorKind::Parse,
-            });
+            return Err(PdfError::with_kind(PdfErrorKind::Parse)
+                .at_offset(0, s.as_bytes())
+                .expecting("`xref`"));
         }
-        for line in lines {
+        for (offset, line) in lines {
+            let located = |e: PdfError| e.at_offset(offset, line.as_bytes());
             if let Ok(header) = line.parse() {
                 if let Some(ss) = subsection.take() {
                     subsections.push(ss);
@@ -69,33 +300,95 @@ impl FromStr for PdfCrossRefTableSection {
                 continue;
             }
             let Some(ss) = &mut subsection else {
-                Err(PdfError {
-                    kind: PdfErrorKind::Parse,
-                })?
-            };
-            let Ok(entry) = line.parse() else {
-                Err(PdfError {
-                    kind: PdfErrorKind::Parse,
-                })?
+                return Err(located(
+                    PdfError::with_kind(PdfErrorKind::Parse).expecting("subsection header"),
+                ));
             };
+            let entry = line
+                .parse()
+                .map_err(|e: PdfError| located(e.expecting("cross-reference entry")))?;
             ss.entries.push(entry);
         }
         if let Some(ss) = subsection.take() {
             subsections.push(ss);
         }
-        Ok(Self { subsections })
+        Ok(Self {
+            subsections,
+            compressed: BTreeMap::new(),
+        })
+    }
+}
+
+impl Display for PdfCrossRefTableSection {
+    /// Writes the section with two bytes line ends, each entry taking 20 bytes (7.5.4).
+    ///
+    /// Objects in object streams cannot be listed by a table and are left out.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", super::constants::CROSS_REF_SECTION_KEYWORD)?;
+        for ss in &self.subsections {
+            writeln!(f, "{} {}", ss.header.first_object, ss.header.len)?;
+            for entry in &ss.entries {
+                writeln!(f, "{entry} ")?;
+            }
+        }
+        Ok(())
     }
 }
 
+/// Splits `s` into lines with their offsets.
+///
+/// Lines end with `\n`, `\r\n` or ` \r`. In lenient mode only, a lone `\r` also ends a line,
+/// with a warning, and so does `\r\r\n`. The space of two bytes line ends is removed.
+fn xref_lines<'s>(s: &'s str, offset: usize, parser: &mut Parser) -> Vec<(usize, &'s str)> {
+    let bytes = s.as_bytes();
+    let mut lines = Vec::new();
+    let mut start = 0;
+    // Whether the last line ended with a lone `\r`, which may be followed by `\r\n`
+    let mut lone_end = false;
+    for (i, b) in bytes.iter().enumerate() {
+        let end = match b {
+            b'\n' if lone_end && start + 1 == i && bytes[start] == b'\r' => {
+                lone_end = false;
+                start = i + 1;
+                continue;
+            }
+            b'\n' if i > start && bytes[i - 1] == b'\r' => i - 1,
+            b'\n' => i,
+            b'\r' if bytes.get(i + 1) != Some(&b'\n') => {
+                let spaced = i > start && bytes[i - 1] == b' ';
+                if !spaced && !parser.is_lenient() {
+                    continue;
+                }
+                if !spaced {
+                    parser.warn_at(ParseWarningKind::LoneCarriageReturn, offset + i);
+                }
+                lone_end = !spaced;
+                lines.push((start, &s[start..i]));
+                start = i + 1;
+                continue;
+            }
+            _ => continue,
+        };
+        lone_end = false;
+        lines.push((start, &s[start..end]));
+        start = i + 1;
+    }
+    if start < s.len() {
+        lines.push((start, &s[start..]));
+    }
+    lines
+        .into_iter()
+        .map(|(start, line)| (start, line.strip_suffix(' ').unwrap_or(line)))
+        .collect()
+}
+
 impl FromStr for PdfCrossRefTableSubsectionHeader {
     type Err = PdfError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let items: Vec<usize> = s.split(' ').map(str::parse).collect::<Result<_, _>>()?;
         if items.len() != 2 {
-            return Err(PdfError {
-                kind: PdfErrorKind::Parse,
-            });
+            return Err(PdfError::with_kind(PdfErrorKind::Parse));
         }
 
         Ok(PdfCrossRefTableSubsectionHeader {
@@ -105,31 +398,50 @@ impl FromStr for PdfCrossRefTableSubsectionHeader {
     }
 }
 
+impl PdfCrossRefTableEntry {
+    pub(crate) fn in_use(offset: u64, gen_number: u16) -> Self {
+        Self {
+            offset,
+            gen_number,
+            free: false,
+        }
+    }
+    /// Free entry linking to the `next` free object.
+    pub(crate) fn free(next: u64, gen_number: u16) -> Self {
+        Self {
+            offset: next,
+            gen_number,
+            free: true,
+        }
+    }
+}
+
+impl Display for PdfCrossRefTableEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.free { 'f' } else { 'n' };
+        write!(f, "{:010} {:05} {kind}", self.offset, self.gen_number)
+    }
+}
+
 impl FromStr for PdfCrossRefTableEntry {
     type Err = PdfError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.len() != 18 {
-            return Err(PdfError {
-                kind: PdfErrorKind::Parse,
-            });
+            return Err(PdfError::with_kind(PdfErrorKind::Parse));
         }
         let items: Vec<_> = [s.get(..10), s.get(11..16), s.get(17..18)]
             .into_iter()
             .flatten()
             .collect();
         if items.len() != 3 {
-            return Err(PdfError {
-                kind: PdfErrorKind::Parse,
-            });
+            return Err(PdfError::with_kind(PdfErrorKind::Parse));
         }
         let (offset, gen_number, free) =
             (items[0].parse()?, items[1].parse()?, match items.get(2) {
                 Some(&"f") => Ok(true),
                 Some(&"n") => Ok(false),
-                _ => Err(PdfError {
-                    kind: PdfErrorKind::Parse,
-                }),
+                _ => Err(PdfError::with_kind(PdfErrorKind::Parse)),
             }?);
 
         Ok(PdfCrossRefTableEntry {
@@ -140,6 +452,142 @@ impl FromStr for PdfCrossRefTableEntry {
     }
 }
 
+/// How many objects can be resolved while resolving another, to break reference cycles.
+const MAX_RESOLVE_DEPTH: usize = 32;
+
+/// Resolves objects of a file loaded in memory through its cross-reference table.
+pub struct XrefResolver<'a> {
+    bytes: &'a [u8],
+    table: &'a PdfCrossRefTable,
+    options: ParseOptions,
+    depth: Cell<usize>,
+}
+
+impl<'a> XrefResolver<'a> {
+    pub fn new(bytes: &'a [u8], table: &'a PdfCrossRefTable, options: ParseOptions) -> Self {
+        Self {
+            bytes,
+            table,
+            options,
+            depth: Cell::new(0),
+        }
+    }
+}
+
+impl Resolve for XrefResolver<'_> {
+    fn resolve(&self, reference: &IndirectData) -> PdfResult<PdfObject> {
+        let mut parser = Parser::new(self.options).with_resolver(self);
+        match self
+            .table
+            .checked_location_of(reference, self.bytes.len())?
+        {
+            ObjectLocation::Offset(offset) => {
+                let offset = offset as usize;
+                parse_object_at(
+                    &self.bytes[offset..],
+                    offset,
+                    reference,
+                    &mut parser,
+                    &self.depth,
+                )
+                .map(|(object, _)| object)
+            }
+            ObjectLocation::Compressed { stream, index } => {
+                let stream = object_stream(self, stream)?;
+                parse_compressed_object(&stream, index, reference, &mut parser)
+            }
+        }
+    }
+}
+
+/// The object stream `number`, which cannot itself be in an object stream.
+pub(crate) fn object_stream(resolver: &impl Resolve, number: usize) -> PdfResult<PdfStream> {
+    resolver.resolve(&IndirectData::new(number, 0))?.as_stream()
+}
+
+/// Parses the `index`-th object of the object stream `stream` (7.5.7), which should be
+/// `reference`.
+pub(crate) fn parse_compressed_object(
+    stream: &PdfStream,
+    index: usize,
+    reference: &IndirectData,
+    parser: &mut Parser,
+) -> PdfResult<PdfObject> {
+    let unresolved = || {
+        PdfError::with_kind(PdfErrorKind::UnresolvedReference(
+            reference.object(),
+            reference.generation(),
+        ))
+    };
+    let invalid = || PdfError::with_kind(PdfErrorKind::InvalidData).expecting("an object stream");
+    if reference.generation() != 0 {
+        return Err(unresolved());
+    }
+    let info = stream.info();
+    let count = info.get_int(b"N")?.ok_or_else(invalid)?;
+    let first = info
+        .get_int(b"First")?
+        .and_then(|f| usize::try_from(f).ok())
+        .ok_or_else(invalid)?;
+    if i64::try_from(index).is_ok_and(|i| i >= count) {
+        return Err(unresolved());
+    }
+    let data = stream.decoded_data()?;
+    let header = data.get(..first).ok_or_else(invalid)?;
+    // The numbers and offsets of this object and of the next one, which ends it
+    let pairs: Vec<usize> = str::from_utf8(header)
+        .map_err(PdfError::from)?
+        .split_ascii_whitespace()
+        .skip(2 * index)
+        .take(4)
+        .map(|n| n.parse().map_err(PdfError::from))
+        .collect::<PdfResult<_>>()?;
+    let [number, offset, ..] = pairs[..] else {
+        return Err(invalid());
+    };
+    if number != reference.object() {
+        return Err(unresolved());
+    }
+    let start = first.saturating_add(offset);
+    let end = pairs
+        .get(3)
+        .map_or(data.len(), |next| first.saturating_add(*next));
+    let bytes = data.get(start..end).ok_or_else(invalid)?;
+    let (object, _) = parser.parse::<PdfObject>(bytes, 0)?;
+    Ok(object.into_indirect(reference.clone()))
+}
+
+/// Parses the indirect object `reference` at the start of `bytes`, found `offset` bytes into the
+/// file, returning it with the bytes after it.
+///
+/// `depth` counts the nested resolutions, failing past [`MAX_RESOLVE_DEPTH`].
+pub(crate) fn parse_object_at<'a>(
+    bytes: &'a [u8],
+    offset: usize,
+    reference: &IndirectData,
+    parser: &mut Parser,
+    depth: &Cell<usize>,
+) -> PdfResult<(PdfObject, &'a [u8])> {
+    if depth.get() >= MAX_RESOLVE_DEPTH {
+        return Err(PdfError::with_kind(PdfErrorKind::UnresolvedReference(
+            reference.object(),
+            reference.generation(),
+        ))
+        .at_offset(offset, bytes));
+    }
+    depth.set(depth.get() + 1);
+    let parsed = parser.parse::<PdfObject>(bytes, offset);
+    depth.set(depth.get() - 1);
+
+    let (parsed, rest) = parsed?;
+    if parsed.indirect() != Some(reference) {
+        return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+            .at_offset(offset, bytes)
+            .expecting("the referenced object"));
+    }
+    Ok((parsed, rest))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,6 +654,7 @@ mod tests {
                         },
                     ],
                 }],
+                compressed: BTreeMap::new(),
             }
         }
 
@@ -264,6 +713,7 @@ mod tests {
                         }],
                     },
                 ],
+                compressed: BTreeMap::new(),
             }
         }
 
@@ -304,6 +754,89 @@ mod tests {
         assert_eq!(combined.parse(), Ok(combined_sections));
     }
 
+    #[test]
+    fn display() {
+        for (text, section) in examples::examples() {
+            let written = section.to_string();
+            assert_eq!(written.lines().count(), text.lines().count());
+            assert!(
+                written
+                    .lines()
+                    .skip(2)
+                    .all(|l| l.len() == 19 || l.len() < 10)
+            );
+            assert_eq!(written.parse(), Ok(section));
+        }
+        let table: PdfCrossRefTable = examples::examples()[1].0.parse().unwrap();
+        let in_use: Vec<_> = table.in_use_objects().iter().map(|r| r.object()).collect();
+        assert_eq!(in_use, [3, 23, 24, 30]);
+    }
+
+    #[test]
+    fn error_offsets() {
+        let table = "xref\n0 1\n0000000000 65535 f\nxref\n3 1\n000025325 00000 n\n";
+        let err = table.parse::<PdfCrossRefTable>().unwrap_err();
+        assert_eq!(err.offset(), Some(37));
+        assert_eq!(err.snippet(), b"000025325 00000 n");
+        assert_eq!(err.expected(), Some("cross-reference entry"));
+    }
+
+    #[test]
+    fn resolve() {
+        let objects = [
+            "1 0 obj\n<</Length 2 0 R>> stream\nabcd\nendstream\nendobj\n",
+            "2 0 obj\n4\nendobj\n",
+            "3 0 obj\n<</Length 3 0 R>> stream\nabcd\nendstream\nendobj\n",
+        ];
+        let mut file = String::from("%PDF-1.7\n");
+        let mut xref = String::from("xref\n0 4\n0000000000 65535 f\n");
+        for object in objects {
+            xref.push_str(&format!("{:010} 00000 n\n", file.len()));
+            file.push_str(object);
+        }
+        let table = xref.parse::<PdfCrossRefTable>().unwrap();
+        let resolver = XrefResolver::new(file.as_bytes(), &table, ParseOptions::strict());
+
+        let stream = resolver.resolve(&IndirectData::new(1, 0)).unwrap();
+        assert_eq!(stream.as_stream().unwrap().len(), 4);
+        assert_eq!(
+            resolver.resolve(&IndirectData::new(2, 1)),
+            Err(PdfError::with_kind(PdfErrorKind::UnresolvedReference(2, 1)))
+        );
+        let cycle = resolver.resolve(&IndirectData::new(3, 0)).unwrap_err();
+        assert_eq!(cycle.kind(), &PdfErrorKind::UnresolvedReference(3, 0));
+        assert_eq!(cycle.object(), Some((3, 0)));
+    }
+
+    #[test]
+    fn lone_carriage_return() {
+        let table = "xref\r0 2\r0000000000 65535 f\r0000000017 00000 n\r";
+        assert!(table.parse::<PdfCrossRefTable>().is_err());
+        let mut parser = Parser::lenient();
+        let parsed = PdfCrossRefTable::parse_with(table, 10, &mut parser).unwrap();
+        assert_eq!(parsed.sections[0].subsections[0].entries.len(), 2);
+        assert_eq!(parser.warnings().len(), 4);
+        assert_eq!(parser.warnings()[1].offset, Some(18));
+        let spaced = "xref\n0 2 \r0000000000 65535 f \r0000000017 00000 n \n";
+        assert_eq!(spaced.parse::<PdfCrossRefTable>(), Ok(parsed));
+    }
+
+    #[test]
+    fn strict_carriage_return() {
+        for table in [
+            "xref\r\r\n0 1\n0000000000 65535 f\r\n",
+            "xref\n0 1\n0000000000 65535 f\r",
+        ] {
+            let mut parser = Parser::strict();
+            assert!(PdfCrossRefTable::parse_with(table, 0, &mut parser).is_err());
+            assert!(parser.warnings().is_empty(), "{table:?}");
+            let mut parser = Parser::lenient();
+            let parsed = PdfCrossRefTable::parse_with(table, 0, &mut parser).unwrap();
+            assert_eq!(parsed.sections[0].subsections[0].entries.len(), 1);
+            assert_eq!(parser.warnings().len(), 1, "{table:?}");
+        }
+    }
+
     #[test]
     fn parse_entry_errors() {
         let examples = [
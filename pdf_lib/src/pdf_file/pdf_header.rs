@@ -1,4 +1,4 @@
-use crate::{Parsable, next_eol, pdf_error::*, strip_whitespace};
+use crate::{Parsable, ParseWarningKind, Parser, next_eol, pdf_error::*, strip_whitespace};
 
 use std::{char::REPLACEMENT_CHARACTER, fmt::Display};
 
@@ -16,6 +16,14 @@ impl PdfHeader {
             })
             .ok_or_else(|| PdfError::with_kind(PdfErrorKind::Parse))
     }
+    pub fn version(&self) -> PdfVersion {
+        self.version
+    }
+    /// The header with version `major.minor` if it is later than its own.
+    pub(crate) fn at_least(self, major: u8, minor: u8) -> Self {
+        let version = self.version.max(PdfVersion { minor, major });
+        Self { version, ..self }
+    }
 }
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub struct PdfVersion {
@@ -23,6 +31,23 @@ pub struct PdfVersion {
     major: u8,
 }
 
+impl PdfVersion {
+    pub fn new(major: u8, minor: u8) -> Self {
+        Self { minor, major }
+    }
+}
+
+impl PartialOrd for PdfVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for PdfVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.major, self.minor).cmp(&(other.major, other.minor))
+    }
+}
+
 impl Display for PdfVersion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}.{}", self.major, self.minor)
@@ -39,14 +64,29 @@ impl Display for PdfHeader {
     }
 }
 
+/// How far into the file lenient parsing looks for the header.
+const HEADER_SEARCH_LEN: usize = 1024;
+
 impl Parsable for PdfHeader {
-    fn from_bytes(mut bytes: &[u8]) -> Result<(Self, &[u8]), crate::pdf_error::PdfError> {
+    fn from_bytes_with<'a>(
+        mut bytes: &'a [u8],
+        parser: &mut Parser,
+    ) -> PdfResult<(Self, &'a [u8])> {
         if !bytes.starts_with(b"%PDF-") {
-            return Err(PdfError::with_kind(PdfErrorKind::Parse));
+            let found = bytes[..bytes.len().min(HEADER_SEARCH_LEN)]
+                .windows(5)
+                .position(|w| w == b"%PDF-");
+            match found {
+                Some(skipped) if parser.is_lenient() => {
+                    parser.warn(ParseWarningKind::JunkBeforeHeader { skipped }, bytes);
+                    bytes = &bytes[skipped..];
+                }
+                _ => return Err(PdfError::parse_at(bytes, "`%PDF-`")),
+            }
         };
         bytes = &bytes[5..];
         if bytes.len() < 3 {
-            return Err(PdfError::with_kind(PdfErrorKind::Parse));
+            return Err(PdfError::parse_at(bytes, "version number"));
         }
         let major = (bytes[0] as char)
             .to_digit(10)
@@ -57,6 +97,7 @@ impl Parsable for PdfHeader {
             .map(TryInto::try_into)
             .map(Result::unwrap);
         if major.and(minor).is_some() && bytes[1] == b'.' {
+            let version_bytes = bytes;
             let minor = minor.unwrap();
             let major = major.unwrap();
             bytes = &bytes[3..];
@@ -70,11 +111,12 @@ impl Parsable for PdfHeader {
             };
 
             let version = PdfVersion { minor, major };
-            let header = Self::with_verion(version, binary_marker)?;
+            let header = Self::with_verion(version, binary_marker)
+                .map_err(|e| e.at(version_bytes).expecting("version 1.0 to 1.7 or 2.0"))?;
 
             Ok((header, bytes))
         } else {
-            Err(PdfError::with_kind(PdfErrorKind::Parse))
+            Err(PdfError::parse_at(bytes, "version number"))
         }
     }
 }
@@ -82,7 +124,7 @@ impl Parsable for PdfHeader {
 #[cfg(test)]
 mod tests {
     use crate::{
-        parse,
+        ParseWarningKind, Parser, parse,
         pdf_error::{PdfError, PdfErrorKind},
     };
 
@@ -127,4 +169,18 @@ mod tests {
             assert_eq!(parse(s.as_bytes()), e.map(|o| (o, &[] as &[u8])));
         }
     }
+
+    #[test]
+    fn junk_before_header() {
+        let input = b"\xef\xbb\xbfjunk%PDF-1.4\n";
+        assert!(parse::<PdfHeader>(input).is_err());
+        let mut parser = Parser::lenient();
+        let (header, _) = parser.parse::<PdfHeader>(input, 0).unwrap();
+        assert_eq!(header.version, PdfVersion { major: 1, minor: 4 });
+        assert_eq!(
+            parser.warnings()[0].kind,
+            ParseWarningKind::JunkBeforeHeader { skipped: 7 }
+        );
+        assert_eq!(parser.warnings()[0].offset, Some(0));
+    }
 }
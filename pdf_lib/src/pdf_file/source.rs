@@ -0,0 +1,181 @@
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    io::{Read, Seek, SeekFrom},
+};
+
+use crate::{
+    Parser,
+    pdf_error::{PdfError, PdfErrorKind, PdfResult},
+};
+
+/// Size of the first window read when parsing from a reader, doubled until the value fits.
+const READ_WINDOW_LEN: usize = 4096;
+
+/// Size of the largest window read when parsing from a reader, bounding the size of a value.
+const MAX_READ_WINDOW_LEN: usize = 1 << 28;
+
+/// [`Read`] and [`Seek`] in a single object safe trait.
+pub(crate) trait ReadSeek: Read + Seek {}
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// Where the bytes of a document come from: a buffer in memory, or a reader only read where
+/// needed.
+pub(crate) enum Source<'a> {
+    Bytes(&'a [u8]),
+    Reader {
+        reader: RefCell<Box<dyn ReadSeek + 'a>>,
+        len: usize,
+    },
+}
+
+impl<'a> Source<'a> {
+    pub fn from_reader(mut reader: impl Read + Seek + 'a) -> PdfResult<Self> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        let len = usize::try_from(len).map_err(|_| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("an addressable file length")
+        })?;
+        Ok(Self::Reader {
+            reader: RefCell::new(Box::new(reader)),
+            len,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Source::Bytes(bytes) => bytes.len(),
+            Source::Reader { len, .. } => *len,
+        }
+    }
+
+    /// Reads at most `len` bytes starting at `offset`.
+    pub fn read(&self, offset: usize, len: usize) -> PdfResult<Cow<'a, [u8]>> {
+        match self {
+            Source::Bytes(bytes) => {
+                let start = offset.min(bytes.len());
+                let end = offset.saturating_add(len).min(bytes.len());
+                Ok(Cow::Borrowed(&bytes[start..end]))
+            }
+            Source::Reader { reader, .. } => {
+                let mut reader = reader.borrow_mut();
+                reader.seek(SeekFrom::Start(offset as u64))?;
+                let mut window = Vec::with_capacity(len);
+                (&mut **reader).take(len as u64).read_to_end(&mut window)?;
+                Ok(Cow::Owned(window))
+            }
+        }
+    }
+
+    /// Parses the value at `offset` with `parse`, which returns it with the number of bytes left
+    /// after it.
+    ///
+    /// A reader is read by growing windows until the value ends before the window does, each
+    /// attempt working on a copy of `parser` so that only the warnings of the last one are kept.
+    /// The window only grows on errors raised at its end, up to [`MAX_READ_WINDOW_LEN`]; other
+    /// errors are returned at once.
+    pub fn parse_at<T>(
+        &self,
+        offset: usize,
+        parser: &mut Parser,
+        mut parse: impl FnMut(&[u8], &mut Parser) -> PdfResult<(T, usize)>,
+    ) -> PdfResult<T> {
+        if let Source::Bytes(bytes) = self {
+            let bytes = bytes.get(offset..).unwrap_or_default();
+            return parse(bytes, parser).map(|(value, _)| value);
+        }
+        let mut len = READ_WINDOW_LEN;
+        loop {
+            let window = self.read(offset, len)?;
+            let at_end = offset + window.len() >= self.len();
+            let mut attempt = parser.clone();
+            match parse(&window, &mut attempt) {
+                Ok((value, left)) if left > 0 || at_end || len >= MAX_READ_WINDOW_LEN => {
+                    *parser = attempt;
+                    return Ok(value);
+                }
+                Err(e) if at_end || !e.is_end_of_input() || len >= MAX_READ_WINDOW_LEN => {
+                    *parser = attempt;
+                    return Err(e);
+                }
+                _ => len = (len * 2).min(MAX_READ_WINDOW_LEN),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        io::{Cursor, Read, Seek, SeekFrom},
+        rc::Rc,
+    };
+
+    use crate::{IndirectData, PdfFile, Resolve, pdf_file::test_file};
+
+    /// Counts the bytes read from the wrapped cursor.
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        read: Rc<Cell<usize>>,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.read.set(self.read.get() + n);
+            Ok(n)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn reads_only_needed_ranges() {
+        let big = "x".repeat(100_000);
+        let stream = format!("<< /Length {} >> stream\n{big}\nendstream", big.len());
+        let long = format!("({})", "y".repeat(10_000));
+        let bytes = test_file::build(
+            &["<< /Type /Catalog /Big 2 0 R >>", &stream, &long],
+            "/Root 1 0 R",
+        );
+        let read = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            inner: Cursor::new(bytes.clone()),
+            read: read.clone(),
+        };
+
+        let file = PdfFile::from_reader(reader).unwrap();
+        let catalog = file.catalog().unwrap();
+        assert!(catalog.get(b"Big").is_some());
+        assert!(read.get() < 20_000);
+
+        let in_memory = PdfFile::load(&bytes).unwrap();
+        for object in 1..=3 {
+            let reference = IndirectData::new(object, 0);
+            assert_eq!(file.resolve(&reference), in_memory.resolve(&reference));
+        }
+    }
+
+    #[test]
+    fn returns_syntax_errors_at_once() {
+        let big = "x".repeat(100_000);
+        let stream = format!("<< /Length {} >> stream\n{big}\nendstream", big.len());
+        let bytes = test_file::build(
+            &["<< /Type /Catalog /Bad 2 0 R >>", "<< /A ] >>", &stream],
+            "/Root 1 0 R",
+        );
+        let read = Rc::new(Cell::new(0));
+        let reader = CountingReader {
+            inner: Cursor::new(bytes),
+            read: read.clone(),
+        };
+
+        let file = PdfFile::from_reader(reader).unwrap();
+        assert!(file.resolve(&IndirectData::new(2, 0)).is_err());
+        assert!(read.get() < 20_000);
+    }
+}
@@ -0,0 +1,474 @@
+use std::io::{self, Write};
+
+use crate::{
+    IndirectData, PdfArray, PdfDict, PdfName, PdfNumeric, PdfObject, PdfObjectKind, PdfStream,
+    Resolve, Writable,
+    filter::{DEFAULT_LEVEL, Filter, encode_flate},
+    pdf_error::PdfResult,
+};
+
+use super::{
+    PdfFile,
+    pdf_cross_ref_table::{PdfCrossRefTableEntry, PdfCrossRefTableSection},
+};
+
+/// Generation number of the head of the free list, object 0 (7.5.4).
+pub(super) const FREE_LIST_HEAD_GENERATION: u16 = 65535;
+
+/// How [`PdfFile::write_with`] lays out a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Whether the cross-reference table is written as a `/Type /XRef` stream (7.5.8), which
+    /// needs PDF 1.5.
+    pub xref_stream: bool,
+    /// Most objects packed in each `/Type /ObjStm` object stream (7.5.7), 0 for none. Objects
+    /// in object streams can only be found through a cross-reference stream, which is then
+    /// written whatever `xref_stream`.
+    pub objects_per_stream: usize,
+    /// Flate compression level of the object and cross-reference streams, from 0 (stored)
+    /// to 10.
+    pub compression_level: u8,
+}
+
+impl Default for WriteOptions {
+    /// A text cross-reference table, and no object streams.
+    fn default() -> Self {
+        Self {
+            xref_stream: false,
+            objects_per_stream: 0,
+            compression_level: DEFAULT_LEVEL,
+        }
+    }
+}
+
+impl WriteOptions {
+    /// A cross-reference stream and object streams of up to 100 objects.
+    pub fn compressed() -> Self {
+        Self {
+            xref_stream: true,
+            objects_per_stream: 100,
+            compression_level: DEFAULT_LEVEL,
+        }
+    }
+}
+
+/// Where an object is found, a row of the cross-reference table or stream.
+#[derive(Debug, Clone, Copy)]
+enum XrefRow {
+    Free {
+        next: u64,
+        generation: u16,
+    },
+    InUse {
+        offset: u64,
+        generation: u16,
+    },
+    /// The `index`-th object of the object stream `stream`, only in cross-reference streams.
+    Compressed {
+        stream: u64,
+        index: u64,
+    },
+}
+
+impl XrefRow {
+    /// Type and fields of the row in a cross-reference stream.
+    fn fields(self) -> [u64; 3] {
+        match self {
+            XrefRow::Free { next, generation } => [0, next, generation.into()],
+            XrefRow::InUse { offset, generation } => [1, offset, generation.into()],
+            XrefRow::Compressed { stream, index } => [2, stream, index],
+        }
+    }
+
+    fn entry(self) -> PdfCrossRefTableEntry {
+        match self {
+            XrefRow::Free { next, generation } => PdfCrossRefTableEntry::free(next, generation),
+            XrefRow::InUse { offset, generation } => {
+                PdfCrossRefTableEntry::in_use(offset, generation)
+            }
+            XrefRow::Compressed { .. } => unreachable!("object streams come with an xref stream"),
+        }
+    }
+}
+
+/// Number of bytes needed to write `value` big-endian.
+fn byte_width(value: u64) -> usize {
+    (8 - value.leading_zeros() as usize / 8).max(1)
+}
+
+/// Keeps track of the number of bytes written, for the offsets of the cross-reference table.
+struct CountingWriter<W> {
+    inner: W,
+    written: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> CountingWriter<W> {
+    /// Writes `object` as the indirect object `reference`, returning its row.
+    fn write_object(&mut self, reference: &IndirectData, object: &PdfObject) -> PdfResult<XrefRow> {
+        let row = XrefRow::InUse {
+            offset: self.written,
+            generation: u16::try_from(reference.generation()).unwrap_or(u16::MAX),
+        };
+        writeln!(
+            self,
+            "{} {} obj",
+            reference.object(),
+            reference.generation()
+        )?;
+        object.write_to(self)?;
+        self.write_all(b"\nendobj\n")?;
+        Ok(row)
+    }
+}
+
+/// The Flate stream of `data`, with the predictor given by `params` if any.
+fn flate_stream(
+    mut info: PdfDict,
+    data: Vec<u8>,
+    params: PdfDict,
+    level: u8,
+) -> PdfResult<PdfStream> {
+    let encoded = encode_flate(data, &params, level)?;
+    info.insert(
+        PdfName::from_raw_bytes(b"Filter"),
+        PdfName::from(&Filter::Flate),
+    );
+    if !params.is_empty() {
+        info.insert(PdfName::from_raw_bytes(b"DecodeParms"), params);
+    }
+    Ok(PdfStream::new(info, encoded))
+}
+
+/// The object stream of `objects`: their numbers and offsets, then the objects themselves.
+fn object_stream(objects: &[(IndirectData, PdfObject)], level: u8) -> PdfResult<PdfStream> {
+    let mut offsets = Vec::new();
+    let mut body = Vec::new();
+    for (reference, object) in objects {
+        write!(offsets, "{} {} ", reference.object(), body.len())?;
+        object.write_to(&mut body)?;
+        body.push(b'\n');
+    }
+    let mut info = PdfDict::empty();
+    info.insert(
+        PdfName::from_raw_bytes(b"Type"),
+        PdfName::from_raw_bytes(b"ObjStm"),
+    );
+    info.insert(
+        PdfName::from_raw_bytes(b"N"),
+        PdfNumeric::PdfInt(objects.len() as i64),
+    );
+    info.insert(
+        PdfName::from_raw_bytes(b"First"),
+        PdfNumeric::PdfInt(offsets.len() as i64),
+    );
+    offsets.extend(body);
+    flate_stream(info, offsets, PdfDict::empty(), level)
+}
+
+/// The cross-reference stream of `rows`, its dictionary holding the entries of `trailer`.
+///
+/// Fields are as narrow as their largest value, and rows encoded with the PNG `Up`
+/// predictor, which turns the similar rows into runs of zeros that compress well.
+fn cross_ref_stream(trailer: PdfDict, rows: &[XrefRow], level: u8) -> PdfResult<PdfStream> {
+    let fields: Vec<_> = rows.iter().map(|r| r.fields()).collect();
+    let widths: [usize; 3] = std::array::from_fn(|i| {
+        let widest = fields.iter().map(|f| f[i]).max().unwrap_or(0);
+        byte_width(widest)
+    });
+    let mut data = Vec::with_capacity(fields.len() * widths.iter().sum::<usize>());
+    for row in &fields {
+        for (value, width) in row.iter().zip(widths) {
+            data.extend_from_slice(&value.to_be_bytes()[8 - width..]);
+        }
+    }
+    let mut info = trailer;
+    info.insert(
+        PdfName::from_raw_bytes(b"Type"),
+        PdfName::from_raw_bytes(b"XRef"),
+    );
+    info.insert(
+        PdfName::from_raw_bytes(b"W"),
+        PdfArray::from_numbers(&widths.map(|w| w as f64)),
+    );
+    let mut params = PdfDict::empty();
+    params.insert(
+        PdfName::from_raw_bytes(b"Predictor"),
+        PdfNumeric::PdfInt(12),
+    );
+    params.insert(
+        PdfName::from_raw_bytes(b"Columns"),
+        PdfNumeric::PdfInt(widths.iter().sum::<usize>() as i64),
+    );
+    flate_stream(info, data, params, level)
+}
+
+impl PdfFile<'_> {
+    /// Writes the document with the changes made since loading, as a new file with a single
+    /// cross-reference section.
+    pub fn write_to(&self, w: impl Write) -> PdfResult<()> {
+        self.write_with(w, &WriteOptions::default())
+    }
+
+    /// Writes the document like [`PdfFile::write_to`], laid out as `options` tell.
+    ///
+    /// With a cross-reference stream, the header is raised to PDF 1.5 if it is earlier. Streams,
+    /// objects of a generation other than 0 and the encryption dictionary are never put in
+    /// object streams.
+    pub fn write_with(&self, w: impl Write, options: &WriteOptions) -> PdfResult<()> {
+        let xref_stream = options.xref_stream || options.objects_per_stream > 0;
+        let mut w = CountingWriter {
+            inner: w,
+            written: 0,
+        };
+        let header = if xref_stream {
+            self.header.at_least(1, 5)
+        } else {
+            self.header
+        };
+        write!(w, "{header}")?;
+
+        let objects = self.objects();
+        let mut size = objects.last().map_or(1, |r| r.object() + 1);
+        let encrypt = self
+            .trailer
+            .dict
+            .get(b"Encrypt")
+            .and_then(PdfObject::as_reference);
+        let mut rows = vec![None; size];
+        let mut packed = Vec::new();
+        for reference in &objects {
+            let object = self.resolve(reference)?;
+            if options.objects_per_stream > 0
+                && reference.generation() == 0
+                && !matches!(object.kind(), PdfObjectKind::Stream(_))
+                && Some(reference) != encrypt
+            {
+                packed.push((reference.clone(), object));
+                continue;
+            }
+            rows[reference.object()] = Some(w.write_object(reference, &object)?);
+        }
+        for chunk in packed.chunks(options.objects_per_stream.max(1)) {
+            let stream = IndirectData::new(size, 0);
+            size += 1;
+            let object = object_stream(chunk, options.compression_level)?.into();
+            rows.push(Some(w.write_object(&stream, &object)?));
+            for (index, (reference, _)) in chunk.iter().enumerate() {
+                rows[reference.object()] = Some(XrefRow::Compressed {
+                    stream: stream.object() as u64,
+                    index: index as u64,
+                });
+            }
+        }
+
+        let mut trailer = self.trailer.dict.clone();
+        trailer.remove(b"Prev");
+        trailer.remove(b"XRefStm");
+        let startxref = w.written;
+        let xref = xref_stream.then(|| {
+            rows.push(Some(XrefRow::InUse {
+                offset: startxref,
+                generation: 0,
+            }));
+            size += 1;
+            IndirectData::new(size - 1, 0)
+        });
+        trailer.insert(
+            PdfName::from_raw_bytes(b"Size"),
+            PdfNumeric::PdfInt(size as i64),
+        );
+
+        // Free objects are linked by increasing number, the last one back to object 0
+        let free: Vec<_> = (0..size).filter(|o| rows[*o].is_none()).collect();
+        for (i, object) in free.iter().enumerate() {
+            let next = free.get(i + 1).map_or(0, |n| *n as u64);
+            let generation = if *object == 0 {
+                FREE_LIST_HEAD_GENERATION
+            } else {
+                0
+            };
+            rows[*object] = Some(XrefRow::Free { next, generation });
+        }
+
+        match xref {
+            Some(xref) => {
+                let rows: Vec<_> = rows.into_iter().flatten().collect();
+                let stream = cross_ref_stream(trailer, &rows, options.compression_level)?;
+                w.write_object(&xref, &stream.into())?;
+            }
+            None => {
+                let entries = rows.into_iter().flatten().map(XrefRow::entry).collect();
+                write!(w, "{}", PdfCrossRefTableSection::with_entries(0, entries))?;
+                w.write_all(b"trailer\n")?;
+                trailer.write_to(&mut w)?;
+                w.write_all(b"\n")?;
+            }
+        }
+        write!(w, "startxref\n{startxref}\n%%EOF\n")?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// The document written by [`PdfFile::write_to`].
+    pub fn to_bytes(&self) -> PdfResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// The document written by [`PdfFile::write_with`].
+    pub fn to_bytes_with(&self, options: &WriteOptions) -> PdfResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.write_with(&mut bytes, options)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        IndirectData, ObjectLocation, PdfDict, PdfErrorKind, PdfFile, PdfName, PdfNumeric,
+        PdfObject, PdfString, Resolve, WriteOptions, parse,
+    };
+
+    use super::super::test_file;
+
+    #[test]
+    fn round_trip() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [] /Count 0 >>",
+                "<< /Length 3 >> stream\nabc\nendstream",
+            ],
+            "/Root 1 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        let written = file.to_bytes().unwrap();
+        let reloaded = PdfFile::load(&written).unwrap();
+        for object in 1..=3 {
+            let reference = IndirectData::new(object, 0);
+            assert_eq!(reloaded.resolve(&reference), file.resolve(&reference));
+        }
+        assert_eq!(reloaded.trailer().get_int(b"Size"), Ok(Some(4)));
+        assert_eq!(reloaded.objects().len(), 3);
+    }
+
+    #[test]
+    fn updates() {
+        let bytes = test_file::build(&["<< /Type /Catalog >>", "(old)", "(gone)"], "/Root 1 0 R");
+        let mut file = PdfFile::load(&bytes).unwrap();
+        let second = IndirectData::new(2, 0);
+        file.set_object(&second, PdfString::from_raw_bytes(b"new"));
+        file.delete_object(&IndirectData::new(3, 0));
+        let added = file.add_object(PdfDict::empty());
+        assert_eq!(added, IndirectData::new(4, 0));
+        let mut catalog = file.catalog().unwrap();
+        catalog.insert(
+            PdfName::from_raw_bytes(b"Extra"),
+            PdfNumeric::from(added.object() as i64),
+        );
+        file.set_object(&IndirectData::new(1, 0), catalog);
+
+        let written = file.to_bytes().unwrap();
+        let reloaded = PdfFile::load(&written).unwrap();
+        assert_eq!(
+            reloaded.resolve(&second).unwrap().as_string(),
+            Ok(PdfString::from_raw_bytes(b"new"))
+        );
+        assert_eq!(
+            reloaded
+                .resolve(&IndirectData::new(3, 0))
+                .unwrap_err()
+                .kind(),
+            &PdfErrorKind::UnresolvedReference(3, 0)
+        );
+        assert_eq!(reloaded.catalog().unwrap().get_int(b"Extra"), Ok(Some(4)));
+        assert_eq!(
+            reloaded
+                .objects()
+                .iter()
+                .map(|r| r.object())
+                .collect::<Vec<_>>(),
+            [1, 2, 4]
+        );
+    }
+
+    #[test]
+    fn compressed() {
+        let bytes = test_file::build(
+            &[
+                "<< /Type /Catalog /Pages 2 0 R >>",
+                "<< /Type /Pages /Kids [] /Count 0 >>",
+                "<< /Length 3 >> stream\nabc\nendstream",
+                "(text)",
+                "[1 2 3 0 R]",
+            ],
+            "/Root 1 0 R /Info 4 0 R",
+        );
+        let file = PdfFile::load(&bytes).unwrap();
+        let options = WriteOptions {
+            objects_per_stream: 2,
+            compression_level: 9,
+            ..WriteOptions::default()
+        };
+        let written = file.to_bytes_with(&options).unwrap();
+        assert!(written.starts_with(b"%PDF-1.7\n"));
+
+        let text = String::from_utf8_lossy(&written);
+        let tail = &text[text.rfind("startxref").unwrap()..];
+        let startxref: usize = tail.lines().nth(1).unwrap().parse().unwrap();
+        let (xref, _) = parse::<PdfObject>(&written[startxref..]).unwrap();
+        // Objects 6 and 7 are the object streams, 8 the cross-reference stream itself
+        assert_eq!(xref.indirect(), Some(&IndirectData::new(8, 0)));
+        let xref = xref.as_stream().unwrap();
+        assert_eq!(
+            xref.info().get_name(b"Type").unwrap().unwrap().bytes(),
+            b"XRef"
+        );
+        assert_eq!(xref.info().get_int(b"Size"), Ok(Some(9)));
+        assert!(xref.info().get(b"Root").is_some() && xref.info().get(b"Info").is_some());
+        let widths: Vec<_> = xref
+            .info()
+            .get_array(b"W")
+            .unwrap()
+            .unwrap()
+            .as_numbers()
+            .unwrap()
+            .into_iter()
+            .map(|w| w as usize)
+            .collect();
+        // The generation of object 0, 65535, takes two bytes
+        assert_eq!(widths, [1, 2, 2]);
+
+        let reloaded = PdfFile::load(&written).unwrap();
+        assert_eq!(reloaded.trailer().get_int(b"Size"), Ok(Some(9)));
+        assert!(reloaded.trailer().get(b"W").is_none());
+        assert_eq!(reloaded.catalog(), file.catalog());
+        let table = reloaded.cross_ref_table();
+        for object in 1..=5 {
+            let reference = IndirectData::new(object, 0);
+            assert_eq!(reloaded.resolve(&reference), file.resolve(&reference));
+            // Streams cannot be put in object streams
+            assert_eq!(
+                matches!(
+                    table.location_of(object, 0),
+                    Some(ObjectLocation::Offset(_))
+                ),
+                object == 3
+            );
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt::{Debug, Display};
+
+use crate::{IndirectData, Parsable, ParsableRef, PdfObject, pdf_error::*};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Any deviation from the specification is an error.
+    #[default]
+    Strict,
+    /// Recover from common deviations, reporting each one as a [`ParseWarning`].
+    Lenient,
+}
+
+/// How documents are parsed.
+///
+/// A stream whose `/Length` is missing, cannot be resolved or does not end at `endstream` is an
+/// error in strict mode. In lenient mode, its data runs up to `endstream` instead, with a
+/// [`ParseWarningKind::WrongStreamLength`] or [`ParseWarningKind::UnresolvedStreamLength`]
+/// warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    pub mode: ParseMode,
+}
+
+impl ParseOptions {
+    pub fn strict() -> Self {
+        Self {
+            mode: ParseMode::Strict,
+        }
+    }
+    pub fn lenient() -> Self {
+        Self {
+            mode: ParseMode::Lenient,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarningKind {
+    /// The stream `/Length` did not match the position of `endstream`.
+    WrongStreamLength {
+        declared: usize,
+        actual: usize,
+    },
+    /// The stream `/Length` was missing or could not be resolved, `endstream` was used instead.
+    UnresolvedStreamLength {
+        actual: usize,
+    },
+    MissingEndobj,
+    /// Bytes found before `%PDF-`.
+    JunkBeforeHeader {
+        skipped: usize,
+    },
+    /// A literal string whose parentheses are not balanced, closed at the first `)`.
+    UnbalancedParentheses,
+    /// A cross-reference line ended by a lone carriage return.
+    LoneCarriageReturn,
+}
+
+/// A deviation from the specification recovered from in lenient mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    /// Absolute byte offset, when known.
+    pub offset: Option<usize>,
+    /// Indirect object being parsed, if any.
+    pub object: Option<(usize, usize)>,
+}
+
+impl Display for ParseWarningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseWarningKind::WrongStreamLength { declared, actual } => {
+                write!(f, "stream /Length is {declared} but data is {actual} bytes")
+            }
+            ParseWarningKind::UnresolvedStreamLength { actual } => {
+                write!(f, "stream /Length unavailable, data is {actual} bytes")
+            }
+            ParseWarningKind::MissingEndobj => write!(f, "missing `endobj`"),
+            ParseWarningKind::JunkBeforeHeader { skipped } => {
+                write!(f, "{skipped} bytes before `%PDF-`")
+            }
+            ParseWarningKind::UnbalancedParentheses => {
+                write!(f, "unbalanced parentheses in literal string")
+            }
+            ParseWarningKind::LoneCarriageReturn => {
+                write!(f, "cross-reference line ended by a lone `\\r`")
+            }
+        }
+    }
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(offset) = self.offset {
+            write!(f, " at byte {offset}")?;
+        }
+        if let Some((object, generation)) = self.object {
+            write!(f, " in object {object} {generation}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Looks indirect objects up, typically through the cross-reference table of a document.
+pub trait Resolve {
+    fn resolve(&self, reference: &IndirectData) -> PdfResult<PdfObject>;
+
+    /// Follows `object` if it is a reference, returns a copy of it otherwise.
+    fn deref(&self, object: &PdfObject) -> PdfResult<PdfObject> {
+        match object.as_reference() {
+            Some(reference) => self.resolve(reference),
+            None => Ok(object.clone()),
+        }
+    }
+}
+
+/// Parsing state shared by the [`Parsable`] implementations: options, collected warnings and
+/// the resolver used for indirect stream lengths.
+#[derive(Clone, Default)]
+pub struct Parser<'r> {
+    options: ParseOptions,
+    warnings: Vec<ParseWarning>,
+    resolver: Option<&'r dyn Resolve>,
+    // Absolute offset of the end of the input being parsed
+    end: usize,
+    object: Option<(usize, usize)>,
+}
+
+impl Debug for Parser<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Parser")
+            .field("options", &self.options)
+            .field("warnings", &self.warnings)
+            .field("resolver", &self.resolver.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'r> Parser<'r> {
+    pub fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+    pub fn with_resolver(mut self, resolver: &'r dyn Resolve) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+    pub fn strict() -> Self {
+        Self::new(ParseOptions::strict())
+    }
+    pub fn lenient() -> Self {
+        Self::new(ParseOptions::lenient())
+    }
+
+    pub fn options(&self) -> ParseOptions {
+        self.options
+    }
+    pub fn is_lenient(&self) -> bool {
+        self.options.mode == ParseMode::Lenient
+    }
+
+    /// Parses a `T` from `bytes`, which start `offset` bytes into the file.
+    pub fn parse<'a, T: Parsable>(
+        &mut self,
+        bytes: &'a [u8],
+        offset: usize,
+    ) -> PdfResult<(T, &'a [u8])> {
+        self.enter(bytes, offset);
+        T::from_bytes_with(bytes, self).map_err(|e| e.locate(bytes, offset))
+    }
+
+    /// Same as [`Parser::parse`] for a `T` borrowing from `bytes`.
+    pub fn parse_ref<'a, T: ParsableRef<'a>>(
+        &mut self,
+        bytes: &'a [u8],
+        offset: usize,
+    ) -> PdfResult<(T, &'a [u8])> {
+        self.enter(bytes, offset);
+        T::from_bytes_ref_with(bytes, self).map_err(|e| e.locate(bytes, offset))
+    }
+
+    /// Resolves `reference` with the resolver, failing if there is none.
+    pub fn resolve(&self, reference: &IndirectData) -> PdfResult<PdfObject> {
+        match self.resolver {
+            Some(resolver) => resolver.resolve(reference),
+            None => Err(PdfError::with_kind(PdfErrorKind::UnresolvedReference(
+                reference.object(),
+                reference.generation(),
+            ))),
+        }
+    }
+
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+    pub fn take_warnings(&mut self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Sets the input the next warnings are located in.
+    pub(crate) fn enter(&mut self, input: &[u8], offset: usize) {
+        self.end = offset + input.len();
+    }
+
+    /// Records a warning raised while looking at `bytes`, a suffix of the current input.
+    pub(crate) fn warn(&mut self, kind: ParseWarningKind, bytes: &[u8]) {
+        self.warnings.push(ParseWarning {
+            kind,
+            offset: self.end.checked_sub(bytes.len()),
+            object: self.object,
+        });
+    }
+
+    /// Records a warning at an absolute `offset`.
+    pub(crate) fn warn_at(&mut self, kind: ParseWarningKind, offset: usize) {
+        self.warnings.push(ParseWarning {
+            kind,
+            offset: Some(offset),
+            object: self.object,
+        });
+    }
+
+    /// Sets the indirect object being parsed, returning the previous one.
+    pub(crate) fn set_object(&mut self, object: Option<(usize, usize)>) -> Option<(usize, usize)> {
+        std::mem::replace(&mut self.object, object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PdfArray, PdfObject, PdfStream, parse};
+
+    use super::*;
+
+    #[test]
+    fn strict_rejects() {
+        let mut parser = Parser::strict();
+        let inputs: [&[u8]; 3] = [
+            b"1 0 obj <</Length 10>> stream\nabc\nendstream endobj",
+            b"1 0 obj 42 2 0 obj 43 endobj",
+            b"(a(b)",
+        ];
+        for input in inputs {
+            assert!(parser.parse::<PdfObject>(input, 0).is_err());
+        }
+        assert!(parser.warnings().is_empty());
+    }
+
+    #[test]
+    fn wrong_stream_length() {
+        let mut parser = Parser::lenient();
+        let input = b"1 0 obj <</Length 10>> stream\nabc\nendstream endobj";
+        let (stream, rest) = parser.parse::<PdfStream>(input, 100).unwrap();
+        assert_eq!(stream.len(), 3);
+        assert!(rest.is_empty());
+        assert_eq!(
+            parser.warnings(),
+            &[ParseWarning {
+                kind: ParseWarningKind::WrongStreamLength {
+                    declared: 10,
+                    actual: 3
+                },
+                offset: Some(130),
+                object: Some((1, 0)),
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_endobj() {
+        let mut parser = Parser::lenient();
+        let input = b"1 0 obj 42\n2 0 obj 43 endobj";
+        let (object, rest) = parser.parse::<PdfObject>(input, 0).unwrap();
+        assert_eq!(object.as_numeric_ref(), Ok(&42.into()));
+        assert_eq!(rest, b"2 0 obj 43 endobj");
+        let warnings = parser.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, ParseWarningKind::MissingEndobj);
+        assert_eq!(warnings[0].offset, Some(11));
+        assert!(parser.warnings().is_empty());
+    }
+
+    #[test]
+    fn unbalanced_parentheses() {
+        let mut parser = Parser::lenient();
+        let (array, _) = parser.parse::<PdfArray>(b"[(a(b) (c)]", 0).unwrap();
+        assert_eq!(Ok((array, &[] as &[u8])), parse(b"[(a\\(b) (c)]"));
+        assert_eq!(
+            parser.warnings()[0].kind,
+            ParseWarningKind::UnbalancedParentheses
+        );
+        assert_eq!(parser.warnings()[0].offset, Some(1));
+    }
+}
@@ -1,33 +1,82 @@
 mod parsable;
 mod pdf_array;
+mod pdf_date;
 mod pdf_dict;
+mod pdf_geometry;
 mod pdf_name;
 mod pdf_null;
 mod pdf_num;
 mod pdf_str;
 mod pdf_stream;
+mod pdf_text;
+mod writable;
 use crate::{PdfError, PdfErrorKind};
 pub use parsable::*;
 use paste::paste;
 pub use pdf_array::*;
+pub use pdf_date::*;
 pub use pdf_dict::*;
+pub use pdf_geometry::*;
 pub use pdf_name::*;
 pub use pdf_null::*;
 pub use pdf_num::*;
 pub use pdf_str::*;
 pub use pdf_stream::*;
+pub use writable::*;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct IndirectData {
     object: usize,
     generation: usize,
 }
+impl IndirectData {
+    pub fn new(object: usize, generation: usize) -> Self {
+        Self { object, generation }
+    }
+    pub fn object(&self) -> usize {
+        self.object
+    }
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
 #[derive(Debug, PartialEq, Clone)]
 pub struct PdfObject {
     kind: PdfObjectKind,
     indirect: Option<IndirectData>,
 }
 
+impl PdfObject {
+    /// Reference to another object, as written `object generation R`.
+    pub fn reference(object: usize, generation: usize) -> Self {
+        Self {
+            kind: PdfObjectKind::Ref,
+            indirect: Some(IndirectData { object, generation }),
+        }
+    }
+    /// The same value as the indirect object `indirect`.
+    pub(crate) fn into_indirect(mut self, indirect: IndirectData) -> Self {
+        self.indirect = Some(indirect);
+        self
+    }
+    pub fn kind(&self) -> &PdfObjectKind {
+        &self.kind
+    }
+    /// Object and generation numbers of an indirect object.
+    pub fn indirect(&self) -> Option<&IndirectData> {
+        self.indirect
+            .as_ref()
+            .filter(|_| self.kind != PdfObjectKind::Ref)
+    }
+    /// Target of a reference, `None` for any other object.
+    pub fn as_reference(&self) -> Option<&IndirectData> {
+        match self.kind {
+            PdfObjectKind::Ref => self.indirect.as_ref(),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum PdfObjectKind {
     Boolean(bool),
@@ -68,6 +117,16 @@ from_impl!(PdfDict, Dict); //
 from_impl!(PdfStream, Stream); //
 from_impl!(PdfNull, Null); //
 
+/// A reference to the object.
+impl From<IndirectData> for PdfObject {
+    fn from(reference: IndirectData) -> Self {
+        Self {
+            kind: PdfObjectKind::Ref,
+            indirect: Some(reference),
+        }
+    }
+}
+
 macro_rules! as_indirect_impl {
     ($($F:ty)+) => {$(
         impl $F {
@@ -104,6 +163,12 @@ macro_rules! as_kind_impl {
                         _=>Err(PdfError::with_kind(PdfErrorKind::WrongType)),
                     }
                 }
+                pub fn [<as_ $K:lower _mut>](&mut self) -> Result<&mut [<Pdf $K>],PdfError> {
+                    match &mut self.kind{
+                        PdfObjectKind::$K(inner) => Ok(inner),
+                        _=>Err(PdfError::with_kind(PdfErrorKind::WrongType)),
+                    }
+                }
             }
         )+}
     };
@@ -113,7 +178,7 @@ as_kind_impl!(
 );
 
 pub(crate) const WHITESPACES: [u8; 6] = *b"\x00\t\n\x0c\r ";
-pub(crate) const EOLS: [u8; 2] = [b'\n', b'\r'];
+pub(crate) const EOLS: [u8; 2] = *b"\n\r";
 pub(crate) const DELIMITERS: [u8; 10] = *b"()<>[]{}/%";
 pub(crate) fn is_regular(byte: u8) -> bool {
     !WHITESPACES.contains(&byte) && !DELIMITERS.contains(&byte)
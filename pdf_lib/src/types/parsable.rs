@@ -1,35 +1,74 @@
 use core::str;
 
-use crate::pdf_error::*;
+use crate::{ParseWarningKind, Parser, pdf_error::*};
 
 use super::*;
 
+/// Implementors provide at least one of the two methods.
 pub trait Parsable: Sized {
-    fn from_bytes(bytes: &[u8]) -> PdfResult<(Self, &[u8])>;
+    fn from_bytes(bytes: &[u8]) -> PdfResult<(Self, &[u8])> {
+        Self::from_bytes_with(bytes, &mut Parser::strict())
+    }
+    /// Same as [`Parsable::from_bytes`], following the options of `parser` and reporting warnings to it.
+    fn from_bytes_with<'a>(bytes: &'a [u8], parser: &mut Parser) -> PdfResult<(Self, &'a [u8])> {
+        let _ = parser;
+        Self::from_bytes(bytes)
+    }
+}
+
+/// Parsing into values borrowing from the input instead of copying it, see [`Parsable`].
+pub trait ParsableRef<'a>: Sized {
+    fn from_bytes_ref(bytes: &'a [u8]) -> PdfResult<(Self, &'a [u8])> {
+        Self::from_bytes_ref_with(bytes, &mut Parser::strict())
+    }
+    fn from_bytes_ref_with(bytes: &'a [u8], parser: &mut Parser) -> PdfResult<(Self, &'a [u8])> {
+        let _ = parser;
+        Self::from_bytes_ref(bytes)
+    }
 }
 
+/// Parses a `T` borrowing from `bytes`, error offsets being relative to `bytes`.
+pub fn parse_ref<'a, T>(bytes: &'a [u8]) -> PdfResult<(T, &'a [u8])>
+where
+    T: ParsableRef<'a>,
+{
+    T::from_bytes_ref(bytes).map_err(|e| e.locate(bytes, 0))
+}
+
+/// Parses a `T` from the start of `bytes`, error offsets being relative to `bytes`.
 pub fn parse<T>(bytes: &[u8]) -> PdfResult<(T, &[u8])>
 where
     T: Parsable,
 {
-    T::from_bytes(bytes)
+    parse_at(bytes, 0)
+}
+
+/// Parses a `T` from `bytes`, which start `offset` bytes into the file.
+pub fn parse_at<T>(bytes: &[u8], offset: usize) -> PdfResult<(T, &[u8])>
+where
+    T: Parsable,
+{
+    T::from_bytes(bytes).map_err(|e| e.locate(bytes, offset))
 }
 
 impl Parsable for PdfObject {
-    fn from_bytes(bytes: &[u8]) -> PdfResult<(Self, &[u8])> {
+    fn from_bytes_with<'a>(bytes: &'a [u8], parser: &mut Parser) -> PdfResult<(Self, &'a [u8])> {
         match bytes {
-            [b'<', b'<', ..] => parse::<PdfDict>(bytes).map(|(o, b)| (o.into(), b)),
-            [b'[', ..] => parse::<PdfArray>(bytes).map(|(o, b)| (o.into(), b)),
-            [b'(' | b'<', ..] => parse::<PdfString>(bytes).map(|(o, b)| (o.into(), b)),
-            [b'/', ..] => parse::<PdfName>(bytes).map(|(o, b)| (o.into(), b)),
-            [b'f' | b't', ..] => parse::<bool>(bytes).map(|(o, b)| (o.into(), b)),
-            [b'n', ..] => parse::<PdfNull>(bytes).map(|(o, b)| (o.into(), b)),
+            [b'<', b'<', ..] => PdfDict::from_bytes_with(bytes, parser).map(|(o, b)| (o.into(), b)),
+            [b'[', ..] => PdfArray::from_bytes_with(bytes, parser).map(|(o, b)| (o.into(), b)),
+            [b'(' | b'<', ..] => {
+                PdfString::from_bytes_with(bytes, parser).map(|(o, b)| (o.into(), b))
+            }
+            [b'/', ..] => PdfName::from_bytes(bytes).map(|(o, b)| (o.into(), b)),
+            [b'f' | b't', ..] => bool::from_bytes(bytes).map(|(o, b)| (o.into(), b)),
+            [b'n', ..] => PdfNull::from_bytes(bytes).map(|(o, b)| (o.into(), b)),
             _ => {
                 // Handle Number, indirect object and ref
-                let indirect = parse_indirect(bytes);
-                let indirect_or_num =
-                    indirect.or_else(|_e| parse::<PdfNumeric>(bytes).map(|(o, b)| (o.into(), b)));
-                indirect_or_num
+                if indirect_header(bytes).is_some() {
+                    parse_indirect(bytes, parser)
+                } else {
+                    PdfNumeric::from_bytes(bytes).map(|(o, b)| (o.into(), b))
+                }
             }
         }
     }
@@ -46,11 +85,9 @@ impl Parsable for bool {
         }
         .and_then(|r| match r.1.len() {
             0 => Some(r),
-            _ => WHITESPACES.contains(&r.1[0]).then_some(r),
-        })
-        .ok_or(PdfError {
-            kind: PdfErrorKind::Parse,
+            _ => (!is_regular(r.1[0])).then_some(r),
         })
+        .ok_or_else(|| PdfError::parse_at(bytes, "`true` or `false`"))
     }
 }
 
@@ -60,8 +97,8 @@ mod test_bool {
 
     #[test]
     fn parsing() {
-        let valid: [&[u8]; 3] = [b"true ", b"false", b"false "];
-        let expected = [(true, 4), (false, 5), (false, 5)];
+        let valid: [&[u8]; 4] = [b"true ", b"false", b"false ", b"true]"];
+        let expected = [(true, 4), (false, 5), (false, 5), (true, 4)];
         for (bytes, (res, rest)) in valid.into_iter().zip(expected) {
             let parsed = parse(bytes);
             assert_eq!(parsed, Ok((res, &bytes[rest..])));
@@ -73,93 +110,194 @@ mod test_bool {
         }
     }
 }
-#[derive(Clone)]
-struct Whitespace {
-    _bytes: Vec<u8>,
-}
-impl Parsable for Whitespace {
-    fn from_bytes(mut bytes: &[u8]) -> PdfResult<(Self, &[u8])> {
-        let mut data = Vec::new();
-        while bytes.first().is_some_and(|b| WHITESPACES.contains(b)) {
-            data.push(bytes[0]);
-            bytes = &bytes[1..];
+
+/// Reads the `object generation obj` or `object generation R` prefix of an indirect object or reference.
+///
+/// Returns the numbers, whether it is a reference and the bytes after the keyword.
+pub(crate) fn indirect_header(bytes: &[u8]) -> Option<(IndirectData, bool, &[u8])> {
+    fn integer(bytes: &[u8]) -> Option<(usize, &[u8])> {
+        let end = bytes
+            .iter()
+            .position(|b| !b.is_ascii_digit())
+            .unwrap_or(bytes.len());
+        let n = str::from_utf8(&bytes[..end]).ok()?.parse().ok()?;
+        Some((n, &bytes[end..]))
+    }
+    let (object, rest) = integer(bytes)?;
+    if !rest.first().is_some_and(|b| WHITESPACES.contains(b)) {
+        return None;
+    }
+    let (generation, rest) = integer(strip_whitespace(rest))?;
+    if !rest.first().is_some_and(|b| WHITESPACES.contains(b)) {
+        return None;
+    }
+    let rest = strip_whitespace(rest);
+    let indirect = IndirectData { object, generation };
+    match rest {
+        [b'R', ..] if rest.get(1).is_none_or(|b| !is_regular(*b)) => {
+            Some((indirect, true, &rest[1..]))
         }
-        Ok((Self { _bytes: data }, bytes))
+        [b'o', b'b', b'j', ..] if rest.get(3).is_none_or(|b| !is_regular(*b)) => {
+            Some((indirect, false, &rest[3..]))
+        }
+        _ => None,
     }
 }
 
-pub(crate) fn parse_indirect(mut bytes: &[u8]) -> PdfResult<(PdfObject, &[u8])> {
-    let e = Err(PdfError::with_kind(PdfErrorKind::Parse));
-    let Some(first_space) = bytes.iter().position(|b| WHITESPACES.contains(b)) else {
-        e?
-    };
-    let o = usize::from_str_radix(str::from_utf8(&bytes[..first_space])?, 10)?;
-    bytes = strip_whitespace(&bytes[first_space..]);
-    let Some(second_space) = bytes.iter().position(|b| WHITESPACES.contains(b)) else {
-        e?
-    };
-    let g = usize::from_str_radix(str::from_utf8(&bytes[..second_space])?, 10)?;
-    let indirect = IndirectData {
-        object: o,
-        generation: g,
+pub(crate) fn parse_indirect<'a>(
+    bytes: &'a [u8],
+    parser: &mut Parser,
+) -> PdfResult<(PdfObject, &'a [u8])> {
+    let Some((indirect, is_ref, rest)) = indirect_header(bytes) else {
+        return Err(PdfError::parse_at(bytes, "indirect object or reference"));
     };
-    bytes = strip_whitespace(&bytes[first_space..]);
-    if !bytes.starts_with(b"obj") {
-        if bytes[0] == b'R' {
-            return Ok((
-                PdfObject {
-                    kind: PdfObjectKind::Ref,
-                    indirect: Some(indirect),
-                },
-                &bytes[1..],
-            ));
-        }
-        e?
+    if is_ref {
+        return Ok((
+            PdfObject {
+                kind: PdfObjectKind::Ref,
+                indirect: Some(indirect),
+            },
+            rest,
+        ));
     }
-    bytes = strip_whitespace(&bytes[3..]);
-    let (mut object, rest) = parse::<PdfObject>(bytes)?;
-    bytes = strip_whitespace(rest);
+    let (body, rest) = parse_indirect_body(rest, &indirect, parser)?;
+    let object = match body {
+        IndirectBody::Object(mut object) => {
+            object.indirect = Some(indirect);
+            object
+        }
+        IndirectBody::Stream(dict, data) => {
+            PdfStream::with_len(dict, data.len(), data.to_vec()).as_indirect(indirect)
+        }
+    };
+    Ok((object, rest))
+}
+
+/// Content of an indirect object, stream data still pointing into the input.
+pub(crate) enum IndirectBody<'a> {
+    Object(PdfObject),
+    Stream(PdfDict, &'a [u8]),
+}
+
+/// Parses what follows `object generation obj`, up to and including `endobj`.
+pub(crate) fn parse_indirect_body<'a>(
+    bytes: &'a [u8],
+    indirect: &IndirectData,
+    parser: &mut Parser,
+) -> PdfResult<(IndirectBody<'a>, &'a [u8])> {
+    let IndirectData { object, generation } = *indirect;
+    let outer = parser.set_object(Some((object, generation)));
+    let parsed = parse_indirect_content(bytes, parser);
+    parser.set_object(outer);
+    parsed.map_err(|e| e.in_object(object, generation))
+}
+
+fn parse_indirect_content<'a>(
+    bytes: &'a [u8],
+    parser: &mut Parser,
+) -> PdfResult<(IndirectBody<'a>, &'a [u8])> {
+    let bytes = strip_whitespace(bytes);
+    let (object, rest) = PdfObject::from_bytes_with(bytes, parser)?;
+    let mut bytes = strip_whitespace(rest);
     if bytes.starts_with(b"endobj") {
         bytes = &bytes[6..];
-        object.indirect = Some(indirect);
-        Ok((object, bytes))
+        Ok((IndirectBody::Object(object), bytes))
     } else if bytes.starts_with(b"stream") {
+        let stream_keyword = bytes;
         bytes = &bytes[6..];
         bytes = match bytes {
             [b'\n', ..] => &bytes[1..],
             [b'\r', b'\n', ..] => &bytes[2..],
-            _ => e?,
+            _ => return Err(PdfError::parse_at(bytes, "end of line after `stream`")),
         };
         let dict = object.as_dict()?;
-        let stream_length: i32 = dict
-            .get_or_null(&PdfName::from_raw_bytes(b"Length"))
-            .as_numeric_ref()?
-            .into();
-        let len = stream_length as usize;
-        if bytes.len() < len {
-            e?
+        let declared = stream_length(&dict, parser).map_err(|e| e.at(stream_keyword));
+        let data_start = bytes;
+        let after_data = |len: usize| bytes.get(len..).map(strip_whitespace);
+        let len = match declared {
+            Ok(len) if after_data(len).is_some_and(|after| after.starts_with(b"endstream")) => len,
+            _ if parser.is_lenient() => {
+                let Some(actual) = find_endstream(data_start) else {
+                    return Err(PdfError::parse_at(data_start, "`endstream`").input_ended());
+                };
+                let warning = match declared {
+                    Ok(declared) => ParseWarningKind::WrongStreamLength { declared, actual },
+                    Err(_) => ParseWarningKind::UnresolvedStreamLength { actual },
+                };
+                parser.warn(warning, data_start);
+                actual
+            }
+            Err(e) => return Err(e),
+            Ok(len) => {
+                return Err(match after_data(len) {
+                    Some(after) => PdfError::parse_at(after, "`endstream`"),
+                    None => PdfError::with_kind(PdfErrorKind::InvalidData)
+                        .at(bytes)
+                        .expecting("stream data of /Length bytes")
+                        .input_ended(),
+                });
+            }
         };
-        let mut data = Vec::with_capacity(stream_length as usize);
-        data.extend_from_slice(&bytes[..len]);
+        let data = &bytes[..len];
         bytes = strip_whitespace(&bytes[len..]);
-        if !bytes.starts_with(b"endstream") {
-            e?
-        }
         bytes = strip_whitespace(&bytes[9..]);
-        if !bytes.starts_with(b"endobj") {
-            e?
+        bytes = end_indirect(bytes, parser)?;
+
+        Ok((IndirectBody::Stream(dict, data), bytes))
+    } else {
+        let bytes = end_indirect(bytes, parser).map_err(|e| e.expecting("`endobj` or `stream`"))?;
+        Ok((IndirectBody::Object(object), bytes))
+    }
+}
+
+/// Reads the `/Length` of a stream dictionary, resolving it through `parser` when indirect.
+fn stream_length(dict: &PdfDict, parser: &Parser) -> PdfResult<usize> {
+    let length = dict
+        .get(b"Length")
+        .ok_or_else(|| PdfError::with_kind(PdfErrorKind::MissingStreamLength))?;
+    let resolved;
+    let length = match length.as_reference() {
+        Some(reference) => {
+            resolved = parser.resolve(reference)?;
+            &resolved
         }
-        bytes = &bytes[6..];
+        None => length,
+    };
+    length
+        .as_numeric_ref()
+        .and_then(PdfNumeric::to_usize)
+        .map_err(|e| e.expecting("a non-negative integer /Length"))
+}
 
-        Ok((
-            PdfStream::with_len(dict, len, data).as_indirect(indirect),
-            bytes,
-        ))
+/// Consumes the `endobj` keyword, tolerating its absence in lenient mode when another object or section follows.
+fn end_indirect<'a>(bytes: &'a [u8], parser: &mut Parser) -> PdfResult<&'a [u8]> {
+    if bytes.starts_with(b"endobj") {
+        return Ok(&bytes[6..]);
+    }
+    let next_section = [b"xref" as &[u8], b"trailer", b"startxref"]
+        .iter()
+        .any(|k| bytes.starts_with(k));
+    if parser.is_lenient()
+        && (bytes.is_empty() || next_section || indirect_header(bytes).is_some_and(|h| !h.1))
+    {
+        parser.warn(ParseWarningKind::MissingEndobj, bytes);
+        Ok(bytes)
     } else {
-        e?
+        Err(PdfError::parse_at(bytes, "`endobj`"))
     }
 }
 
+/// Length of the stream data starting at `data`, found from the following `endstream` keyword.
+pub(crate) fn find_endstream(data: &[u8]) -> Option<usize> {
+    let keyword = data.windows(9).position(|w| w == b"endstream")?;
+    let len = match &data[..keyword] {
+        [.., b'\r', b'\n'] => keyword - 2,
+        [.., b'\r' | b'\n'] => keyword - 1,
+        _ => keyword,
+    };
+    Some(len)
+}
+
 pub(crate) fn strip_whitespace(mut bytes: &[u8]) -> &[u8] {
     while bytes.first().is_some_and(|b| WHITESPACES.contains(b)) {
         bytes = &bytes[1..];
@@ -177,3 +315,86 @@ pub(crate) fn next_eol(mut bytes: &[u8]) -> &[u8] {
         [] => bytes,
     }
 }
+
+#[cfg(test)]
+mod test_indirect {
+    use crate::{
+        ParseWarningKind, Parser, PdfDict, PdfErrorKind, PdfNumeric, PdfObject, PdfStream, parse,
+    };
+
+    #[test]
+    fn parsing() {
+        let parsed = parse::<PdfObject>(b"1 10 obj\n42\nendobj");
+        assert_eq!(
+            parsed,
+            Ok((PdfNumeric::PdfInt(42).as_indirect_raw(1, 10), &[] as &[u8]))
+        );
+        let (stream, rest) =
+            parse::<PdfStream>(b"7 0 obj <</Length 3>> stream\r\nabc\nendstream endobj ").unwrap();
+        assert_eq!(stream.len(), 3);
+        assert_eq!(rest, b" ");
+        let numbers = parse::<PdfObject>(b"1 2 3");
+        assert_eq!(
+            numbers,
+            Ok((PdfNumeric::PdfInt(1).into(), b" 2 3" as &[u8]))
+        );
+    }
+
+    #[test]
+    fn indirect_length() {
+        let err = parse::<PdfStream>(b"1 0 obj <</Length 2 0 R>> stream\nabc\nendstream endobj")
+            .unwrap_err();
+        assert_eq!(err.kind(), &PdfErrorKind::UnresolvedReference(2, 0));
+        assert_eq!(err.offset(), Some(26));
+
+        let mut parser = Parser::lenient();
+        let input = b"1 0 obj <</Length 2 0 R>> stream\nabc\nendstream endobj";
+        let (stream, _) = parser.parse::<PdfStream>(input, 0).unwrap();
+        assert_eq!(stream.len(), 3);
+        assert_eq!(
+            parser.warnings()[0].kind,
+            ParseWarningKind::UnresolvedStreamLength { actual: 3 }
+        );
+    }
+
+    #[test]
+    fn wrong_length() {
+        let input = b"1 0 obj <</Length 5>> stream\nabc\nendstream endobj";
+        let err = parse::<PdfStream>(input).unwrap_err();
+        assert_eq!(err.kind(), &PdfErrorKind::Parse);
+        assert_eq!(err.offset(), Some(34));
+
+        let mut parser = Parser::lenient();
+        let (stream, _) = parser.parse::<PdfStream>(input, 0).unwrap();
+        assert_eq!(stream.len(), 3);
+        assert_eq!(
+            parser.warnings()[0].kind,
+            ParseWarningKind::WrongStreamLength {
+                declared: 5,
+                actual: 3
+            }
+        );
+
+        let mut parser = Parser::lenient();
+        let input = b"3 0 obj <<>> stream\nabc\nendstream endobj";
+        let (stream, _) = parser.parse::<PdfStream>(input, 0).unwrap();
+        assert_eq!(stream.len(), 3);
+        assert_eq!(
+            parser.warnings()[0].kind,
+            ParseWarningKind::UnresolvedStreamLength { actual: 3 }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = parse::<PdfObject>(b"12 0 obj\n(unterminated\n").unwrap_err();
+        assert_eq!(err.object(), Some((12, 0)));
+        assert_eq!(err.offset(), Some(9));
+
+        let err = parse::<PdfObject>(b"3 0 obj <<>> stream\nabc\nendstream endobj").unwrap_err();
+        assert_eq!(err.kind(), &PdfErrorKind::MissingStreamLength);
+        assert_eq!(err.offset(), Some(13));
+
+        assert!(parse::<PdfDict>(b"<</A 1 0 obj 2 endobj>>").is_ok());
+    }
+}
@@ -1,28 +1,131 @@
-use crate::{
-    Parsable, parse,
-    pdf_error::{PdfError, PdfErrorKind},
-};
+use std::ops::Index;
 
-use super::{PdfObject, strip_whitespace};
+use crate::{Parsable, Parser, pdf_error::*};
 
-#[derive(Debug, PartialEq, Clone)]
+use super::{PdfNumeric, PdfObject, strip_whitespace};
+
+#[derive(Debug, PartialEq, Clone, Default)]
 pub struct PdfArray {
     data: Vec<PdfObject>,
 }
 
+impl PdfArray {
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub fn get(&self, index: usize) -> Option<&PdfObject> {
+        self.data.get(index)
+    }
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut PdfObject> {
+        self.data.get_mut(index)
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, PdfObject> {
+        self.data.iter()
+    }
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, PdfObject> {
+        self.data.iter_mut()
+    }
+
+    pub fn push(&mut self, value: impl Into<PdfObject>) {
+        self.data.push(value.into());
+    }
+    /// Inserts `value` at `index`, shifting the following elements. Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: impl Into<PdfObject>) {
+        self.data.insert(index, value.into());
+    }
+    /// Removes the element at `index`, shifting the following elements. Panics if out of bounds.
+    pub fn remove(&mut self, index: usize) -> PdfObject {
+        self.data.remove(index)
+    }
+
+    /// Array of numbers, integral values being written as integers.
+    pub fn from_numbers(numbers: &[f64]) -> Self {
+        numbers.iter().map(|n| number(*n)).collect()
+    }
+    /// The elements as numbers, failing with [`PdfErrorKind::WrongType`] if one is not a number.
+    pub fn as_numbers(&self) -> PdfResult<Vec<f64>> {
+        self.iter().map(|o| o.as_numeric_ref()?.to_f64()).collect()
+    }
+    /// The elements as exactly `N` numbers.
+    pub(crate) fn as_number_array<const N: usize>(&self) -> PdfResult<[f64; N]> {
+        self.as_numbers()?.try_into().map_err(|_| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("an array of numbers")
+        })
+    }
+}
+
+fn number(n: f64) -> PdfObject {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        PdfNumeric::PdfInt(n as i64).into()
+    } else {
+        PdfNumeric::PdfReal(n).into()
+    }
+}
+
+impl Index<usize> for PdfArray {
+    type Output = PdfObject;
+
+    fn index(&self, index: usize) -> &PdfObject {
+        &self.data[index]
+    }
+}
+
+impl From<Vec<PdfObject>> for PdfArray {
+    fn from(data: Vec<PdfObject>) -> Self {
+        Self { data }
+    }
+}
+
+impl<T: Into<PdfObject>> FromIterator<T> for PdfArray {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            data: iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl IntoIterator for PdfArray {
+    type Item = PdfObject;
+    type IntoIter = std::vec::IntoIter<PdfObject>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PdfArray {
+    type Item = &'a PdfObject;
+    type IntoIter = std::slice::Iter<'a, PdfObject>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
 impl Parsable for PdfArray {
-    fn from_bytes(mut bytes: &[u8]) -> Result<(Self, &[u8]), crate::pdf_error::PdfError> {
-        if !matches!(bytes.get(0), Some(b'[')) {
-            return Err(PdfError::with_kind(PdfErrorKind::Parse));
+    fn from_bytes_with<'a>(
+        mut bytes: &'a [u8],
+        parser: &mut Parser,
+    ) -> PdfResult<(Self, &'a [u8])> {
+        if bytes.first() != Some(&b'[') {
+            return Err(PdfError::parse_at(bytes, "`[`"));
         }
-        bytes = &bytes[1..];
+        bytes = strip_whitespace(&bytes[1..]);
         let mut data = Vec::new();
-        while let Ok((obj, b)) = parse::<PdfObject>(bytes) {
+        while !bytes.is_empty() && bytes[0] != b']' {
+            let (obj, b) = PdfObject::from_bytes_with(bytes, parser)?;
             data.push(obj);
             bytes = strip_whitespace(b);
         }
-        if !matches!(bytes.get(0), Some(b']')) {
-            return Err(PdfError::with_kind(PdfErrorKind::Parse));
+        if bytes.first() != Some(&b']') {
+            return Err(PdfError::parse_at(bytes, "`]`"));
         }
         bytes = &bytes[1..];
         Ok((Self { data }, bytes))
@@ -37,8 +140,10 @@ mod test {
     };
 
     use super::PdfArray;
+    use crate::{PdfErrorKind, PdfObject};
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn parsing() {
         let examples = ["[549 3.14 true (Ralph) /SomeName null 12 0 obj <FF> endobj]"];
         let expected = [PdfArray {
@@ -58,4 +163,33 @@ mod test {
             assert_eq!(parsed, Ok((o, &[] as &[u8])))
         }
     }
+
+    #[test]
+    fn collection() {
+        let mut array: PdfArray = [1, 2].map(PdfNumeric::PdfInt).into_iter().collect();
+        array.push(PdfName::from_raw_bytes(b"N"));
+        array.insert(0, true);
+        assert_eq!(array.len(), 4);
+        assert_eq!(array.remove(1), PdfNumeric::PdfInt(1).into());
+        assert_eq!(array[0], true.into());
+        assert_eq!(array.get(2), Some(&PdfName::from_raw_bytes(b"N").into()));
+        assert!(array.get(3).is_none());
+        let elements: Vec<PdfObject> = array.clone().into_iter().collect();
+        assert_eq!(PdfArray::from(elements), array);
+        assert_eq!((&array).into_iter().count(), 3);
+    }
+
+    #[test]
+    fn numbers() {
+        let array = PdfArray::from_numbers(&[0.0, 1.5, -3.0]);
+        assert_eq!(array[0], PdfNumeric::PdfInt(0).into());
+        assert_eq!(array[1], PdfNumeric::PdfReal(1.5).into());
+        assert_eq!(array.as_numbers(), Ok(vec![0.0, 1.5, -3.0]));
+
+        let (mixed, _) = parse::<PdfArray>(b"[1 /Two]").unwrap();
+        assert_eq!(
+            mixed.as_numbers().unwrap_err().kind(),
+            &PdfErrorKind::WrongType
+        );
+    }
 }
@@ -0,0 +1,460 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::pdf_error::*;
+
+use super::{PdfDict, PdfString};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Date written `D:YYYYMMDDHHmmSSOHH'mm'` (7.9.4).
+///
+/// Every field after the year may be omitted, taking its smallest value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PdfDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Offset from UTC in minutes, `None` when the date does not tell.
+    pub utc_offset: Option<i16>,
+}
+
+impl PdfDate {
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> PdfResult<Self> {
+        let date = Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            utc_offset: None,
+        };
+        date.validate()?;
+        Ok(date)
+    }
+
+    pub fn with_utc_offset(mut self, minutes: i16) -> Self {
+        self.utc_offset = Some(minutes);
+        self
+    }
+
+    /// The date `seconds` after 1970-01-01 00:00:00 UTC, in UTC.
+    pub fn from_timestamp(seconds: i64) -> Self {
+        let days = seconds.div_euclid(SECONDS_PER_DAY);
+        let time = seconds.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year: year.clamp(0, 9999) as u16,
+            month,
+            day,
+            hour: (time / 3600) as u8,
+            minute: (time / 60 % 60) as u8,
+            second: (time % 60) as u8,
+            utc_offset: Some(0),
+        }
+    }
+
+    /// Seconds since 1970-01-01 00:00:00 UTC, a date without offset being taken as UTC.
+    pub fn timestamp(&self) -> i64 {
+        let days = days_from_civil(i64::from(self.year), self.month, self.day);
+        let seconds = i64::from(self.hour) * 3600 + i64::from(self.minute) * 60;
+        let offset = i64::from(self.utc_offset.unwrap_or(0)) * 60;
+        days * SECONDS_PER_DAY + seconds + i64::from(self.second) - offset
+    }
+
+    /// The current date, in UTC.
+    pub fn now() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self::from_timestamp(i64::try_from(seconds).unwrap_or(i64::MAX))
+    }
+
+    /// Parses a date in the ISO 8601 profile used by XMP, `YYYY[-MM[-DD[Thh:mm[:ss[.s]]TZD]]]`.
+    ///
+    /// Fractions of seconds are dropped.
+    pub fn from_iso8601(s: &str) -> PdfResult<Self> {
+        let invalid =
+            || PdfError::with_kind(PdfErrorKind::InvalidData).expecting("an ISO 8601 date");
+        let mut bytes = s.trim().as_bytes();
+        let mut date = Self {
+            year: digits(&mut bytes, 4).ok_or_else(invalid)?,
+            month: 1,
+            day: 1,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            utc_offset: None,
+        };
+        // A separator followed by two digits, consumed only if both are there
+        let field = |bytes: &mut &[u8], separator: u8| {
+            let mut rest = bytes.strip_prefix(&[separator])?;
+            let value = digits(&mut rest, 2)?;
+            *bytes = rest;
+            Some(value as u8)
+        };
+        if let Some(month) = field(&mut bytes, b'-') {
+            date.month = month;
+            if let Some(day) = field(&mut bytes, b'-') {
+                date.day = day;
+                if let Some(hour) = field(&mut bytes, b'T') {
+                    date.hour = hour;
+                    date.minute = field(&mut bytes, b':').ok_or_else(invalid)?;
+                    if let Some(second) = field(&mut bytes, b':') {
+                        date.second = second;
+                        if let Some(fraction) = bytes.strip_prefix(b".") {
+                            let len = fraction.iter().take_while(|b| b.is_ascii_digit()).count();
+                            bytes = &fraction[len..];
+                        }
+                    }
+                    date.utc_offset = match bytes {
+                        [b'Z', rest @ ..] => {
+                            bytes = rest;
+                            Some(0)
+                        }
+                        [sign @ (b'+' | b'-'), rest @ ..] => {
+                            let sign = if *sign == b'-' { -1 } else { 1 };
+                            bytes = rest;
+                            let hours = digits(&mut bytes, 2).ok_or_else(invalid)?;
+                            let minutes = field(&mut bytes, b':').ok_or_else(invalid)?;
+                            Some(sign * (hours * 60 + u16::from(minutes)) as i16)
+                        }
+                        _ => None,
+                    };
+                }
+            }
+        }
+        if !bytes.is_empty() {
+            return Err(invalid());
+        }
+        date.validate()?;
+        Ok(date)
+    }
+
+    /// The date as written in XMP, `YYYY-MM-DDThh:mm:ss` followed by the offset if known.
+    pub fn to_iso8601(&self) -> String {
+        let mut s = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        );
+        match self.utc_offset {
+            None => {}
+            Some(0) => s.push('Z'),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                s.push_str(&format!("{sign}{:02}:{:02}", offset / 60, offset % 60));
+            }
+        }
+        s
+    }
+
+    fn validate(&self) -> PdfResult<()> {
+        let valid = self.year <= 9999
+            && (1..=12).contains(&self.month)
+            && (1..=days_in_month(self.year, self.month)).contains(&self.day)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+            && self.utc_offset.is_none_or(|o| o.abs() < 24 * 60);
+        if valid {
+            Ok(())
+        } else {
+            Err(PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a valid date"))
+        }
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Conversions between dates and days since 1970-01-01, after Howard Hinnant's `days_from_civil`
+// and `civil_from_days`
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Reads `len` digits at the start of `s`, `None` if there are fewer.
+fn digits(s: &mut &[u8], len: usize) -> Option<u16> {
+    let field = s.get(..len).filter(|f| f.iter().all(u8::is_ascii_digit))?;
+    *s = &s[len..];
+    Some(field.iter().fold(0, |n, d| n * 10 + u16::from(d - b'0')))
+}
+
+impl FromStr for PdfDate {
+    type Err = PdfError;
+
+    /// Parses a date, also accepting the common deviations: a missing `D:` prefix, offsets
+    /// without apostrophes or with a colon, a `Z` followed by zeros, trailing garbage after the
+    /// offset, and years written `19100` for 2000 by software adding 1900 as a string.
+    fn from_str(s: &str) -> PdfResult<Self> {
+        let invalid = || PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a date");
+        let s = s.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+        let mut bytes = s.strip_prefix("D:").unwrap_or(s).as_bytes();
+
+        let digit_run = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+        let year = if bytes.starts_with(b"191") && (digit_run == 5 || digit_run == 15) {
+            bytes = &bytes[2..];
+            1900 + digits(&mut bytes, 3).ok_or_else(invalid)?
+        } else {
+            digits(&mut bytes, 4).ok_or_else(invalid)?
+        };
+        let mut date = Self {
+            year,
+            month: 1,
+            day: 1,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            utc_offset: None,
+        };
+        for field in [
+            &mut date.month,
+            &mut date.day,
+            &mut date.hour,
+            &mut date.minute,
+            &mut date.second,
+        ] {
+            match digits(&mut bytes, 2) {
+                Some(value) => *field = value as u8,
+                None => break,
+            }
+        }
+
+        date.utc_offset = match bytes.first() {
+            None => None,
+            Some(b'Z' | b'z') => Some(0),
+            Some(sign @ (b'+' | b'-')) => {
+                let sign = if *sign == b'-' { -1 } else { 1 };
+                bytes = &bytes[1..];
+                let hours = digits(&mut bytes, 2).ok_or_else(invalid)?;
+                if let [b'\'' | b':', rest @ ..] = bytes {
+                    bytes = rest;
+                }
+                let minutes = digits(&mut bytes, 2).unwrap_or(0);
+                Some(sign * (hours * 60 + minutes) as i16)
+            }
+            Some(_) => return Err(invalid()),
+        };
+        date.validate()?;
+        Ok(date)
+    }
+}
+
+impl Display for PdfDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "D:{:04}{:02}{:02}{:02}{:02}{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+        match self.utc_offset {
+            None => Ok(()),
+            Some(0) => write!(f, "Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{sign}{:02}'{:02}'", offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+impl TryFrom<&PdfString> for PdfDate {
+    type Error = PdfError;
+
+    fn try_from(string: &PdfString) -> PdfResult<Self> {
+        string.to_text()?.parse()
+    }
+}
+
+impl From<PdfDate> for PdfString {
+    fn from(date: PdfDate) -> Self {
+        PdfString::from_raw_bytes(date.to_string().as_bytes())
+    }
+}
+
+impl PdfDict {
+    /// The date at `key`, `None` if absent and an error if not a string or not a date.
+    pub fn get_date(&self, key: impl AsRef<[u8]>) -> PdfResult<Option<PdfDate>> {
+        self.get(key)
+            .map(|o| PdfDate::try_from(o.as_string_ref()?))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PdfErrorKind, PdfName};
+
+    use super::*;
+
+    fn date(s: &str) -> PdfDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parsing() {
+        let full = PdfDate::new(1998, 12, 23, 19, 52, 0).unwrap();
+        assert_eq!(
+            date("D:19981223195200-08'00'"),
+            full.with_utc_offset(-8 * 60)
+        );
+        assert_eq!(date("D:1998"), PdfDate::new(1998, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(
+            date("D:199812"),
+            PdfDate::new(1998, 12, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(date("D:19981223195200Z"), full.with_utc_offset(0));
+        assert_eq!(
+            date("D:19981223195200+05'30"),
+            full.with_utc_offset(5 * 60 + 30)
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let full = PdfDate::new(1998, 12, 23, 19, 52, 0).unwrap();
+        for (s, offset) in [
+            ("19981223195200", None),
+            ("D:19981223195200Z00'00'", Some(0)),
+            ("D:19981223195200+0530", Some(330)),
+            ("D:19981223195200+05:30", Some(330)),
+            ("D:19981223195200-08", Some(-480)),
+            (" D:19981223195200-08'00' ", Some(-480)),
+        ] {
+            assert_eq!(
+                date(s),
+                PdfDate {
+                    utc_offset: offset,
+                    ..full
+                },
+                "{s}"
+            );
+        }
+        let y2k = date("D:191001231235959");
+        assert_eq!((y2k.year, y2k.month, y2k.day), (2000, 12, 31));
+
+        for s in [
+            "",
+            "D:98",
+            "D:19981323",
+            "D:19980230",
+            "D:19981223195200X",
+            "D:1998+x",
+        ] {
+            let err = s.parse::<PdfDate>().unwrap_err();
+            assert_eq!(err.kind(), &PdfErrorKind::InvalidData, "{s}");
+        }
+    }
+
+    #[test]
+    fn timestamp() {
+        let date = date("D:19981223195200-08'00'");
+        assert_eq!(date.timestamp(), 914_471_520);
+        let utc = PdfDate::from_timestamp(date.timestamp());
+        assert_eq!(
+            utc,
+            PdfDate::new(1998, 12, 24, 3, 52, 0)
+                .unwrap()
+                .with_utc_offset(0)
+        );
+        assert_eq!(PdfDate::from_timestamp(0).to_string(), "D:19700101000000Z");
+        assert_eq!(PdfDate::from_timestamp(-1).year, 1969);
+        for seconds in [951_782_400, 4_107_542_399, -86_400 * 365] {
+            assert_eq!(PdfDate::from_timestamp(seconds).timestamp(), seconds);
+        }
+    }
+
+    #[test]
+    fn iso8601() {
+        let date = PdfDate::new(2024, 2, 29, 8, 5, 9).unwrap();
+        assert_eq!(
+            PdfDate::from_iso8601("2024-02-29T08:05:09.25-02:30"),
+            Ok(date.with_utc_offset(-150))
+        );
+        assert_eq!(
+            PdfDate::from_iso8601("2024-02-29T08:05Z"),
+            Ok(PdfDate {
+                second: 0,
+                ..date.with_utc_offset(0)
+            })
+        );
+        assert_eq!(
+            PdfDate::from_iso8601("2024-02"),
+            PdfDate::new(2024, 2, 1, 0, 0, 0)
+        );
+        assert_eq!(date.to_iso8601(), "2024-02-29T08:05:09");
+        assert_eq!(
+            date.with_utc_offset(330).to_iso8601(),
+            "2024-02-29T08:05:09+05:30"
+        );
+        for s in [
+            "2024-02-30",
+            "2024-02-29T08",
+            "24",
+            "2024-02-29T08:05+0530",
+            "2024/02",
+        ] {
+            assert!(PdfDate::from_iso8601(s).is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn writing() {
+        let date = PdfDate::new(2024, 2, 29, 8, 5, 9).unwrap();
+        assert_eq!(date.to_string(), "D:20240229080509");
+        assert_eq!(
+            date.with_utc_offset(-150).to_string(),
+            "D:20240229080509-02'30'"
+        );
+        let string = PdfString::from(date.with_utc_offset(60));
+        assert_eq!(PdfDate::try_from(&string), Ok(date.with_utc_offset(60)));
+
+        let mut dict = PdfDict::empty();
+        dict.insert(PdfName::from_raw_bytes(b"ModDate"), string);
+        assert_eq!(
+            dict.get_date(b"ModDate"),
+            Ok(Some(date.with_utc_offset(60)))
+        );
+        assert_eq!(dict.get_date(b"CreationDate"), Ok(None));
+    }
+}
@@ -1,12 +1,14 @@
-use std::collections::HashMap;
+use crate::{Parsable, Parser, pdf_error::*, strip_whitespace};
 
-use crate::{Parsable, parse, pdf_error::*, strip_whitespace};
+use super::{PdfArray, PdfName, PdfNull, PdfObject};
 
-use super::{PdfName, PdfNull, PdfObject};
-
-#[derive(Debug, PartialEq, Clone)]
+/// Dictionary keeping its entries in insertion order, so that documents are written back with
+/// their keys in the order they were read.
+///
+/// Lookups are linear, dictionaries being small in practice.
+#[derive(Debug, Clone, Default)]
 pub struct PdfDict {
-    data: HashMap<PdfName, PdfObject>,
+    data: Vec<(PdfName, PdfObject)>,
 }
 
 impl PdfDict {
@@ -16,53 +18,191 @@ impl PdfDict {
     };
 
     pub fn empty() -> Self {
-        PdfDict {
-            data: HashMap::new(),
+        PdfDict { data: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub fn contains_key(&self, key: impl AsRef<[u8]>) -> bool {
+        self.position(key.as_ref()).is_some()
+    }
+
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Option<&PdfObject> {
+        self.position(key.as_ref()).map(|i| &self.data[i].1)
+    }
+    pub fn get_mut(&mut self, key: impl AsRef<[u8]>) -> Option<&mut PdfObject> {
+        self.position(key.as_ref()).map(|i| &mut self.data[i].1)
+    }
+    pub fn get_or_null(&self, key: impl AsRef<[u8]>) -> &PdfObject {
+        self.get(key).unwrap_or(&Self::NULL)
+    }
+
+    /// The name at `key`, `None` if absent and a [`PdfErrorKind::WrongType`] error if not a name.
+    pub fn get_name(&self, key: impl AsRef<[u8]>) -> PdfResult<Option<&PdfName>> {
+        self.get(key).map(PdfObject::as_name_ref).transpose()
+    }
+    /// The integer at `key`, `None` if absent and a [`PdfErrorKind::WrongType`] error if not an
+    /// integer.
+    pub fn get_int(&self, key: impl AsRef<[u8]>) -> PdfResult<Option<i64>> {
+        self.get(key)
+            .map(|o| o.as_numeric_ref()?.to_i64())
+            .transpose()
+    }
+    /// The array at `key`, `None` if absent and a [`PdfErrorKind::WrongType`] error if not an
+    /// array.
+    pub fn get_array(&self, key: impl AsRef<[u8]>) -> PdfResult<Option<&PdfArray>> {
+        self.get(key).map(PdfObject::as_array_ref).transpose()
+    }
+    /// The dictionary at `key`, `None` if absent and a [`PdfErrorKind::WrongType`] error if not a
+    /// dictionary.
+    pub fn get_dict(&self, key: impl AsRef<[u8]>) -> PdfResult<Option<&PdfDict>> {
+        self.get(key).map(PdfObject::as_dict_ref).transpose()
+    }
+
+    /// Sets the value of `key`, returning the previous one. A new key is added last, an existing
+    /// one keeps its position.
+    pub fn insert(&mut self, key: PdfName, value: impl Into<PdfObject>) -> Option<PdfObject> {
+        let value = value.into();
+        match self.position(key.bytes()) {
+            Some(i) => Some(std::mem::replace(&mut self.data[i].1, value)),
+            None => {
+                self.data.push((key, value));
+                None
+            }
+        }
+    }
+    /// Removes `key`, keeping the order of the other entries.
+    pub fn remove(&mut self, key: impl AsRef<[u8]>) -> Option<PdfObject> {
+        self.position(key.as_ref()).map(|i| self.data.remove(i).1)
+    }
+
+    pub fn entry(&mut self, key: PdfName) -> PdfDictEntry<'_> {
+        let index = self.position(key.bytes());
+        PdfDictEntry {
+            dict: self,
+            key,
+            index,
         }
     }
-    pub fn get(&self, key: &PdfName) -> Option<&PdfObject> {
-        self.data.get(key)
+
+    /// Entries in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&PdfName, &PdfObject)> {
+        self.data.iter().map(|(k, v)| (k, v))
+    }
+    pub fn keys(&self) -> impl Iterator<Item = &PdfName> {
+        self.data.iter().map(|(k, _)| k)
+    }
+    pub fn values(&self) -> impl Iterator<Item = &PdfObject> {
+        self.data.iter().map(|(_, v)| v)
     }
 
-    pub fn get_or_null(&self, key: &PdfName) -> &PdfObject {
-        self.data.get(key).unwrap_or(&Self::NULL)
+    fn position(&self, key: &[u8]) -> Option<usize> {
+        self.data.iter().position(|(k, _)| k.bytes() == key)
+    }
+}
+
+// Two dictionaries are the same if they have the same entries, whatever their order.
+impl PartialEq for PdfDict {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl FromIterator<(PdfName, PdfObject)> for PdfDict {
+    fn from_iter<T: IntoIterator<Item = (PdfName, PdfObject)>>(iter: T) -> Self {
+        let mut dict = PdfDict::empty();
+        for (key, value) in iter {
+            dict.insert(key, value);
+        }
+        dict
+    }
+}
+
+impl IntoIterator for PdfDict {
+    type Item = (PdfName, PdfObject);
+    type IntoIter = std::vec::IntoIter<(PdfName, PdfObject)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+/// A key of a [`PdfDict`], present or not, see [`PdfDict::entry`].
+pub struct PdfDictEntry<'a> {
+    dict: &'a mut PdfDict,
+    key: PdfName,
+    index: Option<usize>,
+}
+
+impl<'a> PdfDictEntry<'a> {
+    pub fn key(&self) -> &PdfName {
+        &self.key
+    }
+    pub fn is_occupied(&self) -> bool {
+        self.index.is_some()
+    }
+
+    pub fn or_insert(self, default: impl Into<PdfObject>) -> &'a mut PdfObject {
+        self.or_insert_with(|| default)
+    }
+    pub fn or_insert_with<O: Into<PdfObject>>(
+        self,
+        default: impl FnOnce() -> O,
+    ) -> &'a mut PdfObject {
+        let index = match self.index {
+            Some(index) => index,
+            None => {
+                self.dict.data.push((self.key, default().into()));
+                self.dict.data.len() - 1
+            }
+        };
+        &mut self.dict.data[index].1
+    }
+    /// Calls `f` on the value if the key is present.
+    pub fn and_modify(self, f: impl FnOnce(&mut PdfObject)) -> Self {
+        if let Some(index) = self.index {
+            f(&mut self.dict.data[index].1);
+        }
+        self
     }
 }
 
 impl Parsable for PdfDict {
-    fn from_bytes(bytes: &[u8]) -> PdfResult<(Self, &[u8])> {
+    fn from_bytes_with<'a>(bytes: &'a [u8], parser: &mut Parser) -> PdfResult<(Self, &'a [u8])> {
         let [b'<', b'<', bytes @ ..] = bytes else {
-            return Err(PdfError::with_kind(PdfErrorKind::Parse));
+            return Err(PdfError::parse_at(bytes, "`<<`"));
         };
         let mut bytes = strip_whitespace(bytes);
-        let mut data = HashMap::new();
+        let mut dict = PdfDict::empty();
         while !bytes.is_empty() && !matches!(bytes, [b'>', b'>', ..]) {
-            let (key, key_bytes) = parse(bytes)?;
+            let (key, key_bytes) = PdfName::from_bytes(bytes)?;
             let key_bytes = strip_whitespace(key_bytes);
-            let (value, value_bytes) = parse(key_bytes)?;
+            let (value, value_bytes) = PdfObject::from_bytes_with(key_bytes, parser)?;
             let value_bytes = strip_whitespace(value_bytes);
 
-            data.insert(key, value);
+            dict.insert(key, value);
             bytes = value_bytes;
         }
 
         if !matches!(bytes, [b'>', b'>', ..]) {
-            return Err(PdfError::with_kind(PdfErrorKind::Parse));
+            return Err(PdfError::parse_at(bytes, "`>>`"));
         }
 
         bytes = strip_whitespace(&bytes[2..]);
 
-        Ok((Self { data }, bytes))
+        Ok((dict, bytes))
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::collections::HashMap;
-
     use crate::{
-        parse,
+        PdfArray, PdfErrorKind, parse,
         types::{PdfName, PdfNumeric, PdfString},
     };
 
@@ -83,7 +223,7 @@ mod tests {
                                 >>
                             >>";
         let expected = PdfDict {
-            data: HashMap::from_iter([
+            data: Vec::from_iter([
                 (
                     PdfName::from_raw_bytes(b"Type"),
                     PdfName::from_raw_bytes(b"Example").into(),
@@ -107,7 +247,7 @@ mod tests {
                 (
                     PdfName::from_raw_bytes(b"Subdictionary"),
                     PdfDict {
-                        data: HashMap::from_iter([
+                        data: Vec::from_iter([
                             (
                                 PdfName::from_raw_bytes(b"Item1"),
                                 PdfNumeric::PdfReal(0.4).into(),
@@ -131,4 +271,60 @@ mod tests {
 
         assert_eq!(parsed, Ok((expected, &[] as &[u8])))
     }
+
+    #[test]
+    fn insertion_order() {
+        let (mut dict, _) = parse::<PdfDict>(b"<< /Z 1 /A 2 /M 3 >>").unwrap();
+        let keys = |dict: &PdfDict| dict.keys().map(|k| k.bytes().to_vec()).collect::<Vec<_>>();
+        assert_eq!(keys(&dict), [b"Z", b"A", b"M"]);
+
+        assert_eq!(
+            dict.insert(PdfName::from_raw_bytes(b"A"), true),
+            Some(PdfNumeric::PdfInt(2).into())
+        );
+        dict.insert(PdfName::from_raw_bytes(b"B"), PdfNumeric::PdfInt(4));
+        assert!(dict.remove(b"Z").is_some());
+        assert_eq!(keys(&dict), [b"A", b"M", b"B"]);
+        assert_eq!(dict.len(), 3);
+
+        *dict.get_mut(b"M").unwrap() = PdfNumeric::PdfInt(5).into();
+        assert_eq!(dict.get_int(b"M"), Ok(Some(5)));
+        let reordered: PdfDict = dict.clone().into_iter().rev().collect();
+        assert_eq!(reordered, dict);
+    }
+
+    #[test]
+    fn entry() {
+        let mut dict = PdfDict::empty();
+        let count = PdfName::from_raw_bytes(b"Count");
+        dict.entry(count.clone()).or_insert(PdfNumeric::PdfInt(1));
+        dict.entry(count.clone())
+            .and_modify(|o| *o = PdfNumeric::PdfInt(2).into())
+            .or_insert(PdfNumeric::PdfInt(0));
+        assert_eq!(dict.get(&count), Some(&PdfNumeric::PdfInt(2).into()));
+        assert!(!dict.entry(PdfName::from_raw_bytes(b"Kids")).is_occupied());
+    }
+
+    #[test]
+    fn typed_getters() {
+        let (dict, _) =
+            parse::<PdfDict>(b"<< /Type /Page /Kids [1 0 R] /N 1.5 /D << >> >>").unwrap();
+        assert_eq!(
+            dict.get_name(b"Type"),
+            Ok(Some(&PdfName::from_raw_bytes(b"Page")))
+        );
+        assert_eq!(
+            dict.get_array(b"Kids"),
+            Ok(Some(&parse::<PdfArray>(b"[1 0 R]").unwrap().0))
+        );
+        assert_eq!(dict.get_dict(b"D"), Ok(Some(&PdfDict::empty())));
+        assert_eq!(dict.get_int(b"Missing"), Ok(None));
+        for err in [
+            dict.get_int(b"N").unwrap_err(),
+            dict.get_dict(b"Type").unwrap_err(),
+            dict.get_name(b"Kids").unwrap_err(),
+        ] {
+            assert_eq!(err.kind(), &PdfErrorKind::WrongType);
+        }
+    }
 }
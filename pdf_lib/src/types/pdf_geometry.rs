@@ -0,0 +1,183 @@
+use crate::pdf_error::*;
+
+use super::{PdfArray, PdfObject};
+
+/// Rectangle written `[llx lly urx ury]`, normalized so that `(llx, lly)` is its lower left
+/// corner whichever opposite corners the array gives (7.9.5).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PdfRect {
+    pub llx: f64,
+    pub lly: f64,
+    pub urx: f64,
+    pub ury: f64,
+}
+
+impl PdfRect {
+    pub fn new(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
+        Self {
+            llx: x1.min(x2),
+            lly: y1.min(y2),
+            urx: x1.max(x2),
+            ury: y1.max(y2),
+        }
+    }
+    pub fn width(&self) -> f64 {
+        self.urx - self.llx
+    }
+    pub fn height(&self) -> f64 {
+        self.ury - self.lly
+    }
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        (self.llx..=self.urx).contains(&x) && (self.lly..=self.ury).contains(&y)
+    }
+}
+
+impl TryFrom<&PdfArray> for PdfRect {
+    type Error = PdfError;
+
+    fn try_from(array: &PdfArray) -> PdfResult<Self> {
+        let [x1, y1, x2, y2] = array
+            .as_number_array()
+            .map_err(|e| e.expecting("a rectangle"))?;
+        Ok(Self::new(x1, y1, x2, y2))
+    }
+}
+
+impl TryFrom<&PdfObject> for PdfRect {
+    type Error = PdfError;
+
+    fn try_from(object: &PdfObject) -> PdfResult<Self> {
+        object.as_array_ref()?.try_into()
+    }
+}
+
+impl From<PdfRect> for PdfArray {
+    fn from(rect: PdfRect) -> Self {
+        PdfArray::from_numbers(&[rect.llx, rect.lly, rect.urx, rect.ury])
+    }
+}
+
+/// Transformation matrix written `[a b c d e f]`, mapping `(x, y)` to
+/// `(a x + c y + e, b x + d y + f)` (8.3.3).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PdfMatrix {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl PdfMatrix {
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+    pub const fn translate(tx: f64, ty: f64) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+    pub const fn scale(sx: f64, sy: f64) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    /// The transformation applying `self` then `other`, written `self × other` in the
+    /// specification.
+    pub fn then(&self, other: &PdfMatrix) -> Self {
+        Self {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            e: self.e * other.a + self.f * other.c + other.e,
+            f: self.e * other.b + self.f * other.d + other.f,
+        }
+    }
+
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// Smallest rectangle holding the transformed corners of `rect`.
+    pub fn apply_rect(&self, rect: &PdfRect) -> PdfRect {
+        let corners = [
+            self.apply(rect.llx, rect.lly),
+            self.apply(rect.urx, rect.lly),
+            self.apply(rect.llx, rect.ury),
+            self.apply(rect.urx, rect.ury),
+        ];
+        let (xs, ys): (Vec<_>, Vec<_>) = corners.into_iter().unzip();
+        let min = |v: &[f64]| v.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = |v: &[f64]| v.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        PdfRect::new(min(&xs), min(&ys), max(&xs), max(&ys))
+    }
+}
+
+impl Default for PdfMatrix {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl TryFrom<&PdfArray> for PdfMatrix {
+    type Error = PdfError;
+
+    fn try_from(array: &PdfArray) -> PdfResult<Self> {
+        let [a, b, c, d, e, f] = array
+            .as_number_array()
+            .map_err(|e| e.expecting("a matrix"))?;
+        Ok(Self { a, b, c, d, e, f })
+    }
+}
+
+impl TryFrom<&PdfObject> for PdfMatrix {
+    type Error = PdfError;
+
+    fn try_from(object: &PdfObject) -> PdfResult<Self> {
+        object.as_array_ref()?.try_into()
+    }
+}
+
+impl From<PdfMatrix> for PdfArray {
+    fn from(m: PdfMatrix) -> Self {
+        PdfArray::from_numbers(&[m.a, m.b, m.c, m.d, m.e, m.f])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PdfArray, PdfErrorKind, parse};
+
+    use super::*;
+
+    #[test]
+    fn rect() {
+        let (array, _) = parse::<PdfArray>(b"[612 792 0 0]").unwrap();
+        let rect = PdfRect::try_from(&array).unwrap();
+        assert_eq!(rect, PdfRect::new(0.0, 0.0, 612.0, 792.0));
+        assert_eq!((rect.width(), rect.height()), (612.0, 792.0));
+        assert_eq!(PdfArray::from(rect), parse(b"[0 0 612 792]").unwrap().0);
+
+        let (short, _) = parse::<PdfArray>(b"[0 0 1]").unwrap();
+        let err = PdfRect::try_from(&short).unwrap_err();
+        assert_eq!(err.kind(), &PdfErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn matrix() {
+        let (array, _) = parse::<PdfArray>(b"[2 0 0 2 10 20]").unwrap();
+        let scale_then_move = PdfMatrix::try_from(&array).unwrap();
+        assert_eq!(scale_then_move.apply(1.0, 1.0), (12.0, 22.0));
+        let composed = PdfMatrix::scale(2.0, 2.0).then(&PdfMatrix::translate(10.0, 20.0));
+        assert_eq!(composed, scale_then_move);
+        assert_eq!(PdfMatrix::IDENTITY.then(&composed), composed);
+
+        let rotate = PdfMatrix::new(0.0, 1.0, -1.0, 0.0, 0.0, 0.0);
+        let rect = rotate.apply_rect(&PdfRect::new(0.0, 0.0, 2.0, 1.0));
+        assert_eq!(rect, PdfRect::new(-1.0, 0.0, 0.0, 2.0));
+    }
+}
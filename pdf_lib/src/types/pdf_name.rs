@@ -1,9 +1,7 @@
 use core::str;
+use std::borrow::Cow;
 
-use crate::{
-    Parsable,
-    pdf_error::{PdfError, PdfErrorKind},
-};
+use crate::{Parsable, ParsableRef, pdf_error::*};
 
 use super::is_regular;
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -19,48 +17,114 @@ impl PdfName {
     pub fn bytes(&self) -> &[u8] {
         &self.data
     }
+
+    pub fn as_name_ref(&self) -> PdfNameRef<'_> {
+        PdfNameRef {
+            data: Cow::Borrowed(&self.data),
+        }
+    }
 }
 
-impl Parsable for PdfName {
-    fn from_bytes(mut bytes: &[u8]) -> Result<(Self, &[u8]), crate::pdf_error::PdfError> {
-        let err = Err(PdfError::with_kind(PdfErrorKind::Parse));
-        if !matches!(bytes.get(0), Some(b'/')) {
-            return err;
+/// Name borrowing the parsed input, only copied when it contains `#` escapes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PdfNameRef<'a> {
+    data: Cow<'a, [u8]>,
+}
+
+impl PdfNameRef<'_> {
+    pub fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.data, Cow::Borrowed(_))
+    }
+
+    pub fn into_owned(self) -> PdfName {
+        PdfName {
+            data: self.data.into_owned(),
+        }
+    }
+}
+
+impl AsRef<[u8]> for PdfName {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl From<PdfNameRef<'_>> for PdfName {
+    fn from(name: PdfNameRef<'_>) -> Self {
+        name.into_owned()
+    }
+}
+
+impl<'a> ParsableRef<'a> for PdfNameRef<'a> {
+    fn from_bytes_ref(bytes: &'a [u8]) -> PdfResult<(Self, &'a [u8])> {
+        if bytes.first() != Some(&b'/') {
+            return Err(PdfError::parse_at(bytes, "name"));
+        }
+        let body = &bytes[1..];
+        let end = body
+            .iter()
+            .position(|b| !is_regular(*b))
+            .unwrap_or(body.len());
+        let (raw, rest) = body.split_at(end);
+        if rest.first() == Some(&0) {
+            return Err(PdfError::parse_at(rest, "regular character"));
         }
-        bytes = &bytes[1..];
-        let mut data = Vec::new();
+        if !raw.contains(&b'#') {
+            return Ok((
+                Self {
+                    data: Cow::Borrowed(raw),
+                },
+                rest,
+            ));
+        }
+
+        let mut data = Vec::with_capacity(raw.len());
+        let mut bytes = raw;
         while let [byte, rest @ ..] = bytes {
             let mut rest = rest;
             let n = match *byte {
-                0 => {
-                    return Err(PdfError::with_kind(PdfErrorKind::Parse));
-                }
                 b'#' => {
-                    if let [l, r, rrest @ ..] = rest {
-                        if l.is_ascii_hexdigit() && r.is_ascii_hexdigit() {
-                            rest = rrest;
-                            let a = [*l, *r];
-                            u8::from_str_radix(unsafe { str::from_utf8_unchecked(&a) }, 16)?
-                        } else {
-                            return err;
-                        }
+                    if let [l, r, rrest @ ..] = rest
+                        && l.is_ascii_hexdigit()
+                        && r.is_ascii_hexdigit()
+                    {
+                        rest = rrest;
+                        let a = [*l, *r];
+                        u8::from_str_radix(unsafe { str::from_utf8_unchecked(&a) }, 16)?
                     } else {
-                        return err;
+                        return Err(PdfError::parse_at(
+                            &body[raw.len() - bytes.len()..],
+                            "two hexadecimal digits after `#`",
+                        ));
                     }
                 }
-                b if is_regular(b) => b,
-                _ => break,
+                b => b,
             };
             data.push(n);
             bytes = rest;
         }
-        Ok((Self { data }, bytes))
+        Ok((
+            Self {
+                data: Cow::Owned(data),
+            },
+            rest,
+        ))
+    }
+}
+
+impl Parsable for PdfName {
+    fn from_bytes(bytes: &[u8]) -> PdfResult<(Self, &[u8])> {
+        PdfNameRef::from_bytes_ref(bytes).map(|(name, rest)| (name.into_owned(), rest))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{parse, types::PdfName};
+    use crate::{PdfNameRef, parse, parse_ref, types::PdfName};
 
     #[test]
     fn parsing() {
@@ -103,8 +167,21 @@ mod tests {
         let parsed = parse("/Name]".as_bytes());
         assert_eq!(
             parsed,
-            Ok((PdfName::from_raw_bytes("Name".as_bytes()), &[b']']
-                as &[u8]))
+            Ok((PdfName::from_raw_bytes("Name".as_bytes()), b"]" as &[u8]))
         )
     }
+
+    #[test]
+    fn borrowed() {
+        let (name, rest) = parse_ref::<PdfNameRef>(b"/Type /Page").unwrap();
+        assert!(name.is_borrowed());
+        assert_eq!(name.bytes(), b"Type");
+        assert_eq!(rest, b" /Page");
+        let (name, _) = parse_ref::<PdfNameRef>(b"/Lime#20Green").unwrap();
+        assert!(!name.is_borrowed());
+        assert_eq!(name.into_owned(), PdfName::from_raw_bytes(b"Lime Green"));
+
+        let err = parse_ref::<PdfNameRef>(b"/A#4").unwrap_err();
+        assert_eq!(err.offset(), Some(2));
+    }
 }
@@ -3,37 +3,74 @@ use std::str::FromStr;
 
 use crate::{Parsable, pdf_error::*};
 
-use super::WHITESPACES;
+use super::is_regular;
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum PdfNumeric {
-    PdfInt(i32),
-    PdfReal(f32),
+    PdfInt(i64),
+    PdfReal(f64),
 }
 
-impl From<PdfNumeric> for i32 {
-    fn from(value: PdfNumeric) -> Self {
-        match value {
-            PdfNumeric::PdfInt(v) => v,
-            PdfNumeric::PdfReal(f) => f as i32,
+/// Largest integer magnitude an `f64` holds exactly.
+const MAX_EXACT_F64: i64 = 1 << f64::MANTISSA_DIGITS;
+
+impl PdfNumeric {
+    /// The integer value, failing with [`PdfErrorKind::WrongType`] for a real.
+    pub fn to_i64(&self) -> PdfResult<i64> {
+        match self {
+            PdfNumeric::PdfInt(i) => Ok(*i),
+            PdfNumeric::PdfReal(_) => {
+                Err(PdfError::with_kind(PdfErrorKind::WrongType).expecting("an integer"))
+            }
         }
     }
-}
-impl From<&PdfNumeric> for i32 {
-    fn from(value: &PdfNumeric) -> Self {
-        match value {
-            PdfNumeric::PdfInt(v) => *v,
-            PdfNumeric::PdfReal(f) => *f as i32,
+    /// The integer value as an offset or length, failing for a real or a negative integer.
+    pub fn to_usize(&self) -> PdfResult<usize> {
+        usize::try_from(self.to_i64()?).map_err(|_| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a non-negative integer")
+        })
+    }
+    pub fn to_u64(&self) -> PdfResult<u64> {
+        u64::try_from(self.to_i64()?).map_err(|_| {
+            PdfError::with_kind(PdfErrorKind::InvalidData).expecting("a non-negative integer")
+        })
+    }
+    /// The value as a real, failing for an integer too large to be represented exactly.
+    pub fn to_f64(&self) -> PdfResult<f64> {
+        match self {
+            PdfNumeric::PdfInt(i) if i.unsigned_abs() <= MAX_EXACT_F64 as u64 => Ok(*i as f64),
+            PdfNumeric::PdfInt(_) => Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                .expecting("an integer representable as a real")),
+            PdfNumeric::PdfReal(r) => Ok(*r),
         }
     }
 }
+
+macro_rules! try_from_impl {
+    ($($T:ty => $method:ident),+) => {$(
+        impl TryFrom<&PdfNumeric> for $T {
+            type Error = PdfError;
+
+            fn try_from(value: &PdfNumeric) -> PdfResult<Self> {
+                value.$method()
+            }
+        }
+    )+};
+}
+try_from_impl!(i64 => to_i64, usize => to_usize, u64 => to_u64, f64 => to_f64);
+
 impl From<i32> for PdfNumeric {
     fn from(value: i32) -> Self {
+        Self::PdfInt(value.into())
+    }
+}
+impl From<i64> for PdfNumeric {
+    fn from(value: i64) -> Self {
         Self::PdfInt(value)
     }
 }
-impl From<f32> for PdfNumeric {
-    fn from(value: f32) -> Self {
+impl From<f64> for PdfNumeric {
+    fn from(value: f64) -> Self {
         Self::PdfReal(value)
     }
 }
@@ -41,33 +78,29 @@ impl FromStr for PdfNumeric {
     type Err = PdfError;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        if let Ok(p) = value.parse::<i32>() {
+        if let Ok(p) = value.parse::<i64>() {
             Ok(Self::PdfInt(p))
-        } else if let Ok(p) = value.parse::<f32>() {
+        } else if let Ok(p) = value.parse::<f64>() {
             // No PostScript and no exponential notation
             if !value.contains('e') && !value.contains('#') {
                 Ok(Self::PdfReal(p))
             } else {
-                Err(PdfError {
-                    kind: PdfErrorKind::Parse,
-                })
+                Err(PdfError::with_kind(PdfErrorKind::Parse))
             }
         } else {
-            Err(PdfError {
-                kind: PdfErrorKind::Parse,
-            })
+            Err(PdfError::with_kind(PdfErrorKind::Parse))
         }
     }
 }
 
 impl Parsable for PdfNumeric {
     fn from_bytes(b: &[u8]) -> PdfResult<(Self, &[u8])> {
-        let first_token = b
-            .split(|b| WHITESPACES.contains(b))
-            .next()
-            .ok_or_else(|| PdfError::with_kind(PdfErrorKind::Parse))?;
-        let parsed = str::from_utf8(first_token)?.parse()?;
-        Ok((parsed, &b[first_token.len()..]))
+        let end = b.iter().position(|c| !is_regular(*c)).unwrap_or(b.len());
+        let parsed = str::from_utf8(&b[..end])
+            .map_err(PdfError::from)
+            .and_then(str::parse)
+            .map_err(|e| e.at(b).expecting("number"))?;
+        Ok((parsed, &b[end..]))
     }
 }
 
@@ -78,9 +111,7 @@ mod tests {
 
     #[test]
     fn parsing_str() {
-        let err = Err(PdfError {
-            kind: PdfErrorKind::Parse,
-        });
+        let err = Err(PdfError::with_kind(PdfErrorKind::Parse));
         let test_data: [(&str, PdfNumeric); 13] = [
             ("123", 123.into()),
             ("43445", 43445.into()),
@@ -107,9 +138,7 @@ mod tests {
     }
     #[test]
     fn parsing_bytes() {
-        let err = Err(PdfError {
-            kind: PdfErrorKind::Parse,
-        });
+        let err = Err(PdfError::with_kind(PdfErrorKind::Parse));
         let test_data: [(&[u8], PdfNumeric); 13] = [
             (b"123", 123.into()),
             (b"43445", 43445.into()),
@@ -152,5 +181,35 @@ mod tests {
             parse::<PdfNumeric>(b"0.4\n/"),
             Ok((0.4.into(), b"\n/" as &[u8]))
         );
+        assert_eq!(parse::<PdfNumeric>(b"7]"), Ok((7.into(), b"]" as &[u8])));
+    }
+
+    #[test]
+    fn wide_values() {
+        let (offset, _) = parse::<PdfNumeric>(b"5000000000").unwrap();
+        assert_eq!(offset.to_usize(), Ok(5_000_000_000));
+        assert_eq!(offset.to_u64(), Ok(5_000_000_000));
+        let (coordinate, _) = parse::<PdfNumeric>(b"595.2756").unwrap();
+        assert_eq!(coordinate.to_f64(), Ok(595.2756));
+    }
+
+    #[test]
+    fn checked_conversions() {
+        let real = PdfNumeric::PdfReal(3.7);
+        assert_eq!(
+            real.to_usize().unwrap_err().kind(),
+            &PdfErrorKind::WrongType
+        );
+        assert_eq!(
+            i64::try_from(&real).unwrap_err().kind(),
+            &PdfErrorKind::WrongType
+        );
+        let negative = PdfNumeric::PdfInt(-1);
+        assert_eq!(
+            u64::try_from(&negative).unwrap_err().kind(),
+            &PdfErrorKind::InvalidData
+        );
+        assert_eq!(f64::try_from(&negative), Ok(-1.0));
+        assert!(PdfNumeric::PdfInt(i64::MAX).to_f64().is_err());
     }
 }
@@ -1,12 +1,13 @@
 use core::str;
-use std::str::FromStr;
+use std::{borrow::Cow, str::FromStr};
 
-use crate::{Parsable, pdf_error::*};
+use crate::{Parsable, ParsableRef, ParseWarningKind, Parser, pdf_error::*};
 
 use super::{EOLS, WHITESPACES};
 
 #[allow(dead_code)]
-#[derive(Debug, PartialEq, Clone)]
+// Ordered by bytes, as the keys of name trees
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct PdfString {
     data: Vec<u8>,
 }
@@ -14,7 +15,7 @@ pub struct PdfString {
 impl PdfString {
     pub fn from_raw_bytes(bytes: &[u8]) -> Self {
         Self {
-            data: bytes.iter().copied().collect(),
+            data: bytes.to_vec(),
         }
     }
 
@@ -25,9 +26,7 @@ impl PdfString {
         let mut code = String::with_capacity(3);
         let last = s.get((s.len() - 1)..);
         if !last.is_some_and(|s| s.starts_with(')')) {
-            return Err(PdfError {
-                kind: PdfErrorKind::Parse,
-            });
+            return Err(PdfError::with_kind(PdfErrorKind::Parse));
         }
 
         for b in s[1..(s.len() - 1)].bytes() {
@@ -38,9 +37,9 @@ impl PdfString {
                         b
                     }
                     b')' => {
-                        pars = pars.checked_sub(1).ok_or(PdfError {
-                            kind: PdfErrorKind::Parse,
-                        })?;
+                        pars = pars
+                            .checked_sub(1)
+                            .ok_or(PdfError::with_kind(PdfErrorKind::Parse))?;
                         b
                     }
                     b'\\' => {
@@ -64,9 +63,7 @@ impl PdfString {
                     }
                     (3, true) | (_, false) => {
                         let Ok(code_value) = u8::from_str_radix(&code, 8) else {
-                            return Err(PdfError {
-                                kind: PdfErrorKind::Parse,
-                            });
+                            return Err(PdfError::with_kind(PdfErrorKind::Parse));
                         };
                         data.push(code_value);
                         code.clear();
@@ -97,9 +94,7 @@ impl PdfString {
         }
         if solidus && !code.is_empty() {
             let Ok(code_value) = u8::from_str_radix(&code, 8) else {
-                return Err(PdfError {
-                    kind: PdfErrorKind::Parse,
-                });
+                return Err(PdfError::with_kind(PdfErrorKind::Parse));
             };
             data.push(code_value);
         }
@@ -111,18 +106,14 @@ impl PdfString {
         if pars == 0 {
             Ok(Self { data })
         } else {
-            Err(PdfError {
-                kind: PdfErrorKind::Parse,
-            })
+            Err(PdfError::with_kind(PdfErrorKind::Parse))
         }
     }
     fn from_str_hexa(mut s: &str) -> PdfResult<Self> {
         let mut data = Vec::new();
         let last = s.get((s.len() - 1)..);
         if !last.is_some_and(|s| s.starts_with('>')) {
-            return Err(PdfError {
-                kind: PdfErrorKind::Parse,
-            });
+            return Err(PdfError::with_kind(PdfErrorKind::Parse));
         }
         s = &s[1..(s.len() - 1)];
         let chars = s
@@ -141,14 +132,27 @@ impl PdfString {
         Ok(Self { data })
     }
 
+    pub fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+
     pub fn len(&self) -> usize {
         self.data.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn as_string_ref(&self) -> PdfStringRef<'_> {
+        PdfStringRef {
+            data: Cow::Borrowed(&self.data),
+        }
+    }
+
     fn from_bytes_hexa(bytes: &[u8]) -> PdfResult<(PdfString, &[u8])> {
-        let e = Err(PdfError::with_kind(PdfErrorKind::Parse));
         let Some(right_bracket) = bytes.iter().position(|b| *b == b'>') else {
-            return e;
+            return Err(PdfError::parse_at(bytes, "`>`").input_ended());
         };
 
         let first_token = &bytes[..=right_bracket];
@@ -169,7 +173,7 @@ impl PdfString {
                         Ok(((l as char).to_digit(16).unwrap() * 16
                             + (r as char).to_digit(16).unwrap()) as u8)
                     } else {
-                        Err(PdfError::with_kind(PdfErrorKind::Parse))
+                        Err(PdfError::parse_at(bytes, "hexadecimal digits"))
                     }
                 })
                 .collect::<Result<Vec<_>, _>>()?;
@@ -177,7 +181,9 @@ impl PdfString {
         Ok((Self { data }, rest))
     }
 
-    fn from_bytes_literal(mut bytes: &[u8]) -> PdfResult<(PdfString, &[u8])> {
+    /// Parses a literal string, `nested` being false to end it at the first unescaped `)`.
+    fn from_bytes_literal(mut bytes: &[u8], nested: bool) -> PdfResult<(PdfString, &[u8])> {
+        let start = bytes;
         let mut data = Vec::new();
         let mut pars = 1;
         bytes = &bytes[1..];
@@ -193,14 +199,16 @@ impl PdfString {
                     b')'
                 }
                 b'(' => {
-                    pars += 1;
+                    if nested {
+                        pars += 1;
+                    }
                     b'('
                 }
                 b if EOLS.contains(&b) => {
-                    if b == b'\r' {
-                        if let [b'\n', rrest @ ..] = rest {
-                            rest = rrest;
-                        }
+                    if b == b'\r'
+                        && let [b'\n', rrest @ ..] = rest
+                    {
+                        rest = rrest;
                     }
                     b'\n'
                 }
@@ -261,10 +269,85 @@ impl PdfString {
             data.push(n);
             bytes = rest;
         }
+        if pars != 0 {
+            return Err(PdfError::parse_at(start, "`)`").input_ended());
+        }
         Ok((Self { data }, bytes))
     }
 }
 
+/// String borrowing the parsed input, only copied when it is hexadecimal or has escapes or
+/// carriage returns to decode.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PdfStringRef<'a> {
+    data: Cow<'a, [u8]>,
+}
+
+impl<'a> PdfStringRef<'a> {
+    pub fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.data, Cow::Borrowed(_))
+    }
+
+    pub fn into_owned(self) -> PdfString {
+        PdfString {
+            data: self.data.into_owned(),
+        }
+    }
+
+    fn from_bytes_literal(bytes: &'a [u8], nested: bool) -> PdfResult<(Self, &'a [u8])> {
+        match Self::borrow_literal(bytes, nested) {
+            Some(parsed) => Ok(parsed),
+            None => PdfString::from_bytes_literal(bytes, nested).map(|(s, b)| (s.into(), b)),
+        }
+    }
+
+    /// Borrows a literal string whose content is kept as is, `None` if it needs decoding.
+    fn borrow_literal(bytes: &'a [u8], nested: bool) -> Option<(Self, &'a [u8])> {
+        let mut pars = 1;
+        for (i, byte) in bytes.iter().enumerate().skip(1) {
+            match *byte {
+                b'\\' | b'\r' => return None,
+                b'(' if nested => pars += 1,
+                b')' => {
+                    pars -= 1;
+                    if pars == 0 {
+                        let data = Cow::Borrowed(&bytes[1..i]);
+                        return Some((Self { data }, &bytes[(i + 1)..]));
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+impl From<PdfString> for PdfStringRef<'_> {
+    fn from(string: PdfString) -> Self {
+        Self {
+            data: Cow::Owned(string.data),
+        }
+    }
+}
+
+impl From<PdfStringRef<'_>> for PdfString {
+    fn from(string: PdfStringRef<'_>) -> Self {
+        string.into_owned()
+    }
+}
+
 impl FromStr for PdfString {
     type Err = PdfError;
 
@@ -272,27 +355,36 @@ impl FromStr for PdfString {
         match s.chars().next() {
             Some('<') => Self::from_str_hexa(s),
             Some('(') => Self::from_str_literal(s),
-            _ => Err(PdfError {
-                kind: PdfErrorKind::Parse,
-            }),
+            _ => Err(PdfError::with_kind(PdfErrorKind::Parse)),
         }
     }
 }
 
 impl Parsable for PdfString {
-    fn from_bytes(b: &[u8]) -> PdfResult<(Self, &[u8])> {
+    fn from_bytes_with<'a>(b: &'a [u8], parser: &mut Parser) -> PdfResult<(Self, &'a [u8])> {
+        PdfStringRef::from_bytes_ref_with(b, parser).map(|(s, rest)| (s.into_owned(), rest))
+    }
+}
+
+impl<'a> ParsableRef<'a> for PdfStringRef<'a> {
+    fn from_bytes_ref_with(b: &'a [u8], parser: &mut Parser) -> PdfResult<(Self, &'a [u8])> {
         match b.first() {
-            Some(b'<') => Self::from_bytes_hexa(b),
-            Some(b'(') => Self::from_bytes_literal(b),
-            _ => Err(PdfError {
-                kind: PdfErrorKind::Parse,
+            Some(b'<') => PdfString::from_bytes_hexa(b).map(|(s, rest)| (s.into(), rest)),
+            Some(b'(') => Self::from_bytes_literal(b, true).or_else(|e| {
+                if !parser.is_lenient() {
+                    return Err(e);
+                }
+                let parsed = Self::from_bytes_literal(b, false)?;
+                parser.warn(ParseWarningKind::UnbalancedParentheses, b);
+                Ok(parsed)
             }),
+            _ => Err(PdfError::parse_at(b, "string")),
         }
     }
 }
 #[cfg(test)]
 mod tests {
-    use crate::{PdfString, parse};
+    use crate::{PdfString, PdfStringRef, parse, parse_ref};
 
     #[test]
     fn hexa() {
@@ -359,4 +451,20 @@ mod tests {
             assert_eq!(parsed_str, Ok(r), "S => {e:?}");
         }
     }
+
+    #[test]
+    fn borrowed() {
+        let (string, rest) = parse_ref::<PdfStringRef>(b"(a (nested) string)]").unwrap();
+        assert!(string.is_borrowed());
+        assert_eq!(string.bytes(), b"a (nested) string");
+        assert_eq!(rest, b"]");
+
+        let decoded: [&[u8]; 3] = [b"(tab\\t)", b"(cr\r\nlf)", b"<414243>"];
+        for input in decoded {
+            let (string, _) = parse_ref::<PdfStringRef>(input).unwrap();
+            assert!(!string.is_borrowed());
+            assert_eq!(Ok((string.into_owned(), &[] as &[u8])), parse(input));
+        }
+        assert!(parse_ref::<PdfStringRef>(b"(open").is_err());
+    }
 }
@@ -1,6 +1,9 @@
-use crate::{Parsable, pdf_error::*};
+use crate::{Parsable, ParsableRef, Parser, filter::Filter, pdf_error::*};
 
-use super::{PdfDict, PdfName, PdfNumeric, parse_indirect};
+use super::{
+    IndirectBody, PdfDict, PdfName, PdfNumeric, PdfObject, indirect_header, parse_indirect,
+    parse_indirect_body,
+};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct PdfStream {
@@ -13,15 +16,29 @@ impl PdfStream {
     pub fn with_len(info: PdfDict, length: usize, data: Vec<u8>) -> Self {
         PdfStream { info, length, data }
     }
+    /// Stream of `data`, setting the `/Length` of `info`.
+    pub fn new(mut info: PdfDict, data: Vec<u8>) -> Self {
+        info.insert(
+            PdfName::from_raw_bytes(b"Length"),
+            PdfNumeric::PdfInt(data.len() as i64),
+        );
+        Self::with_len(info, data.len(), data)
+    }
+    /// Stream of `data` encoded by `filter`, setting the `/Filter` and `/Length` of `info`.
+    pub(crate) fn encoded(mut info: PdfDict, data: &[u8], filter: Filter) -> PdfResult<Self> {
+        let encoded = filter
+            .encode(data.iter().copied().map(Ok), PdfDict::empty())
+            .collect::<PdfResult<_>>()?;
+        info.insert(PdfName::from_raw_bytes(b"Filter"), PdfName::from(&filter));
+        Ok(Self::new(info, encoded))
+    }
     pub fn with_data(info: PdfDict, data: Vec<u8>) -> PdfResult<Self> {
-        let &PdfNumeric::PdfInt(dict_len) = info
-            .get(&PdfName::from_raw_bytes(b"Length"))
+        let dict_len = info
+            .get(b"Length")
             .ok_or(PdfError::with_kind(PdfErrorKind::MissingStreamLength))?
             .as_numeric_ref()?
-        else {
-            Err(PdfError::with_kind(PdfErrorKind::WrongType))?
-        };
-        if dict_len as usize != data.len() {
+            .to_usize()?;
+        if dict_len != data.len() {
             Err(PdfError::with_kind(PdfErrorKind::InvalidData))?
         }
         Ok(PdfStream {
@@ -33,11 +50,149 @@ impl PdfStream {
     pub fn len(&self) -> usize {
         self.length
     }
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+    pub fn info(&self) -> &PdfDict {
+        &self.info
+    }
+    pub fn info_mut(&mut self) -> &mut PdfDict {
+        &mut self.info
+    }
+    /// The data as stored, still encoded by the stream's filters.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    /// The data with the stream's `/Filter`s undone, in order.
+    pub fn decoded_data(&self) -> PdfResult<Vec<u8>> {
+        let filters = self.info.get_or_null(b"Filter");
+        let params = self.info.get_or_null(b"DecodeParms");
+        let (filters, params) = match (filters.as_name_ref(), params.as_dict_ref()) {
+            (Ok(filter), params) => (vec![filter], vec![params.ok()]),
+            _ if filters.as_null_ref().is_ok() => return Ok(self.data.clone()),
+            _ => {
+                let filters = filters.as_array_ref()?;
+                let params = params.as_array_ref().ok();
+                let filters = filters
+                    .iter()
+                    .map(PdfObject::as_name_ref)
+                    .collect::<PdfResult<Vec<_>>>()?;
+                let params = (0..filters.len())
+                    .map(|i| params.and_then(|p| p.get(i)?.as_dict_ref().ok()))
+                    .collect();
+                (filters, params)
+            }
+        };
+        let mut data = self.data.clone();
+        for (filter, params) in filters.into_iter().zip(params) {
+            let filter =
+                Filter::try_from(filter).map_err(|e| e.expecting("a standard filter name"))?;
+            let params = params.cloned().unwrap_or_default();
+            data = filter
+                .decode(data.into_iter().map(Ok), params)
+                .collect::<PdfResult<_>>()?;
+        }
+        Ok(data)
+    }
+    /// Replaces the data, updating `/Length`.
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        *self = Self::new(std::mem::take(&mut self.info), data);
+    }
+    pub fn as_stream_ref(&self) -> PdfStreamRef<'_> {
+        PdfStreamRef {
+            info: self.info.clone(),
+            data: &self.data,
+        }
+    }
+}
+
+/// Stream whose data is borrowed from the parsed input.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PdfStreamRef<'a> {
+    info: PdfDict,
+    data: &'a [u8],
+}
+
+impl<'a> PdfStreamRef<'a> {
+    pub fn info(&self) -> &PdfDict {
+        &self.info
+    }
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub fn into_owned(self) -> PdfStream {
+        PdfStream::with_len(self.info, self.data.len(), self.data.to_vec())
+    }
+}
+
+impl From<PdfStreamRef<'_>> for PdfStream {
+    fn from(stream: PdfStreamRef<'_>) -> Self {
+        stream.into_owned()
+    }
 }
 impl Parsable for PdfStream {
-    fn from_bytes(bytes: &[u8]) -> PdfResult<(Self, &[u8])> {
-        let (indirect, b) = parse_indirect(bytes)?;
+    fn from_bytes_with<'a>(bytes: &'a [u8], parser: &mut Parser) -> PdfResult<(Self, &'a [u8])> {
+        let (indirect, b) = parse_indirect(bytes, parser)?;
         let stream = indirect.as_stream()?;
         Ok((stream, b))
     }
 }
+
+impl<'a> ParsableRef<'a> for PdfStreamRef<'a> {
+    fn from_bytes_ref_with(bytes: &'a [u8], parser: &mut Parser) -> PdfResult<(Self, &'a [u8])> {
+        let Some((indirect, false, rest)) = indirect_header(bytes) else {
+            return Err(PdfError::parse_at(bytes, "indirect object"));
+        };
+        match parse_indirect_body(rest, &indirect, parser)? {
+            (IndirectBody::Stream(info, data), rest) => Ok((Self { info, data }, rest)),
+            (IndirectBody::Object(_), _) => Err(PdfError::with_kind(PdfErrorKind::WrongType)
+                .at(bytes)
+                .expecting("stream")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PdfErrorKind, PdfStream, PdfStreamRef, parse, parse_ref};
+
+    #[test]
+    fn borrowed() {
+        let input = b"4 0 obj <</Length 5>> stream\nhello\nendstream endobj";
+        let (stream, rest) = parse_ref::<PdfStreamRef>(input).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(stream.data(), b"hello");
+        assert!(std::ptr::eq(stream.data(), &input[29..34]));
+        assert_eq!(Ok((stream.into_owned(), rest)), parse::<PdfStream>(input));
+
+        let err = parse_ref::<PdfStreamRef>(b"4 0 obj 42 endobj").unwrap_err();
+        assert_eq!(err.kind(), &PdfErrorKind::WrongType);
+    }
+
+    #[test]
+    fn decoded_data() {
+        let decoded = |input: &[u8]| parse::<PdfStream>(input).unwrap().0.decoded_data();
+        assert_eq!(
+            decoded(b"1 0 obj <</Length 3>> stream\nabc\nendstream endobj"),
+            Ok(b"abc".to_vec())
+        );
+        assert_eq!(
+            decoded(
+                b"1 0 obj <</Length 7 /Filter /ASCIIHexDecode>> stream\n616263>\nendstream endobj"
+            ),
+            Ok(b"abc".to_vec())
+        );
+        let chained = b"1 0 obj <</Length 15 /Filter [/ASCIIHexDecode /ASCIIHexDecode]>> stream\n3631363236333E>\nendstream endobj";
+        assert_eq!(decoded(chained), Ok(b"abc".to_vec()));
+        let err =
+            decoded(b"1 0 obj <</Length 3 /Filter /LZWDecode>> stream\nabc\nendstream endobj")
+                .unwrap_err();
+        assert!(matches!(err.kind(), PdfErrorKind::Filter(_)));
+    }
+}
@@ -0,0 +1,253 @@
+use crate::{PdfVersion, pdf_error::*};
+
+use super::PdfString;
+
+const UTF16_BOM: [u8; 2] = [0xfe, 0xff];
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+/// Starts and ends a language escape sequence in Unicode text strings (7.9.2.2.1).
+const LANGUAGE_ESCAPE: char = '\u{1b}';
+
+/// PDFDocEncoding codes 0x18 to 0x1F (Annex D).
+const PDF_DOC_ACCENTS: [char; 8] = ['˘', 'ˇ', 'ˆ', '˙', '˝', '˛', '˚', '˜'];
+/// PDFDocEncoding codes 0x80 to 0xA0, 0x9F being undefined (Annex D).
+const PDF_DOC_SYMBOLS: [Option<char>; 33] = [
+    Some('•'),
+    Some('†'),
+    Some('‡'),
+    Some('…'),
+    Some('—'),
+    Some('–'),
+    Some('ƒ'),
+    Some('⁄'),
+    Some('‹'),
+    Some('›'),
+    Some('−'),
+    Some('‰'),
+    Some('„'),
+    Some('“'),
+    Some('”'),
+    Some('‘'),
+    Some('’'),
+    Some('‚'),
+    Some('™'),
+    Some('ﬁ'),
+    Some('ﬂ'),
+    Some('Ł'),
+    Some('Œ'),
+    Some('Š'),
+    Some('Ÿ'),
+    Some('Ž'),
+    Some('ı'),
+    Some('ł'),
+    Some('œ'),
+    Some('š'),
+    Some('ž'),
+    None,
+    Some('€'),
+];
+
+/// The character of a PDFDocEncoding code, `None` for the codes the encoding leaves undefined.
+fn pdf_doc_char(code: u8) -> Option<char> {
+    match code {
+        0x18..=0x1f => Some(PDF_DOC_ACCENTS[usize::from(code - 0x18)]),
+        0x80..=0xa0 => PDF_DOC_SYMBOLS[usize::from(code - 0x80)],
+        0xad => None,
+        b'\t' | b'\n' | b'\r' | 0x20..=0x7e | 0xa1..=0xff => Some(char::from(code)),
+        _ => None,
+    }
+}
+
+fn pdf_doc_code(c: char) -> Option<u8> {
+    if let Ok(code) = u8::try_from(c)
+        && pdf_doc_char(code) == Some(c)
+    {
+        return Some(code);
+    }
+    (0x18..=0xa0).find(|code| pdf_doc_char(*code) == Some(c))
+}
+
+/// Removes the language escape sequences of decoded Unicode text.
+fn strip_language_escapes(text: &str) -> String {
+    text.split(LANGUAGE_ESCAPE).step_by(2).collect()
+}
+
+impl PdfString {
+    /// Decodes a text string (7.9.2.2): UTF-16BE or UTF-8 after their byte order marks,
+    /// PDFDocEncoding otherwise.
+    ///
+    /// Language escape sequences are dropped. PDFDocEncoding codes without a character are kept
+    /// as the code point of the same value.
+    pub fn to_text(&self) -> PdfResult<String> {
+        self.decode_text(false)
+    }
+
+    /// Decodes a text string as [`Self::to_text`], invalid UTF-16 or UTF-8 sequences and a
+    /// trailing odd UTF-16 byte becoming replacement characters.
+    pub fn to_text_lossy(&self) -> String {
+        self.decode_text(true)
+            .expect("lossy decoding does not fail")
+    }
+
+    fn decode_text(&self, lossy: bool) -> PdfResult<String> {
+        let bytes = self.bytes();
+        if let Some(utf16) = bytes.strip_prefix(&UTF16_BOM) {
+            if lossy {
+                let pairs = utf16.chunks_exact(2);
+                let odd = !pairs.remainder().is_empty();
+                let units = pairs.map(|pair| u16::from_be_bytes([pair[0], pair[1]]));
+                let mut text: String = char::decode_utf16(units)
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect();
+                if odd {
+                    text.push(char::REPLACEMENT_CHARACTER);
+                }
+                return Ok(strip_language_escapes(&text));
+            }
+            if utf16.len() % 2 != 0 {
+                return Err(PdfError::with_kind(PdfErrorKind::InvalidData)
+                    .expecting("an even number of UTF-16 bytes"));
+            }
+            let units = utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]));
+            let text = char::decode_utf16(units)
+                .collect::<Result<String, _>>()
+                .map_err(|_| {
+                    PdfError::with_kind(PdfErrorKind::InvalidData).expecting("valid UTF-16")
+                })?;
+            Ok(strip_language_escapes(&text))
+        } else if let Some(utf8) = bytes.strip_prefix(&UTF8_BOM) {
+            if lossy {
+                return Ok(strip_language_escapes(&String::from_utf8_lossy(utf8)));
+            }
+            Ok(strip_language_escapes(str::from_utf8(utf8)?))
+        } else {
+            Ok(bytes
+                .iter()
+                .map(|b| pdf_doc_char(*b).unwrap_or(char::from(*b)))
+                .collect())
+        }
+    }
+
+    /// Encodes `text` as a text string, in PDFDocEncoding when it can, otherwise in UTF-16BE.
+    pub fn from_text(text: &str) -> Self {
+        Self::encode_text(text, false)
+    }
+
+    /// Encodes `text` as a text string of a document of `version`: as [`Self::from_text`], but
+    /// in UTF-8 when it is shorter than UTF-16BE and the version, 2.0 or later, allows it.
+    pub fn from_text_in(text: &str, version: PdfVersion) -> Self {
+        Self::encode_text(text, version >= PdfVersion::new(2, 0))
+    }
+
+    fn encode_text(text: &str, allow_utf8: bool) -> Self {
+        let pdf_doc: Option<Vec<u8>> = text.chars().map(pdf_doc_code).collect();
+        if let Some(bytes) = pdf_doc
+            && !bytes.starts_with(&UTF16_BOM)
+            && !bytes.starts_with(&UTF8_BOM)
+        {
+            return Self::from_raw_bytes(&bytes);
+        }
+        let utf16_len = UTF16_BOM.len() + 2 * text.encode_utf16().count();
+        let utf8_len = UTF8_BOM.len() + text.len();
+        let bytes: Vec<u8> = if allow_utf8 && utf8_len < utf16_len {
+            UTF8_BOM.iter().copied().chain(text.bytes()).collect()
+        } else {
+            UTF16_BOM
+                .iter()
+                .copied()
+                .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+                .collect()
+        };
+        Self::from_raw_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PdfErrorKind, PdfString};
+
+    use super::*;
+
+    fn text(bytes: &[u8]) -> String {
+        PdfString::from_raw_bytes(bytes).to_text().unwrap()
+    }
+
+    #[test]
+    fn pdf_doc_encoding() {
+        assert_eq!(text(b"Hello, world"), "Hello, world");
+        assert_eq!(
+            text(b"\x80 \x8d\x84\x8e \xa0 \x93 \x18 \xe9"),
+            "• “—” € ﬁ ˘ é"
+        );
+        // Undefined codes are kept as is
+        assert_eq!(text(b"\x9f\x01"), "\u{9f}\u{1}");
+        for code in 0..=255 {
+            if let Some(c) = pdf_doc_char(code) {
+                assert_eq!(pdf_doc_code(c), Some(code));
+            }
+        }
+    }
+
+    #[test]
+    fn unicode() {
+        assert_eq!(text(b"\xfe\xff\x00A\x26\x3a"), "A☺");
+        assert_eq!(text(b"\xfe\xff\xd8\x3d\xde\x00"), "😀");
+        assert_eq!(text("\u{feff}ünïcödé".as_bytes()), "ünïcödé");
+        let err = PdfString::from_raw_bytes(b"\xfe\xff\x00")
+            .to_text()
+            .unwrap_err();
+        assert_eq!(err.kind(), &PdfErrorKind::InvalidData);
+        assert!(
+            PdfString::from_raw_bytes(b"\xfe\xff\xd8\x3d")
+                .to_text()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn lossy() {
+        let lossy = |bytes: &[u8]| PdfString::from_raw_bytes(bytes).to_text_lossy();
+        assert_eq!(lossy(b"\xfe\xff\x00A\xd8\x3d\x00B"), "A\u{fffd}B");
+        assert_eq!(lossy(b"\xfe\xff\x00A\x00"), "A\u{fffd}");
+        assert_eq!(lossy(b"\xef\xbb\xbfA\xffB"), "A\u{fffd}B");
+        assert_eq!(lossy(b"Plain \x85"), "Plain –");
+    }
+
+    #[test]
+    fn language_escapes() {
+        let bytes = b"\xfe\xff\x00\x1b\x00e\x00n\x00U\x00S\x00\x1b\x00H\x00i";
+        assert_eq!(text(bytes), "Hi");
+        assert_eq!(text(b"\xef\xbb\xbf\x1bfr\x1bSalut"), "Salut");
+    }
+
+    #[test]
+    fn from_text() {
+        assert_eq!(PdfString::from_text("Plain – ok").bytes(), b"Plain \x85 ok");
+        // PDFDocEncoding could be mistaken for a byte order mark
+        assert_eq!(
+            PdfString::from_text("þÿ").bytes(),
+            b"\xfe\xff\x00\xfe\x00\xff"
+        );
+        assert_eq!(
+            PdfString::from_text("日本").bytes(),
+            b"\xfe\xff\x65\xe5\x67\x2c"
+        );
+        let mostly_ascii = "ascii text with one ☺";
+        let encoded = PdfString::from_text(mostly_ascii);
+        assert!(encoded.bytes().starts_with(&UTF16_BOM));
+        let encoded = PdfString::from_text_in(mostly_ascii, PdfVersion::new(1, 7));
+        assert!(encoded.bytes().starts_with(&UTF16_BOM));
+        let encoded = PdfString::from_text_in(mostly_ascii, PdfVersion::new(2, 0));
+        assert!(encoded.bytes().starts_with(&UTF8_BOM));
+        assert_eq!(
+            PdfString::from_text_in("日本", PdfVersion::new(2, 0)).bytes(),
+            b"\xfe\xff\x65\xe5\x67\x2c"
+        );
+        for s in ["Plain – ok", "þÿ", "日本", mostly_ascii, "😀 emoji"] {
+            assert_eq!(PdfString::from_text(s).to_text().unwrap(), s);
+            let utf8 = PdfString::from_text_in(s, PdfVersion::new(2, 0));
+            assert_eq!(utf8.to_text().unwrap(), s);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::io::{self, Write};
+
+use super::*;
+
+/// Serialization in PDF syntax, the inverse of [`Parsable`].
+pub trait Writable {
+    fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()>;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes)
+            .expect("writing to a Vec does not fail");
+        bytes
+    }
+}
+
+impl Writable for bool {
+    fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(if *self { b"true" } else { b"false" })
+    }
+}
+
+impl Writable for PdfNull {
+    fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(b"null")
+    }
+}
+
+impl Writable for PdfNumeric {
+    /// Reals are written without exponent and with a fractional part, to be read back as reals.
+    fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        match self {
+            PdfNumeric::PdfInt(i) => write!(w, "{i}"),
+            PdfNumeric::PdfReal(r) if !r.is_finite() => w.write_all(b"0.0"),
+            PdfNumeric::PdfReal(r) if r.fract() == 0.0 => write!(w, "{r:.1}"),
+            PdfNumeric::PdfReal(r) => write!(w, "{r}"),
+        }
+    }
+}
+
+impl Writable for PdfName {
+    fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(b"/")?;
+        for &b in self.bytes() {
+            if is_regular(b) && b != b'#' && (0x21..0x7f).contains(&b) {
+                w.write_all(&[b])?;
+            } else {
+                write!(w, "#{b:02X}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Writable for PdfString {
+    /// Written as a literal string, or in hexadecimal when mostly made of binary data.
+    fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        let bytes = self.bytes();
+        let printable = |b: &u8| (0x20..0x7f).contains(b);
+        if bytes.iter().filter(|b| !printable(b)).count() * 4 > bytes.len() {
+            w.write_all(b"<")?;
+            for b in bytes {
+                write!(w, "{b:02X}")?;
+            }
+            return w.write_all(b">");
+        }
+        w.write_all(b"(")?;
+        for &b in bytes {
+            match b {
+                b'(' | b')' | b'\\' => w.write_all(&[b'\\', b])?,
+                b'\n' => w.write_all(b"\\n")?,
+                b'\r' => w.write_all(b"\\r")?,
+                b if printable(&b) => w.write_all(&[b])?,
+                b => write!(w, "\\{b:03o}")?,
+            }
+        }
+        w.write_all(b")")
+    }
+}
+
+impl Writable for PdfArray {
+    fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(b"[")?;
+        for (i, object) in self.iter().enumerate() {
+            if i > 0 {
+                w.write_all(b" ")?;
+            }
+            object.write_to(w)?;
+        }
+        w.write_all(b"]")
+    }
+}
+
+impl Writable for PdfDict {
+    fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(b"<<")?;
+        for (key, value) in self.iter() {
+            key.write_to(w)?;
+            w.write_all(b" ")?;
+            value.write_to(w)?;
+        }
+        w.write_all(b">>")
+    }
+}
+
+impl Writable for PdfStream {
+    /// The dictionary is written with a direct `/Length` matching the data.
+    fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        let mut info = self.info().clone();
+        info.insert(
+            PdfName::from_raw_bytes(b"Length"),
+            PdfNumeric::PdfInt(self.data().len() as i64),
+        );
+        info.write_to(w)?;
+        w.write_all(b"\nstream\n")?;
+        w.write_all(self.data())?;
+        w.write_all(b"\nendstream")
+    }
+}
+
+impl Writable for PdfObject {
+    /// Writes the value of the object, a reference as `object generation R`.
+    ///
+    /// The `object generation obj` wrapper of an indirect object is left to the document writer.
+    fn write_to<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        match self.kind() {
+            PdfObjectKind::Boolean(b) => b.write_to(w),
+            PdfObjectKind::Numeric(n) => n.write_to(w),
+            PdfObjectKind::String(s) => s.write_to(w),
+            PdfObjectKind::Name(n) => n.write_to(w),
+            PdfObjectKind::Array(a) => a.write_to(w),
+            PdfObjectKind::Dict(d) => d.write_to(w),
+            PdfObjectKind::Stream(s) => s.write_to(w),
+            PdfObjectKind::Null(n) => n.write_to(w),
+            PdfObjectKind::Ref => {
+                let reference = self.as_reference().expect("references have their numbers");
+                write!(w, "{} {} R", reference.object(), reference.generation())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let inputs: [&[u8]; 4] = [
+            b"<</Type /Page /Kids [1 0 R 2 0 R] /Rotate -90 /Scale 0.5 /Visible true>>",
+            b"[(a \\(nested\\) string\\n) <00FF10> /Lime#20Green null 3.0]",
+            b"<</Length 3>>\nstream\nabc\nendstream",
+            b"(\\\\ \\r \\001)",
+        ];
+        for input in inputs {
+            let (object, _) = parse::<PdfObject>(input).unwrap();
+            let written = object.to_bytes();
+            assert_eq!(parse::<PdfObject>(&written).unwrap().0, object);
+        }
+    }
+
+    #[test]
+    fn syntax() {
+        let (dict, _) = parse::<PdfDict>(b"<< /A  [ 1  2 ] /B /C#2FD >>").unwrap();
+        assert_eq!(dict.to_bytes(), b"<</A [1 2]/B /C#2FD>>");
+        assert_eq!(PdfNumeric::PdfReal(-0.25).to_bytes(), b"-0.25");
+        assert_eq!(
+            PdfNumeric::PdfReal(1e21).to_bytes(),
+            b"1000000000000000000000.0"
+        );
+        assert_eq!(
+            PdfString::from_raw_bytes(&[0, 1, 2]).to_bytes(),
+            b"<000102>"
+        );
+        assert_eq!(PdfObject::reference(12, 0).to_bytes(), b"12 0 R");
+    }
+}